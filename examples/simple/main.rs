@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
-use metrics_evaluation::{MapResolver, Value};
-use sprintf::{parser::parse_format_string, printer::sprintf};
+use sprintf::{
+    parser::parse_format_string,
+    printer::sprintf,
+    value::{MapResolver, Value},
+};
 
 fn main() {
     let s = r#""Hello, %s - this is test number %d in %.06fs having 0x%02X%% matches and %06d zeroes", user.name, user.tries, test.seconds, test.percent, test.zeroes"#;
@@ -0,0 +1,50 @@
+/* C interface of the sprintf crate, built by `cargo rustc --lib --crate-type cdylib --features ffi`. */
+#ifndef SPRINTF_H
+#define SPRINTF_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes returned by all functions. sprintf_last_error() describes the last failure. */
+#define SPRINTF_OK 0
+#define SPRINTF_NULL_POINTER 1
+#define SPRINTF_INVALID_UTF8 2
+#define SPRINTF_PARSE_ERROR 3
+#define SPRINTF_ARITY_MISMATCH 4
+#define SPRINTF_UNRESOLVED_VARIABLE 5
+#define SPRINTF_TYPE_MISMATCH 6
+#define SPRINTF_ERROR 99
+
+/* A parsed template. */
+typedef struct SprintfTemplate SprintfTemplate;
+
+/* A named value: the string, unless it is NULL, else the number. */
+typedef struct SprintfValue {
+    const char *name;
+    const char *string;
+    double number;
+} SprintfValue;
+
+/* Parses a statement like `"%s took %.2fs", user, took` into *template. */
+int sprintf_parse(const char *statement, SprintfTemplate **template_);
+
+/* Prints the template with `count` values into *output, to be released by sprintf_string_free(). */
+int sprintf_format(const SprintfTemplate *template_, const SprintfValue *values, size_t count, char **output);
+
+/* Releases a template, does nothing for NULL. */
+void sprintf_free(SprintfTemplate *template_);
+
+/* Releases an output of sprintf_format(), does nothing for NULL. */
+void sprintf_string_free(char *s);
+
+/* The last failure of the calling thread, valid until the next failing call. */
+const char *sprintf_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
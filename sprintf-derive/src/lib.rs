@@ -0,0 +1,279 @@
+//! `#[derive(SprintfResolver)]` for the `sprintf` crate, re-exported by it with the `derive` feature.
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Spacing, Span, TokenStream, TokenTree};
+
+type Result<T> = std::result::Result<T, (String, Span)>;
+
+/// Implements `sprintf::fields::Field` for a struct with named fields, so its fields are resolved
+/// by name through a `sprintf::fields::FieldResolver`. Fields of nested structs deriving it, too,
+/// are resolved by dotted paths like `user.name`, and by their own path as a whole for `%v`. Fields
+/// marked `#[sprintf(skip)]` are left out.
+/// ```
+/// use sprintf::{fields::FieldResolver, parser::parse_format_string, printer::sprintf, SprintfResolver};
+///
+/// #[derive(SprintfResolver)]
+/// struct User<'a> {
+///     name: &'a str,
+///     #[sprintf(skip)]
+///     #[allow(dead_code)]
+///     password: String,
+/// }
+///
+/// #[derive(SprintfResolver)]
+/// struct Request<'a> {
+///     user: User<'a>,
+///     status: u16,
+///     took: Option<f64>,
+/// }
+///
+/// let request = Request {
+///     user: User {
+///         name: "foo",
+///         password: "secret".into(),
+///     },
+///     status: 404,
+///     took: Some(0.25),
+/// };
+/// let resolver = FieldResolver::new(&request);
+/// let parsed = parse_format_string(r#""%s: %d in %.2fs", user.name, status, took"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo: 404 in 0.25s");
+///
+/// let parsed = parse_format_string(r#""%s", user.password"#).unwrap();
+/// assert!(sprintf(&parsed, &resolver).is_err());
+///
+/// let parsed = parse_format_string(r#""%v", user"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "{foo}");
+///
+/// let parsed = parse_format_string(r#""%+v", user"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "{name:foo}");
+///
+/// // type parameters have to implement `Field` themselves
+/// #[derive(SprintfResolver)]
+/// struct Page<'a, T>
+/// where
+///     T: Clone,
+/// {
+///     title: &'a str,
+///     items: Vec<T>,
+/// }
+///
+/// let page = Page {
+///     title: "statuses",
+///     items: vec![request.status, 200],
+/// };
+/// let parsed = parse_format_string(r#""%s: %d of %v", title, items.0, items"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &FieldResolver::new(&page)).unwrap(), "statuses: 404 of [404 200]");
+/// ```
+#[proc_macro_derive(SprintfResolver, attributes(sprintf))]
+pub fn derive_resolver(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(expanded) => expanded,
+        Err((message, span)) => compile_error(&message, span),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream> {
+    let mut tokens = input.into_iter().peekable();
+    // attributes and visibility
+    let name = loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => match tokens.next() {
+                Some(TokenTree::Ident(name)) => break name,
+                _ => return Err(("Expected the name of the struct".into(), ident.span())),
+            },
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "enum" || ident.to_string() == "union" => {
+                return Err(("SprintfResolver can only be derived for structs".into(), ident.span()))
+            }
+            Some(_) => {}
+            None => return Err(("Expected a struct".into(), Span::call_site())),
+        }
+    };
+
+    let mut generics = Vec::new();
+    if matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == '<') {
+        tokens.next();
+        let mut depth = 1;
+        for token in tokens.by_ref() {
+            if let TokenTree::Punct(punct) = &token {
+                match punct.as_char() {
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => {}
+                }
+            }
+            if depth == 0 {
+                break;
+            }
+            generics.push(token);
+        }
+    }
+
+    let mut where_clause = TokenStream::new();
+    let fields = loop {
+        match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => break group.stream(),
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                return Err(("SprintfResolver needs named fields".into(), group.span()))
+            }
+            Some(token) => where_clause.extend([token]),
+            None => return Err(("SprintfResolver needs named fields".into(), name.span())),
+        }
+    };
+
+    let mut body = TokenStream::new();
+    let mut paths = Vec::new();
+    for (field, skip) in field_names(fields)? {
+        if skip {
+            continue;
+        }
+        let name = field.to_string();
+        let mut path = Literal::string(name.strip_prefix("r#").unwrap_or(&name));
+        path.set_span(field.span());
+        body.extend(code(&format!(
+            "::sprintf::fields::Field::collect(&self.{}, &::sprintf::fields::join(path, {}), values);",
+            field, path
+        )));
+        paths.push(path.to_string());
+    }
+    body.extend(code(&format!(
+        "::sprintf::fields::collect_composite(path, ::sprintf::fields::Composite::Map, [{}], values);",
+        paths.join(", ")
+    )));
+
+    let parameters = generic_parameters(&generics);
+    let type_generics: Vec<_> = parameters.iter().map(|(parameter, _)| parameter.as_str()).collect();
+    // like the derives of std, every type parameter has to implement the trait itself
+    let bounds: Vec<_> = parameters
+        .iter()
+        .filter(|(_, is_type)| *is_type)
+        .map(|(parameter, _)| format!("{}: ::sprintf::fields::Field", parameter))
+        .collect();
+    if !bounds.is_empty() {
+        match where_clause.clone().into_iter().last() {
+            None => where_clause.extend(code("where")),
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {}
+            Some(_) => where_clause.extend(code(",")),
+        }
+        where_clause.extend(code(&bounds.join(", ")));
+    }
+    let generics: TokenStream = generics.into_iter().collect();
+    let mut expanded = code(&format!(
+        "#[automatically_derived] impl<{}> ::sprintf::fields::Field for {}<{}> {}",
+        generics,
+        name,
+        type_generics.join(", "),
+        where_clause
+    ));
+    let mut method = code("fn collect(&self, path: &str, values: &mut ::sprintf::fields::Values)");
+    method.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+    expanded.extend([TokenTree::Group(Group::new(Delimiter::Brace, method))]);
+    Ok(expanded)
+}
+
+/// The names of the fields in the body of a struct, and whether they are marked `#[sprintf(skip)]`.
+fn field_names(fields: TokenStream) -> Result<Vec<(Ident, bool)>> {
+    let mut names = Vec::new();
+    let mut tokens = fields.into_iter().peekable();
+    while tokens.peek().is_some() {
+        let mut skip = false;
+        let name = loop {
+            match tokens.next() {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '#' => match tokens.next() {
+                    Some(TokenTree::Group(attribute)) => skip |= is_skip(attribute.stream())?,
+                    _ => return Err(("Expected an attribute".into(), punct.span())),
+                },
+                Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+                    // pub(crate)
+                    if matches!(tokens.peek(), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis)
+                    {
+                        tokens.next();
+                    }
+                }
+                Some(TokenTree::Ident(ident)) => break ident,
+                Some(token) => return Err(("Expected a field".into(), token.span())),
+                None => return Ok(names),
+            }
+        };
+        names.push((name, skip));
+
+        // skip the type, up to the next comma outside of generic arguments
+        let mut depth = 0;
+        let mut arrow = false;
+        for token in tokens.by_ref() {
+            if let TokenTree::Punct(punct) = &token {
+                match punct.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' => depth += 1,
+                    // the `>` of `->` doesn't close anything
+                    '>' if !arrow => depth -= 1,
+                    _ => {}
+                }
+                arrow = punct.as_char() == '-' && punct.spacing() == Spacing::Joint;
+            } else {
+                arrow = false;
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Whether the content of an attribute is `sprintf(skip)`. Fails on other `sprintf` attributes.
+fn is_skip(attribute: TokenStream) -> Result<bool> {
+    let mut tokens = attribute.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Group(arguments))) if ident.to_string() == "sprintf" => {
+            match arguments.stream().to_string().as_str() {
+                "skip" => Ok(true),
+                _ => Err(("Expected #[sprintf(skip)]".into(), arguments.span())),
+            }
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The generic parameters without their bounds and defaults, e.g. `'a` and `T` for
+/// `'a, T: Display`, and whether they are type parameters.
+fn generic_parameters(generics: &[TokenTree]) -> Vec<(String, bool)> {
+    let mut parameters = Vec::new();
+    let mut parameter = String::new();
+    let mut is_const = false;
+    let mut in_bounds = false;
+    let mut depth = 0;
+    for token in generics {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => {
+                let is_type = !is_const && !parameter.starts_with('\'');
+                parameters.push((std::mem::take(&mut parameter), is_type));
+                is_const = false;
+                in_bounds = false;
+            }
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => depth -= 1,
+            TokenTree::Punct(punct) if matches!(punct.as_char(), ':' | '=') && depth == 0 => in_bounds = true,
+            // the `'` of a lifetime is a joint punct
+            TokenTree::Punct(punct) if punct.as_char() == '\'' && !in_bounds => parameter.push('\''),
+            TokenTree::Ident(ident) if !in_bounds && ident.to_string() == "const" => is_const = true,
+            TokenTree::Ident(ident) if !in_bounds => parameter.push_str(&ident.to_string()),
+            _ => {}
+        }
+    }
+    if !parameter.is_empty() {
+        let is_type = !is_const && !parameter.starts_with('\'');
+        parameters.push((parameter, is_type));
+    }
+    parameters
+}
+
+fn code(source: &str) -> TokenStream {
+    source.parse().expect("generated code is valid")
+}
+
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+    let mut error = code("::core::compile_error!");
+    let mut arguments = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+    arguments.set_span(span);
+    error.extend([TokenTree::Group(arguments)]);
+    error
+}
@@ -0,0 +1,418 @@
+//! [sprintf!] formats Rust values with a format string of the `sprintf` crate, which is parsed
+//! and checked while compiling. [template_concat!] checks the parts of a template the same way.
+
+use proc_macro::{Delimiter, Group, Literal, Spacing, Span, TokenStream, TokenTree};
+use sprintf::{
+    parser::{parse_format, Entry, ParsedFormat, Placeholder},
+    specifiers::Conversion,
+};
+
+type Result<T> = std::result::Result<T, (String, Span)>;
+
+/// Formats its arguments like [sprintf::printer::sprintf] prints resolved variables, but with the
+/// format string parsed at compile time. Placeholders are bound to the arguments by position.
+///
+/// Invalid format strings, a wrong number of arguments and arguments which don't fit their
+/// placeholder fail to compile. `%d`, `%x`, `%f`, `%D`, `%h` and `%T` take numbers, see
+/// [sprintf::args::Numeric], `%s` and `%v` anything implementing [std::fmt::Display]. Evaluates to a
+/// `Result<String, SprintfError>`.
+/// ```
+/// use std::{collections::HashMap, time::Duration};
+///
+/// use sprintf_macros::sprintf;
+///
+/// let (x, y) = (42, 1.5);
+/// assert_eq!(sprintf!("x=%04d y=%.2f", x, y).unwrap(), "x=0042 y=1.50");
+///
+/// let user = "foo";
+/// let took = Duration::from_millis(2300);
+/// assert_eq!(sprintf!("%-5s|took %D (%x)", user, took, u64::MAX).unwrap(), "foo  |took 2.3s (ffffffffffffffff)");
+/// assert_eq!(sprintf!(r#"100%% "%{upper}s""#, user).unwrap(), r#"100% "FOO""#);
+///
+/// let ports = HashMap::from([(80u16, "http"), (443, "https")]);
+/// assert_eq!(
+///     sprintf!("%d ports, %v %v", HashMap::<u16, &str>::len(&ports), 1 << 2 < 5, <u8 as Default>::default()).unwrap(),
+///     "2 ports, true 0"
+/// );
+/// ```
+///
+/// ```compile_fail
+/// let (x, y) = (42, 1.5);
+/// sprintf_macros::sprintf!("x=%04d y=%.2f", x);
+/// ```
+///
+/// ```compile_fail
+/// sprintf_macros::sprintf!("%q", 42);
+/// ```
+///
+/// ```compile_fail
+/// sprintf_macros::sprintf!("%d", "42");
+/// ```
+#[proc_macro]
+pub fn sprintf(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(expanded) => expanded,
+        Err((message, span)) => compile_error(&message, span),
+    }
+}
+
+/// Merges several template literals, each followed by its own variables, into one
+/// [sprintf::parser::ParsedFormatString] to print with [sprintf::printer::sprintf].
+///
+/// Parts are separated by `;`, the variables may be expressions. Invalid format strings and parts
+/// with a wrong number of variables fail to compile. The template is built once, when it is used
+/// for the first time, and lent for as long as the program runs.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+/// use sprintf_macros::template_concat;
+///
+/// let mut values = HashMap::new();
+/// values.insert("module", Value::String("net".into()));
+/// values.insert("bytes", Value::Numeric(2048.0));
+/// let values: MapResolver = values.into();
+///
+/// let parsed = template_concat!("[%s] ", module; "received %d kB", bytes / 1024);
+/// assert_eq!(parsed.variables, vec!["module", "bytes / 1024"]);
+/// assert_eq!(sprintf(parsed, &values).unwrap(), "[net] received 2 kB");
+/// ```
+///
+/// ```compile_fail
+/// sprintf_macros::template_concat!("[%s] "; "received %d kB", bytes);
+/// ```
+#[proc_macro]
+pub fn template_concat(input: TokenStream) -> TokenStream {
+    match expand_template(input) {
+        Ok(expanded) => expanded,
+        Err((message, span)) => compile_error(&message, span),
+    }
+}
+
+fn expand_template(input: TokenStream) -> Result<TokenStream> {
+    let mut parts = vec![TokenStream::new()];
+    for token in input {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ';' => parts.push(TokenStream::new()),
+            _ => parts.last_mut().expect("there is always a part").extend([token]),
+        }
+    }
+    // a trailing `;`
+    if parts.len() > 1 && parts.last().is_some_and(TokenStream::is_empty) {
+        parts.pop();
+    }
+
+    let mut format = String::new();
+    let mut variables = Vec::new();
+    for part in parts {
+        let mut tokens = part.into_iter();
+        let (part_format, span) = match tokens.next() {
+            Some(token) => format_string(token)?,
+            None => return Err(("Missing format string".into(), Span::call_site())),
+        };
+        let arguments = split_arguments(tokens)?;
+        let entries = parse_format(&part_format).map_err(|error| (error.to_string(), span))?;
+        let placeholders = entries
+            .iter()
+            .filter(|entry| matches!(entry, Entry::Placeholder(_)))
+            .count();
+        if placeholders != arguments.len() {
+            let message = format!("{} placeholders but {} variables", placeholders, arguments.len());
+            return Err((message, span));
+        }
+        format.push_str(&part_format);
+        variables.extend(arguments.iter().map(|argument| format!("{:?}", argument.to_string())));
+    }
+    // the parts may only be valid on their own, e.g. with a `%` at the end of one
+    parse_format(&format).map_err(|error| (error.to_string(), Span::call_site()))?;
+
+    Ok(group(
+        Delimiter::Brace,
+        code(&format!(
+            "static TEMPLATE: ::std::sync::OnceLock<::sprintf::parser::ParsedFormatString> =
+                ::std::sync::OnceLock::new();
+            TEMPLATE.get_or_init(|| {{
+                ::sprintf::parser::ParsedFormatString::from_format({:?}, &[{}])
+                    .expect(\"checked by template_concat!\")
+            }})",
+            format,
+            variables.join(", ")
+        )),
+    ))
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream> {
+    let mut tokens = input.into_iter();
+    let (format, span) = match tokens.next() {
+        Some(token) => format_string(token)?,
+        None => return Err(("Missing format string".into(), Span::call_site())),
+    };
+    let arguments = split_arguments(tokens)?;
+
+    let entries = parse_format(&format).map_err(|error| (error.to_string(), span))?;
+    let placeholders: Vec<&Placeholder> = entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Placeholder(placeholder) => Some(placeholder),
+            _ => None,
+        })
+        .collect();
+    if placeholders.len() != arguments.len() {
+        let message = format!("{} placeholders but {} arguments", placeholders.len(), arguments.len());
+        return Err((message, span));
+    }
+    for placeholder in &placeholders {
+        // the expansion parses the placeholders again from their text
+        if Placeholder::try_from(placeholder.to_string()).as_ref() != Ok(*placeholder) {
+            return Err((format!("'{}' is not supported by sprintf!", placeholder), span));
+        }
+    }
+
+    let mut arguments = arguments.into_iter();
+    let mut body = TokenStream::new();
+    let mut index = 0;
+    for entry in &entries {
+        match entry {
+            Entry::Text(text) => body.extend(code(&format!("out.push_str({:?});", text))),
+            Entry::Style(styles) => {
+                let codes: Vec<_> = styles.iter().map(|style| style.code().to_string()).collect();
+                body.extend(code(&format!(
+                    "out.push_str({:?});",
+                    format!("\x1b[{}m", codes.join(";"))
+                )));
+            }
+            Entry::Placeholder(placeholder) => {
+                let Some(argument) = arguments.next() else {
+                    break;
+                };
+                body.extend(print_field(index, placeholder, argument));
+                index += 1;
+            }
+            // arguments are expressions, there is no variable to resolve
+            Entry::Section(_) | Entry::SectionEnd(_) => {
+                return Err((format!("'{}' is not supported by sprintf!", entry), span));
+            }
+        }
+    }
+    body.extend(code(
+        "::std::result::Result::<::std::string::String, ::sprintf::error::SprintfError>::Ok(out)",
+    ));
+
+    let size_hint = ParsedFormat::new(entries.clone(), Vec::new()).size_hint();
+    let mut expanded = TokenStream::new();
+    if !placeholders.is_empty() {
+        let formats: Vec<_> = placeholders
+            .iter()
+            .map(|placeholder| {
+                format!(
+                    "::sprintf::parser::Placeholder::try_from(::std::string::String::from({:?})).expect(\"checked by sprintf!\")",
+                    placeholder.to_string()
+                )
+            })
+            .collect();
+        expanded.extend(code(&format!(
+            "static SPRINTF_FORMATS: ::std::sync::OnceLock<[::sprintf::parser::Placeholder; {}]> =
+                ::std::sync::OnceLock::new();
+            let formats = SPRINTF_FORMATS.get_or_init(|| [{}]);",
+            placeholders.len(),
+            formats.join(", ")
+        )));
+    }
+    let binding = if entries.is_empty() { "let" } else { "let mut" };
+    expanded.extend(code(&format!(
+        "{} out = ::std::string::String::with_capacity({});",
+        binding, size_hint
+    )));
+    expanded.extend(code("'sprintf:"));
+    expanded.extend(group(Delimiter::Brace, body));
+    Ok(group(Delimiter::Brace, expanded))
+}
+
+/// Prints the argument for the placeholder at `index` into `out`, or breaks out with the error.
+fn print_field(index: usize, placeholder: &Placeholder, argument: TokenStream) -> TokenStream {
+    let span = argument
+        .clone()
+        .into_iter()
+        .next()
+        .map_or_else(Span::call_site, |token| token.span());
+    let mut argument = Group::new(Delimiter::Parenthesis, argument);
+    argument.set_span(span);
+
+    // filters print the value as string
+    let (path, mut parameters) = match (placeholder, placeholder.conversion()) {
+        (Placeholder::Modified(..), _) | (_, Conversion::Display | Conversion::String) => {
+            ("::sprintf::args::display_value", TokenStream::new())
+        }
+        _ => ("::sprintf::args::numeric_value", code(&format!("&formats[{}],", index))),
+    };
+    // type errors point at the argument
+    let mut value = respan(code(path), span);
+    parameters.extend(respan(code("&"), span));
+    parameters.extend([TokenTree::Group(argument)]);
+    let mut parameters = Group::new(Delimiter::Parenthesis, parameters);
+    parameters.set_span(span);
+    value.extend([TokenTree::Group(parameters)]);
+
+    let mut arguments = code(&format!("&formats[{}], &", index));
+    arguments.extend(value);
+    let mut field = code("match ::sprintf::printer::print_value");
+    field.extend(group(Delimiter::Parenthesis, arguments));
+    field.extend(code(
+        "{
+            ::std::result::Result::Ok(text) => out.push_str(&text),
+            ::std::result::Result::Err(error) => break 'sprintf ::std::result::Result::Err(error),
+        }",
+    ));
+    field
+}
+
+/// The unescaped content of the string literal `token`.
+fn format_string(token: TokenTree) -> Result<(String, Span)> {
+    let literal = match token {
+        // a literal passed through a macro_rules! macro
+        TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+            let mut tokens = group.stream().into_iter();
+            match (tokens.next(), tokens.next()) {
+                (Some(token), None) => return format_string(token),
+                _ => return Err(("Expected a string literal".into(), group.span())),
+            }
+        }
+        TokenTree::Literal(literal) => literal,
+        token => return Err(("Expected a string literal".into(), token.span())),
+    };
+    let span = literal.span();
+    let source = literal.to_string();
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let content = &raw[hashes..raw.len() - hashes];
+        if let Some(content) = content.strip_prefix('"').and_then(|content| content.strip_suffix('"')) {
+            return Ok((content.to_string(), span));
+        }
+    }
+    match source.strip_prefix('"').and_then(|content| content.strip_suffix('"')) {
+        Some(content) => unescape(content)
+            .map(|format| (format, span))
+            .ok_or_else(|| (format!("Unsupported escape sequence in {}", source), span)),
+        None => Err(("Expected a string literal".into(), span)),
+    }
+}
+
+/// Resolves the escape sequences of a Rust string literal.
+fn unescape(content: &str) -> Option<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            't' => result.push('\t'),
+            '0' => result.push('\0'),
+            c @ ('\\' | '\'' | '"') => result.push(c),
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+                result.push(char::from(u8::from_str_radix(&code, 16).ok()?));
+            }
+            'u' => {
+                chars.next_if_eq(&'{')?;
+                let code: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                result.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            '\n' => while chars.next_if(|c| c.is_whitespace()).is_some() {},
+            _ => return None,
+        }
+    }
+    Some(result)
+}
+
+/// Splits the tokens following the format string at their top level commas, which excludes the
+/// commas between generic arguments like those of `HashMap::<u8, u8>::new()`.
+fn split_arguments(tokens: impl Iterator<Item = TokenTree>) -> Result<Vec<TokenStream>> {
+    let mut tokens = tokens.peekable();
+    match tokens.next() {
+        None => return Ok(Vec::new()),
+        Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {}
+        Some(token) => return Err(("Expected a ',' after the format string".into(), token.span())),
+    }
+
+    let mut arguments = Vec::new();
+    let mut argument = TokenStream::new();
+    let mut previous: Option<TokenTree> = None;
+    // generic arguments, which can't follow a value like the `<` of a comparison does
+    let mut depth = 0;
+    for token in tokens {
+        if let TokenTree::Punct(punct) = &token {
+            let follows_punct = match &previous {
+                None => true,
+                // `x? < y` and the second `<` of `x << y` are no generics either
+                Some(TokenTree::Punct(previous)) => !matches!(previous.as_char(), '?' | '<' | '>'),
+                Some(_) => false,
+            };
+            let arrow = matches!(&previous, Some(TokenTree::Punct(previous))
+                if previous.as_char() == '-' && previous.spacing() == Spacing::Joint);
+            match punct.as_char() {
+                ',' if depth == 0 => {
+                    if argument.is_empty() {
+                        return Err(("Expected an argument".into(), punct.span()));
+                    }
+                    arguments.push(std::mem::take(&mut argument));
+                    previous = None;
+                    continue;
+                }
+                '<' if depth > 0 || follows_punct => depth += 1,
+                // the `>` of `->` doesn't close anything
+                '>' if depth > 0 && !arrow => depth -= 1,
+                _ => {}
+            }
+        }
+        previous = Some(token.clone());
+        argument.extend([token]);
+    }
+    if !argument.is_empty() {
+        arguments.push(argument);
+    }
+    Ok(arguments)
+}
+
+/// Parses generated code. Its variables and labels can't clash with those of the arguments.
+fn code(source: &str) -> TokenStream {
+    let tokens: TokenStream = source.parse().expect("generated code is valid");
+    respan(tokens, Span::mixed_site())
+}
+
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                token = TokenTree::Group(respanned);
+            }
+            token.set_span(span);
+            token
+        })
+        .collect()
+}
+
+fn group(delimiter: Delimiter, tokens: TokenStream) -> TokenStream {
+    let mut group = Group::new(delimiter, tokens);
+    group.set_span(Span::mixed_site());
+    TokenStream::from(TokenTree::Group(group))
+}
+
+fn compile_error(message: &str, span: Span) -> TokenStream {
+    let mut message = Literal::string(message);
+    message.set_span(span);
+    let mut error = respan(code("::core::compile_error!"), span);
+    let mut arguments = Group::new(Delimiter::Parenthesis, TokenTree::Literal(message).into());
+    arguments.set_span(span);
+    error.extend([TokenTree::Group(arguments)]);
+    error
+}
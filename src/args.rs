@@ -0,0 +1,86 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::{parser::Placeholder, specifiers::Conversion, value::Value};
+
+/// A Rust number which can be printed by numeric placeholders like `%d`, `%.2f` or `%D`, e.g. as
+/// argument of the `sprintf!` macro of the `sprintf-macros` crate.
+#[diagnostic::on_unimplemented(message = "`{Self}` can't be printed by a numeric placeholder like `%d`")]
+pub trait Numeric {
+    fn as_f64(&self) -> f64;
+
+    /// The exact value, if it is an integer.
+    fn as_i128(&self) -> Option<i128> {
+        None
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(impl Numeric for $t {
+            fn as_f64(&self) -> f64 {
+                *self as f64
+            }
+
+            fn as_i128(&self) -> Option<i128> {
+                i128::try_from(*self).ok()
+            }
+        })*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl Numeric for f32 {
+    fn as_f64(&self) -> f64 {
+        *self as f64
+    }
+}
+
+impl Numeric for f64 {
+    fn as_f64(&self) -> f64 {
+        *self
+    }
+}
+
+/// Seconds, e.g. for `%D`.
+impl Numeric for Duration {
+    fn as_f64(&self) -> f64 {
+        self.as_secs_f64()
+    }
+}
+
+impl<T: Numeric + ?Sized> Numeric for &T {
+    fn as_f64(&self) -> f64 {
+        (**self).as_f64()
+    }
+
+    fn as_i128(&self) -> Option<i128> {
+        (**self).as_i128()
+    }
+}
+
+/// `value` as [Value] for `format`. Integers are kept exact for `%d` and `%x`, see [Value::Integer].
+/// ```
+/// use std::time::Duration;
+///
+/// use sprintf::{args::numeric_value, printer::print_value};
+///
+/// let format = "%d".to_string().try_into().unwrap();
+/// assert_eq!(print_value(&format, &numeric_value(&format, &u64::MAX)).unwrap(), "18446744073709551615");
+/// let format = "%.1f".to_string().try_into().unwrap();
+/// assert_eq!(print_value(&format, &numeric_value(&format, &7u8)).unwrap(), "7.0");
+/// let format = "%D".to_string().try_into().unwrap();
+/// let took = Duration::from_secs(90);
+/// assert_eq!(print_value(&format, &numeric_value(&format, &took)).unwrap(), "1m 30s");
+/// ```
+pub fn numeric_value(format: &Placeholder, value: &impl Numeric) -> Value {
+    match (format.conversion(), value.as_i128()) {
+        (Conversion::Number | Conversion::Hex | Conversion::UpperHex, Some(integer)) => Value::Integer(integer),
+        _ => Value::Numeric(value.as_f64()),
+    }
+}
+
+/// `value` as [Value] for `%s` and `%v`.
+pub fn display_value(value: &impl Display) -> Value {
+    Value::String(value.to_string())
+}
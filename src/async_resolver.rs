@@ -0,0 +1,96 @@
+use std::{
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use crate::{
+    error::Result,
+    fields::{FieldResolver, Values},
+    parser::ParsedFormat,
+    printer::sprintf,
+    value::Value,
+};
+
+/// Resolves variables asynchronously, e.g. from a remote metrics store. Unlike a
+/// [crate::value::Resolver] it returns owned values.
+pub trait AsyncResolver {
+    fn resolve(&self, name: &str) -> impl Future<Output = Option<Value>>;
+}
+
+/// Like [sprintf], but with the variables resolved by an [AsyncResolver]. All variables of the
+/// template are resolved concurrently before it is printed, each only once.
+/// ```
+/// use std::{
+///     cell::RefCell,
+///     future::Future,
+///     pin::pin,
+///     task::{Context, Poll, Waker},
+/// };
+///
+/// use sprintf::{
+///     async_resolver::{sprintf_async, AsyncResolver},
+///     parser::parse_format_string,
+///     value::Value,
+/// };
+///
+/// struct Store {
+///     requests: RefCell<Vec<String>>,
+/// }
+///
+/// impl AsyncResolver for Store {
+///     async fn resolve(&self, name: &str) -> Option<Value> {
+///         self.requests.borrow_mut().push(name.to_string());
+///         match name {
+///             "host" => Some(Value::String("foo".into())),
+///             "req.total_ms" => Some(Value::Numeric(1234.0)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// fn block_on<F: Future>(future: F) -> F::Output {
+///     let mut future = pin!(future);
+///     loop {
+///         if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+///             return output;
+///         }
+///     }
+/// }
+///
+/// let store = Store { requests: RefCell::new(Vec::new()) };
+/// let parsed = parse_format_string(r#""%s took %.2fs (%{:-?}s)", host, req.total_ms / 1000, host.os"#).unwrap();
+/// assert_eq!(block_on(sprintf_async(&parsed, &store)).unwrap(), "foo took 1.23s (?)");
+/// assert_eq!(*store.requests.borrow(), vec!["host", "req.total_ms", "host.os"]);
+///
+/// let parsed = parse_format_string(r#""%s%{?req.total_ms} took %.2fs%{/req.total_ms}", host, req.total_ms / 1000"#).unwrap();
+/// assert_eq!(block_on(sprintf_async(&parsed, &store)).unwrap(), "foo took 1.23s");
+/// let parsed = parse_format_string(r#""%s%{?error} failed%{/error}", host"#).unwrap();
+/// assert_eq!(block_on(sprintf_async(&parsed, &store)).unwrap(), "foo");
+/// ```
+pub async fn sprintf_async(parsed: &ParsedFormat<'_>, resolver: &impl AsyncResolver) -> Result<String> {
+    let names = parsed.names();
+    let mut pending: Vec<_> = names
+        .iter()
+        .map(|name| Some(Box::pin(resolver.resolve(name))))
+        .collect();
+    let mut values = Values::new();
+    poll_fn(|cx| {
+        for (name, future) in names.iter().zip(pending.iter_mut()) {
+            if let Some(resolving) = future {
+                if let Poll::Ready(value) = resolving.as_mut().poll(cx) {
+                    *future = None;
+                    if let Some(value) = value {
+                        values.insert(name.to_string(), value);
+                    }
+                }
+            }
+        }
+        match pending.iter().all(Option::is_none) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    })
+    .await;
+
+    sprintf(parsed, &FieldResolver::from(values))
+}
@@ -0,0 +1,90 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{error::Result, parser::ParsedFormat, value::Resolver};
+
+/// Prints `parsed` once for every resolver, e.g. for every row of a table. The template is compiled
+/// only once, see [ParsedFormat::compile]. Every row fails on its own, unless the template itself
+/// can't be compiled, then all rows fail alike.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     batch::sprintf_batch,
+///     parser::{parse_format_string, ParsedFormat},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let rows = || {
+///     [Some("foo"), None, Some("bar")].into_iter().map(|user| {
+///         let mut values = HashMap::new();
+///         if let Some(user) = user {
+///             values.insert("user", Value::String(user.into()));
+///         }
+///         MapResolver::from(values)
+///     })
+/// };
+///
+/// let parsed = parse_format_string(r#""[%-4s]", user"#).unwrap();
+/// let printed = sprintf_batch(&parsed, rows());
+/// assert_eq!(printed[0].as_deref().unwrap(), "[foo ]");
+/// assert!(printed[1].is_err());
+/// assert_eq!(printed[2].as_deref().unwrap(), "[bar ]");
+///
+/// let unbound = ParsedFormat::new(parsed.entries, vec![]);
+/// assert!(sprintf_batch(&unbound, rows()).iter().all(Result::is_err));
+/// ```
+pub fn sprintf_batch<R: Resolver>(
+    parsed: &ParsedFormat,
+    resolvers: impl IntoIterator<Item = R>,
+) -> Vec<Result<String>> {
+    match parsed.compile() {
+        Ok(compiled) => resolvers
+            .into_iter()
+            .map(|resolver| compiled.sprintf(&resolver))
+            .collect(),
+        Err(error) => resolvers.into_iter().map(|_| Err(error.clone())).collect(),
+    }
+}
+
+/// Like [sprintf_batch], but prints the rows in parallel on the rayon thread pool. The results keep
+/// the order of the resolvers.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     batch::par_sprintf_batch,
+///     parser::parse_format_string,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let rows: Vec<MapResolver> = (0..1000)
+///     .map(|id| {
+///         let mut values = HashMap::new();
+///         values.insert("id", Value::Numeric(id as f64));
+///         values.into()
+///     })
+///     .collect();
+///
+/// let parsed = parse_format_string(r#""row %04d", id"#).unwrap();
+/// let printed = par_sprintf_batch(&parsed, rows);
+/// assert_eq!(printed.len(), 1000);
+/// assert_eq!(printed[42].as_deref().unwrap(), "row 0042");
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_sprintf_batch<R>(
+    parsed: &ParsedFormat,
+    resolvers: impl IntoParallelIterator<Item = R>,
+) -> Vec<Result<String>>
+where
+    R: Resolver + Send,
+{
+    let compiled = parsed.compile();
+    resolvers
+        .into_par_iter()
+        .map(|resolver| match &compiled {
+            Ok(compiled) => compiled.sprintf(&resolver),
+            Err(error) => Err(error.clone()),
+        })
+        .collect()
+}
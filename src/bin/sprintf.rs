@@ -0,0 +1,138 @@
+//! Prints a format string statement with values from the command line or a JSON document.
+
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use sprintf::{
+    fields::{Field, FieldResolver, Values},
+    parser::parse_format_string,
+    printer::sprintf,
+    value::Value,
+};
+
+const USAGE: &str = r#"Usage: sprintf [OPTIONS] STATEMENT
+
+Prints STATEMENT, e.g. '"%s took %.2fs", user.name, took', with the given values.
+
+Options:
+  -s, --set NAME=VALUE  Sets a variable, numbers are taken as numbers
+  -j, --json FILE       Takes the variables from a JSON document, `-` reads it from stdin.
+                        Nested objects are resolved by dotted paths like `user.name`.
+      --check           Only checks the statement, and the values if any are given
+  -h, --help            Prints this help"#;
+
+struct Args {
+    statement: String,
+    values: Values,
+    has_values: bool,
+    check: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut statement = None;
+    let mut values = Values::new();
+    let mut has_values = false;
+    let mut check = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--check" => check = true,
+            "-s" | "--set" => {
+                let assignment = args.next().ok_or("--set needs NAME=VALUE")?;
+                let (name, value) = assignment
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected NAME=VALUE instead of '{}'", assignment))?;
+                let value = match (value.parse(), value.parse()) {
+                    (Ok(integer), _) => Value::Integer(integer),
+                    (_, Ok(number)) => Value::Numeric(number),
+                    _ => Value::String(value.to_string()),
+                };
+                values.insert(name.to_string(), value);
+                has_values = true;
+            }
+            "-j" | "--json" => {
+                let file = args.next().ok_or("--json needs a FILE")?;
+                let document = match file.as_str() {
+                    "-" => {
+                        let mut document = String::new();
+                        io::stdin()
+                            .read_to_string(&mut document)
+                            .map_err(|error| format!("Unable to read stdin: {}", error))?;
+                        document
+                    }
+                    _ => fs::read_to_string(&file).map_err(|error| format!("Unable to read {}: {}", file, error))?,
+                };
+                let document: serde_json::Value =
+                    serde_json::from_str(&document).map_err(|error| format!("Invalid JSON in {}: {}", file, error))?;
+                document.collect("", &mut values);
+                has_values = true;
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => return Err(format!("Unknown option '{}'", arg)),
+            _ if statement.is_none() => statement = Some(arg),
+            _ => return Err(format!("Unexpected argument '{}'", arg)),
+        }
+    }
+
+    let statement = statement.ok_or("Missing STATEMENT")?;
+    Ok(Some(Args {
+        statement,
+        values,
+        has_values,
+        check,
+    }))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let parsed = match parse_format_string(&args.statement) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let resolver = FieldResolver::from(args.values);
+
+    if args.check {
+        if !args.has_values {
+            return ExitCode::SUCCESS;
+        }
+        return match parsed.validate(&resolver) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(errors) => {
+                for error in errors {
+                    match error.variable {
+                        Some(variable) => eprintln!("{}: {}", variable, error.error),
+                        None => eprintln!("{}", error.error),
+                    }
+                }
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    match sprintf(&parsed, &resolver) {
+        Ok(printed) => {
+            println!("{}", printed);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
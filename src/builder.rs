@@ -0,0 +1,98 @@
+use crate::{
+    error::Result,
+    parser::{check_arity, Entry, ParsedFormatString, Placeholder},
+    types::{FloatFormat, HexFormat, NumberFormat, StringFormat, Style},
+};
+
+/// Builds a [ParsedFormatString] in code, without string concatenation and re-parsing.
+///
+/// Every placeholder is followed by the [FormatBuilder::var] it is bound to.
+/// ```
+/// use sprintf::{builder::FormatBuilder, types::*};
+///
+/// let parsed = FormatBuilder::new()
+///     .text("x=")
+///     .number(NumberFormat {
+///         digits: Some(4),
+///         fill_zeros: true,
+///         ..Default::default()
+///     })
+///     .var("x")
+///     .text(", name=")
+///     .string(StringFormat::default())
+///     .var("name")
+///     .build()
+///     .unwrap();
+/// assert_eq!(parsed.to_string(), r#""x=%04d, name=%s", x, name"#);
+///
+/// assert!(FormatBuilder::new().text("x=").hex(HexFormat::default()).build().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct FormatBuilder {
+    entries: Vec<Entry<'static>>,
+    variables: Vec<String>,
+}
+
+impl FormatBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends literal text, merged with directly preceding text.
+    pub fn text(mut self, text: impl AsRef<str>) -> Self {
+        match self.entries.last_mut() {
+            Some(Entry::Text(last)) => last.to_mut().push_str(text.as_ref()),
+            _ => self.entries.push(Entry::Text(text.as_ref().to_string().into())),
+        }
+        self
+    }
+
+    /// Appends ANSI styles like `%[bold red]`.
+    pub fn style(mut self, styles: impl IntoIterator<Item = Style>) -> Self {
+        self.entries.push(Entry::Style(styles.into_iter().collect()));
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: Placeholder) -> Self {
+        self.entries.push(Entry::Placeholder(placeholder));
+        self
+    }
+
+    /// `%v`
+    pub fn display(self, format: StringFormat) -> Self {
+        self.placeholder(Placeholder::Display(format))
+    }
+
+    /// `%s`
+    pub fn string(self, format: StringFormat) -> Self {
+        self.placeholder(Placeholder::String(format))
+    }
+
+    /// `%d`
+    pub fn number(self, format: NumberFormat) -> Self {
+        self.placeholder(Placeholder::Number(format))
+    }
+
+    /// `%f`
+    pub fn float(self, format: FloatFormat) -> Self {
+        self.placeholder(Placeholder::Float(format))
+    }
+
+    /// `%x` or `%X`
+    pub fn hex(self, format: HexFormat) -> Self {
+        self.placeholder(Placeholder::Hex(format))
+    }
+
+    /// Binds the next placeholder to `variable`, which may also be an expression.
+    pub fn var(mut self, variable: impl Into<String>) -> Self {
+        self.variables.push(variable.into());
+        self
+    }
+
+    /// Fails if the number of placeholders differs from the number of variables.
+    pub fn build(self) -> Result<ParsedFormatString> {
+        check_arity(&self.entries, &self.variables)?;
+
+        Ok(ParsedFormatString::new(self.entries, self.variables))
+    }
+}
@@ -0,0 +1,601 @@
+//! Strict C99 `printf` conversions for templates parsed with [crate::options::Dialect::C99]. They
+//! print byte for byte what glibc prints for the same values, e.g. to compare the output with a
+//! service written in C.
+//!
+//! The full grammar `%[flags][width][.precision][length]conversion` is accepted, with the flags
+//! `-+ #0`, the lengths `hh h l ll j z t L` and the conversions `d i o u x X f F e E g G a A c s`.
+//! Integers are converted to the C type of their length first, with `long` being 64 bits, so
+//! `%hhd` prints 300 as `44` and `%u` prints -1 as `4294967295`. Floats print their exact binary
+//! value rounded half to even, so `%.2f` prints 2.675 as `2.67`. Widths and precisions count bytes.
+//!
+//! The extensions of this crate are rejected: `{...}` blocks, styles, the `^`, `'`, `*` and `!`
+//! flags and all other conversions. So are widths and precisions taken from an argument like `%*d`,
+//! as every placeholder binds exactly one variable, and `%n` and `%p`, as there are no pointers.
+//! [crate::options::PrintOptions] like the locale or rounding don't apply, escaping and redaction
+//! do.
+//!
+//! Where C prints bytes which aren't valid UTF-8, the output differs: `%c` without `l` only prints
+//! ASCII characters and the precision of `%s` never splits a character. `L` prints the `f64` value,
+//! which differs from a `long double` only for `%La`.
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use sprintf::{
+//!     options::{Dialect, ParseOptions},
+//!     parser::parse_format_string_with,
+//!     printer::sprintf,
+//!     value::{MapResolver, Value},
+//! };
+//!
+//! let options = ParseOptions {
+//!     dialect: Dialect::C99,
+//!     ..Default::default()
+//! };
+//! let values: MapResolver = HashMap::from([
+//!     ("price", Value::Numeric(2.675)),
+//!     ("tiny", Value::Numeric(1e-5)),
+//!     ("count", Value::Numeric(-1.0)),
+//!     ("id", Value::from(u64::MAX)),
+//!     ("name", Value::String("foo".into())),
+//! ])
+//! .into();
+//!
+//! let statement = r#""%.2f|%+08.3e|%g|%#.3G|%a|%-6.2s|%%", price, price, tiny, price, price, name"#;
+//! let parsed = parse_format_string_with(statement, &options).unwrap();
+//! assert_eq!(parsed.to_string(), statement);
+//! assert_eq!(
+//!     sprintf(&parsed, &values).unwrap(),
+//!     "2.67|+2.675e+00|1e-05|2.67|0x1.5666666666666p+1|fo    |%"
+//! );
+//!
+//! let parsed = parse_format_string_with(r#""%u %hhd %lu %#o %#hX", count, count, id, id, count"#, &options).unwrap();
+//! assert_eq!(
+//!     sprintf(&parsed, &values).unwrap(),
+//!     "4294967295 -1 18446744073709551615 037777777777 0XFFFF"
+//! );
+//!
+//! for extension in [r#""%{upper}s", name"#, r#""%^8s", name"#, r#""%*d", count"#, r#""%v", name"#, r#""%n", count"#] {
+//!     assert!(parse_format_string_with(extension, &options).is_err());
+//! }
+//! let err = parse_format_string_with(r#""%'d", count"#, &options).unwrap_err();
+//! assert_eq!(err.to_string(), "invalid conversion ''' at column 2");
+//! ```
+
+use std::fmt;
+
+use crate::{
+    error::{Result, SprintfError},
+    printer::{get_integer, get_number, get_string},
+    specifiers::Conversion,
+    value::Value,
+};
+
+/// The conversions of C99 besides `%n` and `%p`.
+pub const C99_CONVERSIONS: &[char] = &[
+    'd', 'i', 'o', 'u', 'x', 'X', 'f', 'F', 'e', 'E', 'g', 'G', 'a', 'A', 'c', 's',
+];
+
+const DEFAULT_PRECISION: usize = 6;
+
+/// The length modifier of a C99 conversion, i.e. the C type of the argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Length {
+    /// `hh`, `char`
+    Char,
+    /// `h`, `short`
+    Short,
+    /// `l`, `long`
+    Long,
+    /// `ll`, `long long`
+    LongLong,
+    /// `j`, `intmax_t`
+    IntMax,
+    /// `z`, `size_t`
+    Size,
+    /// `t`, `ptrdiff_t`
+    PtrDiff,
+    /// `L`, `long double`
+    LongDouble,
+}
+
+impl Length {
+    /// All lengths, the ones starting like a shorter one first.
+    const ALL: [Length; 8] = [
+        Length::Char,
+        Length::LongLong,
+        Length::Short,
+        Length::Long,
+        Length::IntMax,
+        Length::Size,
+        Length::PtrDiff,
+        Length::LongDouble,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Length::Char => "hh",
+            Length::Short => "h",
+            Length::Long => "l",
+            Length::LongLong => "ll",
+            Length::IntMax => "j",
+            Length::Size => "z",
+            Length::PtrDiff => "t",
+            Length::LongDouble => "L",
+        }
+    }
+}
+
+/// Bits of the integer type of `length` on 64 bit platforms.
+fn integer_bits(length: Option<Length>) -> u32 {
+    match length {
+        Some(Length::Char) => 8,
+        Some(Length::Short) => 16,
+        None => 32,
+        Some(_) => 64,
+    }
+}
+
+/// The flags of a C99 conversion, in any order and repeatable in the template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CFlags {
+    /// `-`
+    pub left: bool,
+    /// `+`
+    pub plus: bool,
+    /// ` `
+    pub space: bool,
+    /// `#`
+    pub alternate: bool,
+    /// `0`
+    pub zero: bool,
+}
+
+/// A C99 conversion like `%-+8.3le`, see the [module](self) documentation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CFormat {
+    pub flags: CFlags,
+    pub width: Option<u16>,
+    pub precision: Option<u16>,
+    pub length: Option<Length>,
+    /// One of [C99_CONVERSIONS].
+    pub conversion: char,
+}
+
+/// Result of scanning the text following the `%` of a placeholder.
+pub(crate) enum Scan {
+    /// The placeholder and its length in bytes.
+    Format(CFormat, usize),
+    /// Byte offset of a character which can't continue the placeholder.
+    Invalid(usize),
+    Unterminated,
+}
+
+/// Splits the leading digits off `s`.
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+impl CFormat {
+    /// Parses a single placeholder like `%-08.3lx`.
+    pub fn parse(placeholder: &str) -> Result<Self> {
+        match placeholder.strip_prefix('%').map(Self::scan) {
+            Some(Scan::Format(format, len)) if len == placeholder.len() - 1 => Ok(format),
+            _ => Err(SprintfError::InvalidPlaceholder(placeholder.into())),
+        }
+    }
+
+    /// Scans the placeholder at the start of `spec`, which follows the `%`.
+    pub(crate) fn scan(spec: &str) -> Scan {
+        let offset = |rest: &str| spec.len() - rest.len();
+        let mut flags = CFlags::default();
+        let mut rest = spec;
+        while let Some(flag) = rest.strip_prefix(['-', '+', ' ', '#', '0']) {
+            match rest.as_bytes()[0] {
+                b'-' => flags.left = true,
+                b'+' => flags.plus = true,
+                b' ' => flags.space = true,
+                b'#' => flags.alternate = true,
+                _ => flags.zero = true,
+            }
+            rest = flag;
+        }
+
+        let (digits, after) = split_digits(rest);
+        let width = match digits.parse() {
+            Ok(width) => Some(width),
+            Err(_) if digits.is_empty() => None,
+            Err(_) => return Scan::Invalid(offset(rest)),
+        };
+        rest = after;
+        let mut precision = None;
+        if let Some(after) = rest.strip_prefix('.') {
+            let (digits, after) = split_digits(after);
+            precision = match digits.parse() {
+                Ok(precision) => Some(precision),
+                // a lone dot is a precision of zero
+                Err(_) if digits.is_empty() => Some(0),
+                Err(_) => return Scan::Invalid(offset(after) - digits.len()),
+            };
+            rest = after;
+        }
+        let length = Length::ALL.into_iter().find(|length| rest.starts_with(length.as_str()));
+        if let Some(length) = length {
+            rest = &rest[length.as_str().len()..];
+        }
+
+        match rest.chars().next() {
+            Some(conversion) if C99_CONVERSIONS.contains(&conversion) => {
+                let format = CFormat {
+                    flags,
+                    width,
+                    precision,
+                    length,
+                    conversion,
+                };
+                Scan::Format(format, offset(rest) + conversion.len_utf8())
+            }
+            Some(_) => Scan::Invalid(offset(rest)),
+            None => Scan::Unterminated,
+        }
+    }
+
+    /// The built-in conversion closest to this one, e.g. [Conversion::Float] for `%e`.
+    pub fn closest_conversion(&self) -> Conversion {
+        match self.conversion {
+            'd' | 'i' | 'o' | 'u' => Conversion::Number,
+            'x' => Conversion::Hex,
+            'X' => Conversion::UpperHex,
+            's' => Conversion::String,
+            // a character code or a character
+            'c' => Conversion::Display,
+            _ => Conversion::Float,
+        }
+    }
+
+    /// Writes everything between the `%` and the conversion.
+    pub(crate) fn write_spec(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            (self.flags.left, '-'),
+            (self.flags.plus, '+'),
+            (self.flags.space, ' '),
+            (self.flags.alternate, '#'),
+            (self.flags.zero, '0'),
+        ];
+        for (_, flag) in flags.iter().filter(|(set, _)| *set) {
+            write!(f, "{}", flag)?;
+        }
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        match self.length {
+            Some(length) => write!(f, "{}", length.as_str()),
+            None => Ok(()),
+        }
+    }
+
+    /// Prints `value` like C's `printf` with this conversion.
+    pub fn print(&self, value: &Value) -> Result<String> {
+        match self.conversion {
+            'd' | 'i' | 'o' | 'u' | 'x' | 'X' => self.print_integer(value),
+            'c' => self.print_char(value),
+            's' => self.print_string(value),
+            _ => self.print_float(value),
+        }
+    }
+
+    /// The sign of a signed conversion.
+    fn sign(&self, negative: bool) -> &'static str {
+        match (negative, self.flags.plus, self.flags.space) {
+            (true, _, _) => "-",
+            (false, true, _) => "+",
+            (false, false, true) => " ",
+            (false, false, false) => "",
+        }
+    }
+
+    /// Pads `prefix` and `body` to the width, with zeros between them if `zeros` may be used.
+    fn pad(&self, prefix: &str, body: &str, zeros: bool) -> String {
+        let fill = usize::from(self.width.unwrap_or_default()).saturating_sub(prefix.len() + body.len());
+        match (self.flags.left, zeros && self.flags.zero) {
+            (true, _) => format!("{}{}{}", prefix, body, " ".repeat(fill)),
+            (false, true) => format!("{}{}{}", prefix, "0".repeat(fill), body),
+            (false, false) => format!("{}{}{}", " ".repeat(fill), prefix, body),
+        }
+    }
+
+    fn print_integer(&self, value: &Value) -> Result<String> {
+        let integer = get_integer(value)?;
+        // converted to the C type of the length
+        let shift = 128 - integer_bits(self.length);
+        let (negative, magnitude) = match self.conversion {
+            'd' | 'i' => {
+                let wrapped = (integer << shift) >> shift;
+                (wrapped < 0, wrapped.unsigned_abs())
+            }
+            _ => (false, integer as u128 & (u128::MAX >> shift)),
+        };
+        let mut digits = match self.conversion {
+            'o' => format!("{:o}", magnitude),
+            'x' => format!("{:x}", magnitude),
+            'X' => format!("{:X}", magnitude),
+            _ => magnitude.to_string(),
+        };
+        if let Some(precision) = self.precision {
+            if magnitude == 0 && precision == 0 {
+                digits.clear();
+            }
+            digits = format!("{:0>1$}", digits, usize::from(precision));
+        }
+        let prefix = match (self.conversion, self.flags.alternate) {
+            ('d' | 'i', _) => self.sign(negative),
+            ('o', true) if !digits.starts_with('0') => {
+                digits.insert(0, '0');
+                ""
+            }
+            ('x', true) if magnitude != 0 => "0x",
+            ('X', true) if magnitude != 0 => "0X",
+            _ => "",
+        };
+        // a precision disables zero padding
+        Ok(self.pad(prefix, &digits, self.precision.is_none()))
+    }
+
+    fn print_char(&self, value: &Value) -> Result<String> {
+        let c = match value {
+            Value::String(s) if s.chars().count() == 1 => s.chars().next(),
+            Value::Integer(_) | Value::Numeric(_) => {
+                let code = get_integer(value)?;
+                match self.length {
+                    Some(Length::Long) => u32::try_from(code).ok().and_then(char::from_u32),
+                    // converted to `unsigned char`
+                    _ => Some(code as u8).filter(u8::is_ascii).map(char::from),
+                }
+            }
+            _ => None,
+        };
+        let c = c.ok_or(SprintfError::TypeMismatch { expected: "character" })?;
+        Ok(self.pad("", c.encode_utf8(&mut [0; 4]), false))
+    }
+
+    fn print_string(&self, value: &Value) -> Result<String> {
+        let text = get_string(value)?;
+        let mut end = self
+            .precision
+            .map_or(text.len(), |precision| text.len().min(precision.into()));
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        Ok(self.pad("", &text[..end], false))
+    }
+
+    fn print_float(&self, value: &Value) -> Result<String> {
+        let number = get_number(value)?;
+        let uppercase = self.conversion.is_ascii_uppercase();
+        let sign = self.sign(number.is_sign_negative());
+        if !number.is_finite() {
+            let text = match (number.is_nan(), uppercase) {
+                (true, false) => "nan",
+                (true, true) => "NAN",
+                (false, false) => "inf",
+                (false, true) => "INF",
+            };
+            return Ok(self.pad(sign, text, false));
+        }
+
+        let number = number.abs();
+        let precision = self.precision.map_or(DEFAULT_PRECISION, usize::from);
+        let alternate = self.flags.alternate;
+        let (prefix, body) = match self.conversion.to_ascii_lowercase() {
+            'f' => (sign.to_string(), fixed(number, precision, alternate)),
+            'e' => (sign.to_string(), exponential(number, precision, alternate)),
+            'g' => (sign.to_string(), general(number, precision, alternate)),
+            _ => (
+                format!("{}0{}", sign, if uppercase { 'X' } else { 'x' }),
+                hex_float(number, self.precision.map(usize::from), alternate),
+            ),
+        };
+        let body = if uppercase { body.to_ascii_uppercase() } else { body };
+        Ok(self.pad(&prefix, &body, true))
+    }
+}
+
+/// Writes the placeholder in its C form, like `%-08.3lx`.
+impl fmt::Display for CFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%")?;
+        self.write_spec(f)?;
+        write!(f, "{}", self.conversion)
+    }
+}
+
+/// The exact value of a finite `f64` as `digits` times 10^`exponent`.
+struct ExactDecimal {
+    /// Decimal digits, most significant first.
+    digits: Vec<u8>,
+    exponent: i32,
+}
+
+impl ExactDecimal {
+    /// The exact value of the finite, non-negative `number`. Every `f64` has a finite decimal
+    /// expansion, as its fraction is a power of two.
+    fn new(number: f64) -> Self {
+        let bits = number.to_bits();
+        let fraction = bits & ((1 << 52) - 1);
+        let (mut mantissa, mut exponent) = match (bits >> 52) as i32 & 0x7ff {
+            // subnormal
+            0 => (fraction, -1074),
+            biased => (fraction | 1 << 52, biased - 1075),
+        };
+        if mantissa == 0 {
+            return Self {
+                digits: vec![0],
+                exponent: 0,
+            };
+        }
+        let zeros = mantissa.trailing_zeros();
+        mantissa >>= zeros;
+        exponent += zeros as i32;
+
+        // least significant first while multiplying
+        let mut digits: Vec<u8> = mantissa.to_string().bytes().rev().map(|digit| digit - b'0').collect();
+        // m * 2^-k is m * 5^k / 10^k
+        let (factor, times) = match exponent >= 0 {
+            true => (2, exponent),
+            false => (5, -exponent),
+        };
+        for _ in 0..times {
+            let mut carry = 0;
+            for digit in digits.iter_mut() {
+                let product = u32::from(*digit) * factor + carry;
+                *digit = (product % 10) as u8;
+                carry = product / 10;
+            }
+            if carry > 0 {
+                digits.push(carry as u8);
+            }
+        }
+        digits.reverse();
+        Self {
+            digits,
+            exponent: exponent.min(0),
+        }
+    }
+
+    /// Power of ten of the first digit.
+    fn leading(&self) -> i32 {
+        self.digits.len() as i32 - 1 + self.exponent
+    }
+
+    /// Rounds half to even to a multiple of 10^`exponent`, like glibc in the default rounding mode.
+    fn round(&mut self, exponent: i32) {
+        if exponent <= self.exponent {
+            let zeros = (self.exponent - exponent) as usize;
+            self.digits.resize(self.digits.len() + zeros, 0);
+            self.exponent = exponent;
+            return;
+        }
+        let dropped = (exponent - self.exponent) as usize;
+        self.exponent = exponent;
+        // otherwise the first dropped digit is a leading zero
+        let round_up = dropped <= self.digits.len() && {
+            let kept = self.digits.len() - dropped;
+            let first = self.digits[kept];
+            let odd = kept > 0 && self.digits[kept - 1] % 2 == 1;
+            first > 5 || (first == 5 && (self.digits[kept + 1..].iter().any(|&digit| digit > 0) || odd))
+        };
+        self.digits.truncate(self.digits.len().saturating_sub(dropped));
+        if round_up {
+            match self.digits.iter().rposition(|&digit| digit < 9) {
+                Some(position) => {
+                    self.digits[position] += 1;
+                    self.digits[position + 1..].fill(0);
+                }
+                None => {
+                    self.digits.fill(0);
+                    self.digits.insert(0, 1);
+                }
+            }
+        }
+        if self.digits.is_empty() {
+            self.digits.push(0);
+        }
+    }
+
+    fn text(&self) -> String {
+        self.digits.iter().map(|&digit| char::from(b'0' + digit)).collect()
+    }
+}
+
+/// `%f` of a finite, non-negative `number`.
+fn fixed(number: f64, precision: usize, alternate: bool) -> String {
+    let mut decimal = ExactDecimal::new(number);
+    decimal.round(-(precision as i32));
+    // at least one digit before the point
+    let digits = format!("{:0>1$}", decimal.text(), precision + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - precision);
+    match precision > 0 || alternate {
+        true => format!("{}.{}", integer, fraction),
+        false => integer.to_string(),
+    }
+}
+
+/// `count` significant digits of a finite, non-negative `number`, the power of ten of the first
+/// one and whether rounding carried it into the next power of ten, like 9.99 to 10.0.
+fn significant(number: f64, count: usize) -> (String, i32, bool) {
+    if number == 0.0 {
+        return ("0".repeat(count), 0, false);
+    }
+    let mut decimal = ExactDecimal::new(number);
+    let leading = decimal.leading();
+    decimal.round(leading + 1 - count as i32);
+    let carried = decimal.digits.len() > count;
+    decimal.digits.truncate(count);
+    (decimal.text(), leading + i32::from(carried), carried)
+}
+
+/// `%e` of a finite, non-negative `number`.
+fn exponential(number: f64, precision: usize, alternate: bool) -> String {
+    let (digits, exponent, _) = significant(number, precision + 1);
+    let (first, rest) = digits.split_at(1);
+    let point = if precision > 0 || alternate { "." } else { "" };
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}{}{}e{}{:02}", first, point, rest, sign, exponent.abs())
+}
+
+/// `%g` of a finite, non-negative `number`: `%e` for very large or small numbers, `%f` otherwise,
+/// both with `precision` significant digits and without trailing zeros unless `alternate`.
+fn general(number: f64, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+    let (_, exponent, carried) = significant(number, precision);
+    let printed = match exponent >= -4 && exponent < precision as i32 {
+        true => fixed(number, (precision as i32 - 1 - exponent) as usize, alternate),
+        // like glibc, which drops the zeros of `%#.3g` for 999.7 when rounding makes it switch to
+        // the exponent, printing `1.e+03` instead of `1.00e+03`
+        false if alternate && carried && exponent == precision as i32 => format!("1.e+{:02}", exponent),
+        false => exponential(number, precision - 1, alternate),
+    };
+    if alternate {
+        return printed;
+    }
+    let (mantissa, exponent) = printed.split_at(printed.find('e').unwrap_or(printed.len()));
+    match mantissa.contains('.') {
+        true => format!("{}{}", mantissa.trim_end_matches('0').trim_end_matches('.'), exponent),
+        false => printed,
+    }
+}
+
+/// `%a` of a finite, non-negative `number` without the `0x`. Without a precision, all hex digits
+/// of the fraction up to the last non-zero one are printed.
+fn hex_float(number: f64, precision: Option<usize>, alternate: bool) -> String {
+    let bits = number.to_bits();
+    let fraction = bits & ((1 << 52) - 1);
+    let (leading, exponent) = match (bits >> 52) as i32 & 0x7ff {
+        0 if fraction == 0 => (0, 0),
+        0 => (0, -1022),
+        biased => (1, biased - 1023),
+    };
+    let (leading, digits) = match precision {
+        None => (leading, format!("{:013x}", fraction).trim_end_matches('0').to_string()),
+        Some(precision) if precision >= 13 => (leading, format!("{:013x}{}", fraction, "0".repeat(precision - 13))),
+        Some(precision) => {
+            // rounded half to even, which may carry into the leading digit like in `0x2p+0`
+            let dropped = 52 - 4 * precision as u32;
+            let mut kept = (leading << (4 * precision)) | fraction >> dropped;
+            let rest = fraction & ((1 << dropped) - 1);
+            let half = 1 << (dropped - 1);
+            if rest > half || (rest == half && kept % 2 == 1) {
+                kept += 1;
+            }
+            let digits = match precision {
+                0 => String::new(),
+                _ => format!("{:01$x}", kept & ((1 << (4 * precision)) - 1), precision),
+            };
+            (kept >> (4 * precision), digits)
+        }
+    };
+    let point = if !digits.is_empty() || alternate { "." } else { "" };
+    format!("{}{}{}p{:+}", leading, point, digits, exponent)
+}
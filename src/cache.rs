@@ -0,0 +1,140 @@
+use std::sync::{Mutex, OnceLock};
+
+use crate::{
+    collections::Map,
+    error::Result,
+    parser::{parse_format_string, SharedFormatString},
+    printer::sprintf,
+    value::Resolver,
+};
+
+/// Capacity of the cache behind [sprintf_cached].
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Memoizes [parse_format_string] by the input string, for callers printing the same few templates
+/// over and over. Once `capacity` templates are cached, the least recently used one is dropped.
+/// Templates which fail to parse are not cached.
+/// ```
+/// use std::{collections::HashMap, sync::Arc};
+///
+/// use sprintf::{
+///     cache::FormatCache,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// let values: MapResolver = values.into();
+///
+/// let mut cache = FormatCache::new(2);
+/// let first = cache.get(r#""Hello %s", user"#).unwrap();
+/// assert!(Arc::ptr_eq(&first, &cache.get(r#""Hello %s", user"#).unwrap()));
+/// assert_eq!(cache.sprintf(r#""Bye %s", user"#, &values).unwrap(), "Bye foo");
+///
+/// // "Hello" was used less recently than "Bye"
+/// cache.get(r#""%s", user"#).unwrap();
+/// assert_eq!(cache.len(), 2);
+/// assert!(!Arc::ptr_eq(&first, &cache.get(r#""Hello %s", user"#).unwrap()));
+///
+/// assert!(cache.get(r#""%q", user"#).is_err());
+/// assert_eq!(cache.len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct FormatCache {
+    capacity: usize,
+    /// Parsed templates and when they were used last.
+    entries: Map<String, (SharedFormatString, u64)>,
+    uses: u64,
+}
+
+impl FormatCache {
+    /// A cache holding at most `capacity` templates. With a capacity of zero, nothing is cached.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Map::new(),
+            uses: 0,
+        }
+    }
+
+    /// The parsed `input`, parsed only if it isn't cached yet.
+    pub fn get(&mut self, input: &str) -> Result<SharedFormatString> {
+        self.uses += 1;
+        if let Some((parsed, used)) = self.entries.get_mut(input) {
+            *used = self.uses;
+            return Ok(parsed.clone());
+        }
+
+        let parsed = parse_format_string(input)?.into_shared();
+        if self.capacity == 0 {
+            return Ok(parsed);
+        }
+        if self.entries.len() >= self.capacity {
+            let least_recent = self.entries.iter().min_by_key(|(_, (_, used))| *used);
+            if let Some(input) = least_recent.map(|(input, _)| input.clone()) {
+                self.entries.remove(&input);
+            }
+        }
+        self.entries.insert(input.into(), (parsed.clone(), self.uses));
+        Ok(parsed)
+    }
+
+    /// Like [sprintf], with `input` parsed by [FormatCache::get].
+    pub fn sprintf(&mut self, input: &str, resolver: &impl Resolver) -> Result<String> {
+        let parsed = self.get(input)?;
+        sprintf(&parsed, resolver)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached templates.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Default for FormatCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Like [sprintf] with the statement parsed by [parse_format_string], but parses every statement only
+/// once. The templates are kept in a process wide [FormatCache] with [DEFAULT_CAPACITY].
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     cache::sprintf_cached,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("n", Value::Numeric(42.0));
+/// let values: MapResolver = values.into();
+///
+/// for _ in 0..3 {
+///     assert_eq!(sprintf_cached(r#""n=%03d", n"#, &values).unwrap(), "n=042");
+/// }
+/// ```
+pub fn sprintf_cached(input: &str, resolver: &impl Resolver) -> Result<String> {
+    static CACHE: OnceLock<Mutex<FormatCache>> = OnceLock::new();
+
+    let cache = CACHE.get_or_init(Mutex::default);
+    // a panic while holding the lock can't leave the cache inconsistent
+    let parsed = cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .get(input)?;
+    sprintf(&parsed, resolver)
+}
@@ -0,0 +1,13 @@
+/// Map behind the internal caches. Maps in the public API are always [std::collections::BTreeMap]s.
+///
+/// With the `deterministic` feature this is a [std::collections::BTreeMap], so iteration order is
+/// reproducible, e.g. for golden tests. Otherwise it's a [std::collections::HashMap].
+#[cfg(feature = "deterministic")]
+pub(crate) type Map<K, V> = std::collections::BTreeMap<K, V>;
+
+/// Map behind the internal caches. Maps in the public API are always [std::collections::BTreeMap]s.
+///
+/// With the `deterministic` feature this is a [std::collections::BTreeMap], so iteration order is
+/// reproducible, e.g. for golden tests. Otherwise it's a [std::collections::HashMap].
+#[cfg(not(feature = "deterministic"))]
+pub(crate) type Map<K, V> = std::collections::HashMap<K, V>;
@@ -0,0 +1,280 @@
+use std::{borrow::Cow, fmt::Write};
+
+use crate::{
+    error::{Result, SprintfError},
+    expression::Expression,
+    options::{PrintOptions, TagPosition, TruncateScope},
+    parser::{check_arity, Entry, ParsedFormat, Placeholder},
+    printer::{
+        check_budget, or_default, print_tag, resolve_argument, style_sequence, write_field, write_joined, written,
+        Limited,
+    },
+    value::{Resolver, Value},
+};
+
+#[derive(Debug, Clone)]
+enum Step {
+    /// Adjacent text, styles and tags, merged into one.
+    Literal(String),
+    Field {
+        format: Placeholder,
+        /// Index into [CompiledFormat::arguments].
+        argument: usize,
+    },
+    /// A section printed only if its variable resolves, see [Entry::Section].
+    Section {
+        variable: String,
+        /// Index of the first step behind the section.
+        end: usize,
+    },
+}
+
+/// A [ParsedFormat] prepared for printing it over and over, e.g. for every log event.
+///
+/// Compiling merges adjacent literal text, renders styles and the [PrintOptions::tag] up front and
+/// binds every placeholder to its variable by index. Variables used by several placeholders are
+/// resolved only once per run.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let parsed = parse_format_string(r#""%s took %.2fs (%s)", user, took, user"#).unwrap();
+/// let compiled = parsed.compile().unwrap();
+/// assert_eq!(compiled.variables().collect::<Vec<_>>(), vec!["user", "took"]);
+///
+/// let mut out = String::new();
+/// for took in [1.5, 0.25] {
+///     let mut values = HashMap::new();
+///     values.insert("user", Value::String("foo".into()));
+///     values.insert("took", Value::Numeric(took));
+///     let values: MapResolver = values.into();
+///     compiled.run(&values, &mut out).unwrap();
+/// }
+/// assert_eq!(out, "foo took 0.25s (foo)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledFormat {
+    steps: Vec<Step>,
+    /// Every distinct variable with its parsed argument.
+    arguments: Vec<(String, Expression)>,
+    /// Estimated length of the output, see [ParsedFormat::size_hint].
+    size_hint: usize,
+    options: PrintOptions,
+}
+
+impl CompiledFormat {
+    /// The distinct variables in order of their first use.
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.arguments.iter().map(|(variable, _)| variable.as_str())
+    }
+
+    /// Prints into `out`, which is cleared first, like [crate::printer::sprintf_into].
+    pub fn run(&self, resolver: &impl Resolver, out: &mut String) -> Result<()> {
+        out.clear();
+        out.reserve(self.size_hint);
+        match &self.options.max_len {
+            Some(max) if max.scope == TruncateScope::Output => {
+                let mut limited = Limited::new(out, max);
+                self.write_steps(&mut limited, resolver)?;
+                written(limited.finish())
+            }
+            _ => self.write_steps(out, resolver),
+        }
+    }
+
+    /// Like [CompiledFormat::run], but returns a new [String]. An aborted print keeps the output
+    /// printed so far.
+    /// ```
+    /// use sprintf::{
+    ///     error::SprintfError,
+    ///     options::{Budget, CancelToken, PrintOptions},
+    ///     parser::parse_format_string,
+    ///     value::{Resolver, Value},
+    /// };
+    ///
+    /// // cancels the print once asked for anything but `user`
+    /// struct Cancelling {
+    ///     user: Value,
+    ///     cancel: CancelToken,
+    /// }
+    ///
+    /// impl Resolver for Cancelling {
+    ///     fn resolve(&self, name: &str) -> Option<&Value> {
+    ///         match name {
+    ///             "user" => Some(&self.user),
+    ///             _ => {
+    ///                 self.cancel.cancel();
+    ///                 None
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let cancel = CancelToken::new();
+    /// let options = PrintOptions {
+    ///     budget: Some(Budget {
+    ///         cancel: Some(cancel.clone()),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let parsed = parse_format_string(r#""Hello %s%{?stop}!%{/stop}, bye", user"#).unwrap();
+    /// let resolver = Cancelling {
+    ///     user: "foo".into(),
+    ///     cancel,
+    /// };
+    /// let err = parsed.compile_with(&options).unwrap().sprintf(&resolver).unwrap_err();
+    /// assert!(matches!(err, SprintfError::Aborted { reason: "cancelled", partial } if partial == "Hello foo"));
+    /// ```
+    pub fn sprintf(&self, resolver: &impl Resolver) -> Result<String> {
+        let mut out = String::new();
+        match self.run(resolver, &mut out) {
+            Ok(()) => Ok(out),
+            Err(SprintfError::Aborted { reason, .. }) => Err(SprintfError::Aborted { reason, partial: out }),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn write_steps(&self, out: &mut impl Write, resolver: &impl Resolver) -> Result<()> {
+        let values: Vec<Result<Cow<Value>>> = self
+            .arguments
+            .iter()
+            .map(|(variable, argument)| resolve_argument(variable, argument, resolver))
+            .collect();
+
+        let mut position = 0;
+        let mut index = 0;
+        while let Some(step) = self.steps.get(index) {
+            index += 1;
+            check_budget(&self.options)?;
+            match step {
+                Step::Literal(text) => written(out.write_str(text))?,
+                Step::Section { variable, end } => {
+                    if resolver.resolve(variable).is_none() {
+                        // the placeholders of a skipped section still count
+                        position += self.steps[index..*end]
+                            .iter()
+                            .filter(|step| matches!(step, Step::Field { .. }))
+                            .count();
+                        index = *end;
+                    }
+                }
+                Step::Field { format, argument } => {
+                    let variable = &self.arguments[*argument].0;
+                    position += 1;
+                    let printed = match format.join() {
+                        // the elements are resolved on their own
+                        Some(separator) => write_joined(out, format, separator, variable, resolver, &self.options),
+                        None => {
+                            let value = match &values[*argument] {
+                                Ok(value) => Ok(Cow::Borrowed(value.as_ref())),
+                                Err(error) => Err(error.clone()),
+                            };
+                            write_field(out, format, variable, or_default(format, value), &self.options)
+                        }
+                    };
+                    printed.map_err(|error| error.in_placeholder(position, format, variable))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ParsedFormat<'_> {
+    /// Compiles the template for printing with the default [PrintOptions].
+    pub fn compile(&self) -> Result<CompiledFormat> {
+        self.compile_with(&PrintOptions::default())
+    }
+
+    /// Compiles the template for printing with `options`. Fails if the number of variables doesn't
+    /// match the placeholders.
+    pub fn compile_with(&self, options: &PrintOptions) -> Result<CompiledFormat> {
+        check_arity(&self.entries, &self.variables)?;
+        options.limits.check(&self.entries)?;
+
+        let mut steps = Vec::new();
+        let mut arguments: Vec<(String, Expression)> = Vec::new();
+        let mut literal = String::new();
+        let tag = options.tag.as_ref().map(|tag| (tag.position, print_tag(tag, self)));
+        if let Some((TagPosition::Prepend, tag)) = &tag {
+            literal.push_str(tag);
+        }
+
+        let mut bound = self.variables.iter().zip(&self.arguments);
+        // steps of the sections not ended yet
+        let mut sections = Vec::new();
+        for entry in &self.entries {
+            match entry {
+                Entry::Text(text) => literal.push_str(text),
+                Entry::Style(styles) => literal.push_str(&style_sequence(styles, options)),
+                Entry::Section(variable) => {
+                    if !literal.is_empty() {
+                        steps.push(Step::Literal(std::mem::take(&mut literal)));
+                    }
+                    sections.push(steps.len());
+                    steps.push(Step::Section {
+                        variable: variable.clone(),
+                        end: 0,
+                    });
+                }
+                Entry::SectionEnd(_) => {
+                    if !literal.is_empty() {
+                        steps.push(Step::Literal(std::mem::take(&mut literal)));
+                    }
+                    let end = steps.len();
+                    if let Some(Step::Section { end: section_end, .. }) =
+                        sections.pop().and_then(|start| steps.get_mut(start))
+                    {
+                        *section_end = end;
+                    }
+                }
+                Entry::Placeholder(format) => {
+                    if !literal.is_empty() {
+                        steps.push(Step::Literal(std::mem::take(&mut literal)));
+                    }
+                    // check_arity made sure there is a variable for every placeholder
+                    let Some((variable, argument)) = bound.next() else {
+                        break;
+                    };
+                    let index = match arguments.iter().position(|(known, _)| known == variable) {
+                        Some(index) => index,
+                        None => {
+                            arguments.push((variable.clone(), argument.clone()));
+                            arguments.len() - 1
+                        }
+                    };
+                    steps.push(Step::Field {
+                        format: format.clone(),
+                        argument: index,
+                    });
+                }
+            }
+        }
+        if let Some((TagPosition::Append, tag)) = &tag {
+            literal.push_str(tag);
+        }
+        if !literal.is_empty() {
+            steps.push(Step::Literal(literal));
+        }
+        // sections not ended run to the end
+        for start in sections {
+            let end = steps.len();
+            if let Some(Step::Section { end: section_end, .. }) = steps.get_mut(start) {
+                *section_end = end;
+            }
+        }
+
+        let tag_len = tag.map_or(0, |(_, tag)| tag.len());
+        Ok(CompiledFormat {
+            steps,
+            arguments,
+            size_hint: self.size_hint() + tag_len,
+            options: options.clone(),
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    error::{Result, SprintfError},
+    options::ParseOptions,
+    parser::{parse_format_string_with, ParsedFormatString},
+};
+
+/// Lines starting with one of these are ignored.
+const COMMENT_MARKERS: &[&str] = &["#", "//"];
+/// A line ending with this is continued by the next line.
+const CONTINUATION: char = '\\';
+
+/// Parses a document with one statement like `"%s took %.2fs", user, duration` per line.
+///
+/// Blank lines and lines starting with `#` or `//` are skipped. A line ending with `\` is continued
+/// by the next line, whose leading whitespace is dropped. Spans of the parsed statements refer to the
+/// joined statement. Errors are reported with the line the statement starts at.
+/// ```
+/// use sprintf::{document::parse_document, error::SprintfError};
+///
+/// let document = r#"
+/// ## request logging
+/// "%s %s", method, path
+///
+/// // timing
+/// "%s took \
+///     %.2fs", path, duration
+/// "#;
+/// let parsed = parse_document(document).unwrap();
+/// assert_eq!(parsed.len(), 2);
+/// assert_eq!(parsed[1].variables, vec!["path", "duration"]);
+/// assert_eq!(parsed[1].to_string(), r#""%s took %.2fs", path, duration"#);
+///
+/// let err = parse_document("\"%s\", a\n\n\"%s %s\", b").unwrap_err();
+/// assert_eq!(err.to_string(), "line 3: Unmatched variables(1) and placeholders(2)");
+/// ```
+pub fn parse_document(input: &str) -> Result<Vec<ParsedFormatString>> {
+    parse_document_with(input, &ParseOptions::default())
+}
+
+/// Like [parse_document], but with [ParseOptions] applied to every statement.
+pub fn parse_document_with(input: &str, options: &ParseOptions) -> Result<Vec<ParsedFormatString>> {
+    let mut result = Vec::new();
+    let mut statement = String::new();
+    let mut start = 0;
+    for (index, line) in input.lines().enumerate() {
+        let line = if statement.is_empty() {
+            start = index + 1;
+            line.trim()
+        } else {
+            line.trim_start()
+        };
+        if statement.is_empty() && (line.is_empty() || COMMENT_MARKERS.iter().any(|m| line.starts_with(m))) {
+            continue;
+        }
+        if let Some(line) = line.strip_suffix(CONTINUATION) {
+            statement.push_str(line);
+            continue;
+        }
+        statement.push_str(line);
+
+        let parsed = parse_format_string_with(&statement, options).map_err(|error| SprintfError::InvalidLine {
+            line: start,
+            error: Box::new(error),
+        })?;
+        result.push(parsed.into_owned());
+        statement.clear();
+    }
+    if !statement.is_empty() {
+        return Err(SprintfError::InvalidLine {
+            line: start,
+            error: Box::new(SprintfError::InvalidTemplate("Missing line after continuation".into())),
+        });
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,95 @@
+use std::fmt::Write;
+
+use crate::parser::{Entry, ParsedFormat, Placeholder};
+
+const HEADER: [&str; 5] = ["idx", "kind", "spec", "variable", "span"];
+
+fn kind(entry: &Entry) -> &'static str {
+    match entry {
+        Entry::Text(_) => "text",
+        Entry::Style(_) => "style",
+        Entry::Section(_) => "section",
+        Entry::SectionEnd(_) => "end",
+        Entry::Placeholder(placeholder) => placeholder_kind(placeholder),
+    }
+}
+
+fn placeholder_kind(placeholder: &Placeholder) -> &'static str {
+    match placeholder {
+        Placeholder::Display(_) => "display",
+        Placeholder::String(_) => "string",
+        Placeholder::Float(_) => "float",
+        Placeholder::Number(_) => "number",
+        Placeholder::Hex(_) => "hex",
+        Placeholder::Duration(_) => "duration",
+        Placeholder::Size(_) => "size",
+        Placeholder::Percent(_) => "percent",
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(_) => "datetime",
+        Placeholder::Custom(..) => "custom",
+        Placeholder::C99(_) => "c99",
+        Placeholder::Modified(_, inner) => placeholder_kind(inner),
+    }
+}
+
+impl ParsedFormat<'_> {
+    /// Lists all entries with their kind, spec, bound variable and source span, one entry per line
+    /// and aligned in columns. Without source spans, the spans refer to the reconstructed format
+    /// text.
+    /// ```
+    /// use sprintf::parser::parse_format_string;
+    ///
+    /// let parsed = parse_format_string(r#""%-8s took %.2fs", user, duration"#).unwrap();
+    /// assert_eq!(
+    ///     parsed.dump(),
+    ///     "\
+    /// idx kind   spec     variable span
+    /// 0   string %-8s     user     1..5
+    /// 1   text   \" took \"          5..11
+    /// 2   float  %.2f     duration 11..15
+    /// 3   text   \"s\"               15..16
+    /// "
+    /// );
+    /// ```
+    pub fn dump(&self) -> String {
+        let mut rows = vec![HEADER.map(String::from)];
+        let mut variables = self.variables.iter();
+        let mut offset = 0;
+        for (index, entry) in self.entries.iter().enumerate() {
+            let spec = entry.to_string();
+            let span = offset..offset + spec.len();
+            offset = span.end;
+            let span = self.spans.get(index).cloned().unwrap_or(span);
+            let (spec, variable) = match entry {
+                Entry::Text(_) => (format!("{:?}", spec), String::new()),
+                Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => (spec, String::new()),
+                Entry::Placeholder(_) => (spec, variables.next().cloned().unwrap_or_default()),
+            };
+            rows.push([
+                index.to_string(),
+                kind(entry).into(),
+                spec,
+                variable,
+                format!("{:?}", span),
+            ]);
+        }
+
+        let mut widths = [0; HEADER.len()];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut result = String::new();
+        for row in &rows {
+            let mut line = String::new();
+            for (width, cell) in widths.iter().zip(row) {
+                let _ = write!(line, "{:width$} ", cell, width = width);
+            }
+            result.push_str(line.trim_end());
+            result.push('\n');
+        }
+        result
+    }
+}
@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use crate::{
+    error::{Result, SprintfError},
+    parser::{Entry, ParsedFormatString},
+    validate::ValidationError,
+    value::Resolver,
+};
+
+/// A [ParsedFormatString] under edit, e.g. backing a template editor.
+///
+/// Every edit is revalidated right away against `schema`, a resolver with sample values of all
+/// known variables, which covers the arity, the bindings and the value types. Edits can be undone.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     editor::EditableTemplate,
+///     parser::*,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut schema = HashMap::new();
+/// schema.insert("user", Value::String("foo".into()));
+/// schema.insert("duration", Value::Numeric(1.0));
+/// let schema: MapResolver = schema.into();
+///
+/// let parsed = parse_format_string(r#""%s logged in", user"#).unwrap();
+/// let mut editor = EditableTemplate::new(parsed, &schema);
+/// assert!(editor.is_valid());
+///
+/// // the new placeholder is not bound yet
+/// let errors = editor.splice_entries(2..2, parse_format(" after %.2fs").unwrap()).unwrap();
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].variable, None);
+///
+/// // bound to a variable the schema doesn't know
+/// let errors = editor.splice_variables(1..1, ["durtion"]).unwrap();
+/// assert_eq!(errors[0].variable.as_deref(), Some("durtion"));
+///
+/// editor.undo();
+/// assert!(editor.splice_variables(1..1, ["duration"]).unwrap().is_empty());
+/// assert_eq!(editor.template().to_string(), r#""%s logged in after %.2fs", user, duration"#);
+///
+/// assert!(editor.splice_entries(7..9, []).is_err());
+/// ```
+#[derive(Debug)]
+pub struct EditableTemplate<'s, R> {
+    template: ParsedFormatString,
+    schema: &'s R,
+    errors: Vec<ValidationError>,
+    history: Vec<(Vec<Entry<'static>>, Vec<String>)>,
+}
+
+impl<'s, R: Resolver> EditableTemplate<'s, R> {
+    pub fn new(template: ParsedFormatString, schema: &'s R) -> Self {
+        let mut result = Self {
+            template,
+            schema,
+            errors: Vec::new(),
+            history: Vec::new(),
+        };
+        result.revalidate();
+        result
+    }
+
+    pub fn template(&self) -> &ParsedFormatString {
+        &self.template
+    }
+
+    pub fn into_template(self) -> ParsedFormatString {
+        self.template
+    }
+
+    /// Problems found by the last revalidation.
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Replaces the entries in `range` by `entries` and returns the problems of the result.
+    pub fn splice_entries(
+        &mut self,
+        range: Range<usize>,
+        entries: impl IntoIterator<Item = Entry<'static>>,
+    ) -> Result<&[ValidationError]> {
+        let mut edited = self.template.entries.clone();
+        splice(&mut edited, range, entries)?;
+        let variables = self.template.variables.clone();
+        Ok(self.apply(edited, variables))
+    }
+
+    /// Replaces the variables in `range` by `variables` and returns the problems of the result.
+    pub fn splice_variables(
+        &mut self,
+        range: Range<usize>,
+        variables: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<&[ValidationError]> {
+        let mut edited = self.template.variables.clone();
+        splice(&mut edited, range, variables.into_iter().map(Into::into))?;
+        let entries = self.template.entries.clone();
+        Ok(self.apply(entries, edited))
+    }
+
+    /// Reverts the last edit. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((entries, variables)) => {
+                self.template = ParsedFormatString::new(entries, variables);
+                self.revalidate();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn apply(&mut self, entries: Vec<Entry<'static>>, variables: Vec<String>) -> &[ValidationError] {
+        let previous = std::mem::replace(&mut self.template, ParsedFormatString::new(entries, variables));
+        self.history.push((previous.entries, previous.variables));
+        self.revalidate();
+        &self.errors
+    }
+
+    fn revalidate(&mut self) {
+        self.errors = self.template.validate(self.schema).err().unwrap_or_default();
+    }
+}
+
+fn splice<T>(items: &mut Vec<T>, range: Range<usize>, replacement: impl IntoIterator<Item = T>) -> Result<()> {
+    if range.start > range.end || range.end > items.len() {
+        return Err(SprintfError::InvalidTemplate(format!(
+            "Range {:?} is out of bounds for {} items",
+            range,
+            items.len()
+        )));
+    }
+    items.splice(range, replacement);
+    Ok(())
+}
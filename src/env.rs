@@ -0,0 +1,91 @@
+use std::env;
+
+use crate::{
+    fields::Values,
+    printer::is_decimal,
+    value::{Resolver, Value},
+};
+
+/// Resolves variables from environment variables, e.g. for banners printed at startup. Values which
+/// are plain decimals like `5432` or `1.10` are resolved as [Value::Decimal], so numeric
+/// placeholders take them and `%s` prints them as they are. All others are strings. The variables
+/// are read once when the resolver is created, names keep their case.
+/// ```
+/// use sprintf::{env::EnvResolver, parser::parse_format_string, printer::sprintf};
+///
+/// let vars = [
+///     ("APP_NAME", "foo"),
+///     ("APP_DB__PORT", "5432"),
+///     ("APP_ZIP", "01234"),
+///     ("APP_VERSION", "1.10"),
+///     ("HOME", "/root"),
+/// ];
+/// let resolver = EnvResolver::from_vars(vars.map(|(name, value)| (name.to_string(), value.to_string())))
+///     .prefix("APP_")
+///     .nested();
+///
+/// let parsed = parse_format_string(r#""%s connects to port %05d", NAME, DB.PORT"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo connects to port 05432");
+///
+/// let parsed = parse_format_string(r#""%s %d, version %s", ZIP, ZIP, VERSION"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "01234 1234, version 1.10");
+///
+/// let parsed = parse_format_string(r#""%s", HOME"#).unwrap();
+/// assert!(sprintf(&parsed, &resolver).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EnvResolver {
+    values: Values,
+}
+
+impl EnvResolver {
+    /// All environment variables of the process by their name. Variables which aren't valid
+    /// unicode are left out.
+    pub fn new() -> Self {
+        Self::from_vars(
+            env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?))),
+        )
+    }
+
+    /// The given variables instead of the ones of the process.
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        let values = vars
+            .into_iter()
+            .map(|(name, value)| {
+                // the text is kept as it is, `%s` prints it byte for byte
+                let value = match is_decimal(&value) {
+                    true => Value::Decimal(value),
+                    false => Value::String(value),
+                };
+                (name, value)
+            })
+            .collect();
+        Self { values }
+    }
+
+    /// Only keeps the variables starting with `prefix`, resolved by their name without it.
+    pub fn prefix(self, prefix: &str) -> Self {
+        let values = self
+            .values
+            .into_iter()
+            .filter_map(|(name, value)| Some((name.strip_prefix(prefix)?.to_string(), value)))
+            .collect();
+        Self { values }
+    }
+
+    /// Resolves variables with `__` in their name by dotted paths, e.g. `DB__PORT` as `DB.PORT`.
+    pub fn nested(self) -> Self {
+        let values = self
+            .values
+            .into_iter()
+            .map(|(name, value)| (name.replace("__", "."), value))
+            .collect();
+        Self { values }
+    }
+}
+
+impl Resolver for EnvResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
@@ -0,0 +1,157 @@
+use std::{io, ops::Range};
+
+use thiserror::Error;
+
+use crate::parser::Placeholder;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SprintfError {
+    /// The placeholder ends with an unknown conversion character.
+    #[error("invalid conversion '{conversion}' at column {column}")]
+    UnknownConversion {
+        conversion: char,
+        /// 1-based character column of the conversion.
+        column: usize,
+        /// Byte range of the placeholder including the conversion.
+        span: Range<usize>,
+    },
+    /// The input ends in the middle of a placeholder.
+    #[error("unterminated placeholder at column {column}")]
+    UnterminatedFormat {
+        /// 1-based character column of the placeholder's `%`.
+        column: usize,
+        span: Range<usize>,
+    },
+    #[error("No opening quote found")]
+    MissingQuote,
+    #[error("No terminating quote found")]
+    UnterminatedQuote,
+    #[error("Invalid placeholder '{0}'")]
+    InvalidPlaceholder(String),
+    #[error("Invalid escape sequence '{0}'")]
+    InvalidEscape(String),
+    #[error("Invalid expression '{expression}': {reason}")]
+    InvalidExpression { expression: String, reason: String },
+    #[error("Invalid template: {0}")]
+    InvalidTemplate(String),
+    #[error("Unmatched variables({variables}) and placeholders({placeholders})")]
+    ArityMismatch { variables: usize, placeholders: usize },
+    #[error("Not a {expected} value")]
+    TypeMismatch { expected: &'static str },
+    #[error("Unable to resolve variable {0:?}")]
+    UnresolvedVariable(String),
+    /// A custom conversion is printed without a handler, see [crate::formatter::Formatter].
+    #[error("No handler registered for conversion '{0}'")]
+    UnregisteredConversion(char),
+    /// A [crate::types::Filter::Custom] is printed without a handler, see [crate::formatter::Formatter].
+    #[error("Unknown filter '{0}'")]
+    UnknownFilter(String),
+    /// The [crate::options::Budget] of a render was exceeded, `partial` is the output printed so far.
+    /// When printing into a sink, the sink holds the partial output instead.
+    #[error("Rendering aborted: {reason}")]
+    Aborted { reason: &'static str, partial: String },
+    /// A template exceeds one of its [crate::options::ParseLimits].
+    #[error("{limit} of {actual} exceeds the limit of {max}")]
+    LimitExceeded {
+        limit: &'static str,
+        actual: usize,
+        max: usize,
+    },
+    /// A line printed by a [crate::layout::FixedRecord] has the wrong length.
+    #[error("Record is {actual} characters long instead of {expected}")]
+    RecordLength { expected: usize, actual: usize },
+    #[error("Unable to write the output")]
+    Write,
+    /// Writing into a [std::io::Write] failed.
+    #[error("Unable to write the output: {message}")]
+    Io { kind: io::ErrorKind, message: String },
+    /// The input scanned by [crate::scanner::sscanf] was not printed by the template.
+    #[error("Input does not match at byte {offset}: {reason}")]
+    Mismatch { offset: usize, reason: String },
+    /// Printing a single placeholder failed, e.g. because its variable holds a value of the wrong
+    /// type. Locates the problem within long templates.
+    #[error("placeholder #{position} ({placeholder}) for variable `{variable}`: {error}")]
+    PlaceholderFailed {
+        /// 1-based position among the placeholders of the template.
+        position: usize,
+        placeholder: String,
+        variable: String,
+        error: Box<SprintfError>,
+    },
+    /// Error in a statement of a document, see [crate::document::parse_document].
+    #[error("line {line}: {error}")]
+    InvalidLine {
+        /// 1-based line the statement starts at.
+        line: usize,
+        error: Box<SprintfError>,
+    },
+}
+
+impl SprintfError {
+    /// Byte range within the parsed input the error refers to, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            SprintfError::UnknownConversion { span, .. } | SprintfError::UnterminatedFormat { span, .. } => {
+                Some(span.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// The error without the context of [SprintfError::PlaceholderFailed] and
+    /// [SprintfError::InvalidLine], e.g. to match on its kind.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     error::SprintfError,
+    ///     parser::parse_format_string,
+    ///     printer::sprintf,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("user", Value::String("foo".into()));
+    /// values.insert("test.seconds", Value::String("slow".into()));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let parsed = parse_format_string(r#""%s: %d, %s, %.2f", user, 1 + 1, user, test.seconds"#).unwrap();
+    /// let err = sprintf(&parsed, &values).unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "placeholder #4 (%.2f) for variable `test.seconds`: Not a numeric value"
+    /// );
+    /// assert_eq!(err.root(), &SprintfError::TypeMismatch { expected: "numeric" });
+    /// ```
+    pub fn root(&self) -> &SprintfError {
+        match self {
+            SprintfError::PlaceholderFailed { error, .. } | SprintfError::InvalidLine { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// Adds the context of the placeholder at 1-based `position` to an error of printing it.
+    /// Errors of the output as a whole are kept as they are.
+    pub(crate) fn in_placeholder(self, position: usize, placeholder: &Placeholder, variable: &str) -> Self {
+        match self {
+            SprintfError::Write | SprintfError::Io { .. } | SprintfError::Aborted { .. } => self,
+            error => SprintfError::PlaceholderFailed {
+                position,
+                placeholder: placeholder.to_string(),
+                variable: variable.into(),
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+impl From<io::Error> for SprintfError {
+    fn from(error: io::Error) -> Self {
+        SprintfError::Io {
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+pub type Result<T, E = SprintfError> = std::result::Result<T, E>;
@@ -0,0 +1,277 @@
+use std::{borrow::Cow, iter::Peekable, str::CharIndices};
+
+use crate::{
+    error::{Result, SprintfError},
+    printer::get_number,
+    value::{Resolver, Value},
+};
+
+/// Maximal nesting of parentheses and negations within an expression.
+const MAX_NESTING: usize = 64;
+/// Maximal number of operators within an expression.
+const MAX_OPERATORS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// Arithmetic expression in the variable list of a statement, e.g. `req.total_ms / 1000`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Variable(String),
+    Number(f64),
+    Negate(Box<Expression>),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+    /// Parses an expression made of variables, numbers, `+ - * /` and parentheses.
+    /// ```
+    /// use sprintf::expression::*;
+    ///
+    /// let expr = Expression::parse("req.total_ms / 1000").unwrap();
+    /// assert_eq!(
+    ///     expr,
+    ///     Expression::Binary(
+    ///         Box::new(Expression::Variable("req.total_ms".into())),
+    ///         Operator::Div,
+    ///         Box::new(Expression::Number(1000.0))
+    ///     )
+    /// );
+    ///
+    /// let expr = Expression::parse("-(a + 1) * 2").unwrap();
+    /// assert!(matches!(expr, Expression::Binary(_, Operator::Mul, _)));
+    ///
+    /// assert!(Expression::parse("a +").is_err());
+    /// assert!(Expression::parse("(a").is_err());
+    ///
+    /// // nesting and length are limited, so deeply nested input can't overflow the stack
+    /// assert!(Expression::parse(&format!("{}a{}", "(".repeat(100_000), ")".repeat(100_000))).is_err());
+    /// assert!(Expression::parse(&format!("{}a", "-".repeat(100_000))).is_err());
+    /// assert!(Expression::parse(&vec!["a"; 100_000].join(" + ")).is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = ExpressionParser {
+            input,
+            chars: input.char_indices().peekable(),
+            nesting: 0,
+            operators: 0,
+        };
+        let expression = parser.sum()?;
+        parser.skip_whitespace();
+        if let Some(&(i, c)) = parser.chars.peek() {
+            return Err(parser.error(format!("Unexpected '{}' at column {}", c, i + 1)));
+        }
+
+        Ok(expression)
+    }
+
+    /// Parses an argument of a variable list. Arguments which are no valid expression are taken as
+    /// plain variable names.
+    pub fn from_argument(argument: &str) -> Self {
+        Self::parse(argument).unwrap_or_else(|_| Self::Variable(argument.into()))
+    }
+
+    /// Evaluates the expression, resolving all variables with `resolver`.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     expression::Expression,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("total_ms", Value::Numeric(1500.0));
+    /// values.insert("count", Value::Numeric(3.0));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let expr = Expression::parse("total_ms / 1000 / count").unwrap();
+    /// assert_eq!(*expr.evaluate(&values).unwrap(), Value::Numeric(0.5));
+    ///
+    /// let expr = Expression::parse(&vec!["count"; 200].join(" + ")).unwrap();
+    /// assert_eq!(*expr.evaluate(&values).unwrap(), Value::Numeric(600.0));
+    /// ```
+    pub fn evaluate<'r>(&self, resolver: &'r impl Resolver) -> Result<Cow<'r, Value>> {
+        // evaluated with an explicit stack instead of recursion, so long expressions can't overflow the
+        // call stack
+        let mut work = vec![Step::Evaluate(self)];
+        let mut values: Vec<Cow<'r, Value>> = Vec::new();
+        while let Some(step) = work.pop() {
+            let result = match step {
+                Step::Evaluate(Expression::Variable(name)) => {
+                    let value = resolver
+                        .resolve(name)
+                        .ok_or_else(|| SprintfError::UnresolvedVariable(name.clone()))?;
+                    values.push(Cow::Borrowed(value));
+                    continue;
+                }
+                Step::Evaluate(Expression::Number(n)) => *n,
+                Step::Evaluate(Expression::Negate(expression)) => {
+                    work.extend([Step::Negate, Step::Evaluate(expression)]);
+                    continue;
+                }
+                Step::Evaluate(Expression::Binary(left, operator, right)) => {
+                    work.extend([Step::Apply(*operator), Step::Evaluate(right), Step::Evaluate(left)]);
+                    continue;
+                }
+                Step::Negate => -pop_number(&mut values)?,
+                Step::Apply(operator) => {
+                    let right = pop_number(&mut values)?;
+                    let left = pop_number(&mut values)?;
+                    match operator {
+                        Operator::Add => left + right,
+                        Operator::Sub => left - right,
+                        Operator::Mul => left * right,
+                        Operator::Div => left / right,
+                    }
+                }
+            };
+            values.push(Cow::Owned(Value::Numeric(result)));
+        }
+
+        values.pop().ok_or(SprintfError::TypeMismatch { expected: "numeric" })
+    }
+
+    /// The names of all variables in the expression, in order of appearance.
+    /// ```
+    /// use sprintf::expression::Expression;
+    ///
+    /// let expr = Expression::parse("-(a + 1) * b / a").unwrap();
+    /// assert_eq!(expr.variables(), vec!["a", "b", "a"]);
+    /// ```
+    pub fn variables(&self) -> Vec<&str> {
+        let mut variables = Vec::new();
+        let mut work = vec![self];
+        while let Some(expression) = work.pop() {
+            match expression {
+                Expression::Variable(name) => variables.push(name.as_str()),
+                Expression::Number(_) => {}
+                Expression::Negate(expression) => work.push(expression),
+                Expression::Binary(left, _, right) => work.extend([right.as_ref(), left.as_ref()]),
+            }
+        }
+        variables
+    }
+}
+
+/// Pending work of [Expression::evaluate].
+enum Step<'e> {
+    Evaluate(&'e Expression),
+    Negate,
+    Apply(Operator),
+}
+
+fn pop_number(values: &mut Vec<Cow<Value>>) -> Result<f64> {
+    match values.pop().as_deref() {
+        Some(Value::Numeric(n)) => Ok(*n),
+        Some(Value::Integer(n)) => Ok(*n as f64),
+        Some(value @ Value::Decimal(_)) => get_number(value),
+        _ => Err(SprintfError::TypeMismatch { expected: "numeric" }),
+    }
+}
+
+/// Recursive descent parser, one method per precedence level.
+struct ExpressionParser<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    nesting: usize,
+    operators: usize,
+}
+
+impl ExpressionParser<'_> {
+    fn error(&self, reason: impl Into<String>) -> SprintfError {
+        SprintfError::InvalidExpression {
+            expression: self.input.into(),
+            reason: reason.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+
+    fn operator(&mut self, operators: &[(char, Operator)]) -> Result<Option<Operator>> {
+        self.skip_whitespace();
+        let Some((_, c)) = self.chars.peek() else {
+            return Ok(None);
+        };
+        let Some((_, operator)) = operators.iter().find(|(o, _)| o == c) else {
+            return Ok(None);
+        };
+        self.chars.next();
+        self.operators += 1;
+        if self.operators > MAX_OPERATORS {
+            return Err(self.error(format!("More than {} operators", MAX_OPERATORS)));
+        }
+        Ok(Some(*operator))
+    }
+
+    fn sum(&mut self) -> Result<Expression> {
+        let mut left = self.product()?;
+        while let Some(operator) = self.operator(&[('+', Operator::Add), ('-', Operator::Sub)])? {
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.product()?));
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expression> {
+        let mut left = self.unary()?;
+        while let Some(operator) = self.operator(&[('*', Operator::Mul), ('/', Operator::Div)])? {
+            left = Expression::Binary(Box::new(left), operator, Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    /// Parses a nested expression, failing if nested too deep.
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> Result<Expression>) -> Result<Expression> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING {
+            return Err(self.error(format!("Nested deeper than {}", MAX_NESTING)));
+        }
+        let expression = parse(self);
+        self.nesting -= 1;
+        expression
+    }
+
+    fn unary(&mut self) -> Result<Expression> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some((_, '-')) => {
+                self.chars.next();
+                Ok(Expression::Negate(Box::new(self.nested(Self::unary)?)))
+            }
+            Some((_, '(')) => {
+                self.chars.next();
+                let expression = self.nested(Self::sum)?;
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some((_, ')')) => Ok(expression),
+                    _ => Err(self.error("Missing ')'")),
+                }
+            }
+            Some(&(start, _)) => {
+                let mut end = start;
+                while let Some((i, c)) = self
+                    .chars
+                    .next_if(|(_, c)| !c.is_whitespace() && !"+-*/()".contains(*c))
+                {
+                    end = i + c.len_utf8();
+                }
+                let operand = &self.input[start..end];
+                if operand.is_empty() {
+                    return Err(self.error("Missing operand"));
+                }
+                match operand.parse::<f64>() {
+                    Ok(n) if operand.starts_with(|c: char| c.is_ascii_digit()) => Ok(Expression::Number(n)),
+                    _ => Ok(Expression::Variable(operand.into())),
+                }
+            }
+            None => Err(self.error("Missing operand")),
+        }
+    }
+}
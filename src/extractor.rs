@@ -1,35 +1,319 @@
-use anyhow::{anyhow, Result};
+use crate::{
+    error::{Result, SprintfError},
+    types::{Alignment, Branch, Filter, FloatFormat, Modifiers, NumberFormat, Overflow, StringFormat},
+};
 
-use crate::types::{FloatFormat, NumberFormat};
+/// Starts and ends the [Modifiers] block right after the `%`, e.g. `%{upper|trim}s`.
+pub(crate) const MODIFIERS_START: char = '{';
+pub(crate) const MODIFIERS_END: char = '}';
+/// Surround the variable name of a placeholder naming its variable inline, e.g. `%(user)s`.
+pub(crate) const NAME_START: char = '(';
+pub(crate) const NAME_END: char = ')';
+/// Follow the [MODIFIERS_START] of the start and end of a conditional section, e.g.
+/// `%{?error}failed: %s%{/error}`.
+pub(crate) const SECTION_START: char = '?';
+pub(crate) const SECTION_END: char = '/';
+/// Surround the styles of a style token, e.g. `%[bold red]`.
+pub(crate) const STYLE_START: char = '[';
+pub(crate) const STYLE_END: char = ']';
+/// Separates the filters within the modifiers block.
+pub(crate) const FILTER_SEPARATOR: char = '|';
+/// Starts the default value at the end of the modifiers block, e.g. `%{:-unknown}s`.
+pub(crate) const DEFAULT_MARKER: &str = ":-";
+/// Starts the pattern of the conversion within the modifiers block, e.g. `%{:%Y-%m-%d}T`.
+pub(crate) const PATTERN_MARKER: char = ':';
+/// Filters choosing a message, followed by [PATTERN_MARKER] and the messages, e.g.
+/// `%{plural:one{# file} other{# files}}s`.
+pub(crate) const PLURAL: &str = "plural";
+pub(crate) const SELECT: &str = "select";
+/// Prints every element of a sequence, followed by the separator in braces, e.g. `%{join{, }}d`.
+pub(crate) const JOIN: &str = "join";
 
-pub(crate) fn extract_number_format(from: &str) -> Result<NumberFormat> {
-    if from.len() == 0 {
-        return Ok(NumberFormat::default());
+/// Prints the field names of composite values, e.g. `%+v`.
+pub(crate) const VERBOSE_MARKER: char = '+';
+/// Marks the following character as the fill character, e.g. `%*.10d`.
+pub(crate) const FILL_MARKER: char = '*';
+pub(crate) const LEFT_MARKER: char = '-';
+pub(crate) const CENTER_MARKER: char = '^';
+/// Groups thousands, e.g. `%'d`.
+pub(crate) const GROUPING_MARKER: char = '\'';
+/// Truncates a value longer than the width, or replaces it if followed by a character, e.g. `%!#8d`.
+pub(crate) const OVERFLOW_MARKER: char = '!';
+
+/// Splits a leading `-` or `^` alignment flag off `from`.
+fn extract_alignment(from: &str) -> (Alignment, &str) {
+    if let Some(rest) = from.strip_prefix(LEFT_MARKER) {
+        (Alignment::Left, rest)
+    } else if let Some(rest) = from.strip_prefix(CENTER_MARKER) {
+        (Alignment::Center, rest)
+    } else {
+        (Alignment::Right, from)
+    }
+}
+
+/// Splits a leading `'` grouping flag off `from`.
+fn extract_grouping(from: &str) -> (bool, &str) {
+    match from.strip_prefix(GROUPING_MARKER) {
+        Some(rest) => (true, rest),
+        None => (false, from),
+    }
+}
+
+/// Splits a leading `*<char>` fill specification off `from`.
+fn extract_fill_char(from: &str) -> Result<(Option<char>, &str)> {
+    let mut chars = from.chars();
+    if chars.next() != Some(FILL_MARKER) {
+        return Ok((None, from));
+    }
+    let fill = chars
+        .next()
+        .ok_or_else(|| SprintfError::InvalidPlaceholder(from.into()))?;
+
+    Ok((Some(fill), chars.as_str()))
+}
+
+/// Splits a leading `!` or `!<char>` overflow specification off `from`.
+fn extract_overflow(from: &str) -> (Overflow, &str) {
+    let Some(rest) = from.strip_prefix(OVERFLOW_MARKER) else {
+        return (Overflow::Extend, from);
+    };
+    let mut chars = rest.chars();
+    match chars.next() {
+        Some(fill) if !fill.is_ascii_digit() && fill != '.' => (Overflow::Fill(fill), chars.as_str()),
+        _ => (Overflow::Truncate, rest),
     }
+}
 
-    let mut result = NumberFormat::default();
+/// A `{filters:pattern:-default}` block with all parts optional.
+pub(crate) struct Block<'a> {
+    pub modifiers: Modifiers,
+    pub pattern: Option<String>,
+    /// The placeholder behind the block, without its `%`.
+    pub rest: &'a str,
+}
 
-    result.digits = from.parse::<u16>().ok();
-    result.fill_zeros = from.chars().next().expect("Unexpected end of input") == '0';
+/// Byte offset of the `}` closing the block `from` is the inside of, skipping nested blocks.
+fn closing_brace(from: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in from.char_indices() {
+        match c {
+            MODIFIERS_START => depth += 1,
+            MODIFIERS_END if depth == 0 => return Some(i),
+            MODIFIERS_END => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
 
-    Ok(result)
+/// Splits `block` at every `separator` outside of nested blocks.
+fn split_outside_braces(block: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in block.char_indices() {
+        match c {
+            MODIFIERS_START => depth += 1,
+            MODIFIERS_END => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                parts.push(&block[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&block[start..]);
+    parts
+}
+
+/// Splits the filters of a modifiers block off its `:pattern:-default` tail. The `:` of a
+/// `plural:` or `select:` filter belongs to the filter.
+fn split_tail(block: &str) -> (&str, Option<&str>) {
+    let mut depth = 0usize;
+    let mut filter_start = 0;
+    for (i, c) in block.char_indices() {
+        match c {
+            MODIFIERS_START => depth += 1,
+            MODIFIERS_END => depth = depth.saturating_sub(1),
+            FILTER_SEPARATOR if depth == 0 => filter_start = i + 1,
+            PATTERN_MARKER if depth == 0 && !matches!(block[filter_start..i].trim(), PLURAL | SELECT) => {
+                return (&block[..i], Some(&block[i + 1..]))
+            }
+            _ => {}
+        }
+    }
+    (block, None)
+}
+
+/// Parses the messages of a `plural:` or `select:` filter like `one{# file} other{# files}`. An
+/// `other` message is required, plural keys have to be `=N` or one of the English plural categories
+/// `one` and `other`. Messages are plain text, nested messages are rejected.
+fn extract_branches(from: &str, plural: bool) -> Option<Vec<Branch>> {
+    let mut branches = Vec::new();
+    let mut rest = from.trim_start();
+    while !rest.is_empty() {
+        let (key, message) = rest.split_once(MODIFIERS_START)?;
+        let key = key.trim();
+        let valid = match plural {
+            true => {
+                key.strip_prefix('=').is_some_and(|n| n.parse::<f64>().is_ok())
+                    || matches!(key, "one" | "other")
+            }
+            false => !key.is_empty() && !key.contains(char::is_whitespace),
+        };
+        let (message, after) = message.split_once(MODIFIERS_END)?;
+        if !valid || message.contains(MODIFIERS_START) {
+            return None;
+        }
+        branches.push(Branch {
+            key: key.to_string(),
+            message: message.to_string(),
+        });
+        rest = after.trim_start();
+    }
+    branches.iter().any(|branch| branch.key == "other").then_some(branches)
+}
+
+/// Splits a leading `{...}` modifiers block off `from`.
+pub(crate) fn extract_modifiers(from: &str) -> Result<Option<Block<'_>>> {
+    let Some(rest) = from.strip_prefix(MODIFIERS_START) else {
+        return Ok(None);
+    };
+    let Some(end) = closing_brace(rest) else {
+        return Err(SprintfError::InvalidPlaceholder(from.into()));
+    };
+    let (block, rest) = (&rest[..end], &rest[end + MODIFIERS_END.len_utf8()..]);
+    let (block, pattern, default) = match split_tail(block) {
+        (block, Some(default)) if default.starts_with('-') => (block, None, Some(default[1..].to_string())),
+        // the pattern itself may contain colons like `%H:%M`
+        (block, Some(pattern)) => match pattern.rsplit_once(DEFAULT_MARKER) {
+            Some((pattern, default)) => (block, Some(pattern.to_string()), Some(default.to_string())),
+            None => (block, Some(pattern.to_string()), None),
+        },
+        (block, None) => (block, None, None),
+    };
+    let mut filters = Vec::new();
+    let mut join = None;
+    // only a pattern or default like `{:-unknown}` has no filters
+    if !block.is_empty() || (pattern.is_none() && default.is_none()) {
+        for filter in split_outside_braces(block, FILTER_SEPARATOR) {
+            let filter = filter.trim();
+            let separator = filter
+                .strip_prefix(JOIN)
+                .and_then(|rest| rest.strip_prefix(MODIFIERS_START))
+                .and_then(|rest| rest.strip_suffix(MODIFIERS_END));
+            if let Some(separator) = separator {
+                if join.replace(separator.to_string()).is_some() {
+                    return Err(SprintfError::InvalidPlaceholder(from.into()));
+                }
+                continue;
+            }
+            if let Some((kind, branches)) = filter.split_once(PATTERN_MARKER) {
+                let plural = kind.trim() == PLURAL;
+                let branches =
+                    extract_branches(branches, plural).ok_or_else(|| SprintfError::InvalidPlaceholder(from.into()))?;
+                filters.push(match plural {
+                    true => Filter::Plural(branches),
+                    false => Filter::Select(branches),
+                });
+                continue;
+            }
+            if filter.is_empty() || !filter.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(SprintfError::InvalidPlaceholder(from.into()));
+            }
+            filters.push(Filter::from_name(filter));
+        }
+    }
+
+    Ok(Some(Block {
+        modifiers: Modifiers { filters, default, join },
+        pattern,
+        rest,
+    }))
+}
+
+/// A width or precision like the `08` of `%08d`. Fails if it doesn't fit instead of ignoring it.
+fn extract_digits(from: &str) -> Result<Option<u16>> {
+    match from.parse() {
+        Ok(digits) => Ok(Some(digits)),
+        Err(_) if !from.is_empty() && from.chars().all(|c| c.is_ascii_digit()) => {
+            Err(SprintfError::InvalidPlaceholder(from.into()))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+pub(crate) fn extract_string_format(from: &str) -> Result<StringFormat> {
+    let (align, from) = extract_alignment(from);
+    let (fill_char, from) = extract_fill_char(from)?;
+    let (overflow, from) = extract_overflow(from);
+
+    Ok(StringFormat {
+        width: extract_digits(from)?,
+        fill_char,
+        align,
+        overflow,
+        verbose: false,
+    })
+}
+
+/// Like [extract_string_format], but with a leading `+` for [StringFormat::verbose].
+pub(crate) fn extract_display_format(from: &str) -> Result<StringFormat> {
+    match from.strip_prefix(VERBOSE_MARKER) {
+        Some(rest) => Ok(StringFormat {
+            verbose: true,
+            ..extract_string_format(rest)?
+        }),
+        None => extract_string_format(from),
+    }
+}
+
+pub(crate) fn extract_number_format(from: &str) -> Result<NumberFormat> {
+    let (align, from) = extract_alignment(from);
+    let (grouping, from) = extract_grouping(from);
+    let (fill_char, from) = extract_fill_char(from)?;
+    let (overflow, from) = extract_overflow(from);
+    if from.is_empty() {
+        return Ok(NumberFormat {
+            fill_char,
+            align,
+            grouping,
+            overflow,
+            ..Default::default()
+        });
+    }
+
+    Ok(NumberFormat {
+        digits: extract_digits(from)?,
+        fill_zeros: from.starts_with('0'),
+        fill_char,
+        align,
+        grouping,
+        overflow,
+    })
 }
 
 pub(crate) fn extract_float_format(from: &str) -> Result<FloatFormat> {
+    let (align, from) = extract_alignment(from);
+    let (grouping, from) = extract_grouping(from);
+    let (fill_char, from) = extract_fill_char(from)?;
+    let (overflow, from) = extract_overflow(from);
     if !from.contains('.') {
         return Ok(FloatFormat {
-            base: extract_number_format(from)?,
+            base: NumberFormat {
+                fill_char,
+                align,
+                grouping,
+                overflow,
+                ..extract_number_format(from)?
+            },
             ..Default::default()
         });
     }
 
     let mut splitted = from.split('.');
 
-    let base = extract_number_format(
-        splitted
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of input"))?,
-    )?;
+    let base = extract_number_format(splitted.next().unwrap_or_default())?;
 
     let exponent = if let Some(s) = splitted.next() {
         extract_number_format(s)?
@@ -38,7 +322,13 @@ pub(crate) fn extract_float_format(from: &str) -> Result<FloatFormat> {
     };
 
     Ok(FloatFormat {
-        base,
+        base: NumberFormat {
+            fill_char,
+            align,
+            grouping,
+            overflow,
+            ..base
+        },
         fraction: exponent,
     })
 }
@@ -0,0 +1,234 @@
+//! C interface to parse and print templates, declared in `include/sprintf.h`.
+//!
+//! Every function returns one of the `SPRINTF_*` status codes, [sprintf_last_error] describes the
+//! last failure of the calling thread. A panic doesn't unwind into the caller, it fails with
+//! [SPRINTF_ERROR].
+//! ```
+//! use std::{
+//!     ffi::{CStr, CString},
+//!     ptr,
+//! };
+//!
+//! use sprintf::ffi::*;
+//!
+//! let statement = CString::new(r#""%s took %.2fs", user, took"#).unwrap();
+//! let mut template = ptr::null_mut();
+//! assert_eq!(unsafe { sprintf_parse(statement.as_ptr(), &mut template) }, SPRINTF_OK);
+//!
+//! let (user, took, foo) = (CString::new("user").unwrap(), CString::new("took").unwrap(), CString::new("foo").unwrap());
+//! let values = [
+//!     SprintfValue { name: user.as_ptr(), string: foo.as_ptr(), number: 0.0 },
+//!     SprintfValue { name: took.as_ptr(), string: ptr::null(), number: 1.5 },
+//! ];
+//! let mut output = ptr::null_mut();
+//! assert_eq!(unsafe { sprintf_format(template, values.as_ptr(), 2, &mut output) }, SPRINTF_OK);
+//! assert_eq!(unsafe { CStr::from_ptr(output) }.to_str().unwrap(), "foo took 1.50s");
+//! unsafe { sprintf_string_free(output) };
+//!
+//! assert_eq!(unsafe { sprintf_format(template, values.as_ptr(), 1, &mut output) }, SPRINTF_UNRESOLVED_VARIABLE);
+//! let error = unsafe { CStr::from_ptr(sprintf_last_error()) };
+//! assert_eq!(error.to_str().unwrap(), r#"placeholder #2 (%.2f) for variable `took`: Unable to resolve variable "took""#);
+//! unsafe { sprintf_free(template) };
+//!
+//! let statement = CString::new(r#""%q", user"#).unwrap();
+//! assert_eq!(unsafe { sprintf_parse(statement.as_ptr(), &mut template) }, SPRINTF_PARSE_ERROR);
+//! ```
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+use crate::{
+    error::SprintfError,
+    parser::{parse_format_string, ParsedFormatString},
+    printer::sprintf,
+    value::{Resolver, Value},
+};
+
+pub const SPRINTF_OK: c_int = 0;
+/// A pointer argument is null.
+pub const SPRINTF_NULL_POINTER: c_int = 1;
+/// A string argument is no valid UTF-8.
+pub const SPRINTF_INVALID_UTF8: c_int = 2;
+/// The statement can't be parsed.
+pub const SPRINTF_PARSE_ERROR: c_int = 3;
+/// The number of variables doesn't match the placeholders.
+pub const SPRINTF_ARITY_MISMATCH: c_int = 4;
+pub const SPRINTF_UNRESOLVED_VARIABLE: c_int = 5;
+/// A value doesn't fit its placeholder, e.g. a string for `%d`.
+pub const SPRINTF_TYPE_MISMATCH: c_int = 6;
+/// Any other failure.
+pub const SPRINTF_ERROR: c_int = 99;
+
+/// A parsed template, created by [sprintf_parse] and released by [sprintf_free].
+pub struct SprintfTemplate(ParsedFormatString);
+
+/// A named value for [sprintf_format]. It is the `string` unless that is null, else the `number`.
+#[repr(C)]
+pub struct SprintfValue {
+    pub name: *const c_char,
+    pub string: *const c_char,
+    pub number: f64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(code: c_int, message: &str) -> c_int {
+    // messages never contain NUL, but don't lose them if they do
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+fn fail_with(error: &SprintfError) -> c_int {
+    let code = match error.root() {
+        SprintfError::UnknownConversion { .. }
+        | SprintfError::UnterminatedFormat { .. }
+        | SprintfError::MissingQuote
+        | SprintfError::UnterminatedQuote
+        | SprintfError::InvalidPlaceholder(_)
+        | SprintfError::InvalidEscape(_)
+        | SprintfError::InvalidExpression { .. }
+        | SprintfError::InvalidTemplate(_) => SPRINTF_PARSE_ERROR,
+        SprintfError::ArityMismatch { .. } => SPRINTF_ARITY_MISMATCH,
+        SprintfError::UnresolvedVariable(_) => SPRINTF_UNRESOLVED_VARIABLE,
+        SprintfError::TypeMismatch { .. } => SPRINTF_TYPE_MISMATCH,
+        _ => SPRINTF_ERROR,
+    };
+    fail(code, &error.to_string())
+}
+
+/// Runs the body of an exported function, a panic fails with [SPRINTF_ERROR] as unwinding into the
+/// caller would abort it.
+fn guarded(body: impl FnOnce() -> c_int) -> c_int {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| fail(SPRINTF_ERROR, "Internal error (panic)"))
+}
+
+/// # Safety
+/// `s` must be null or a NUL terminated string.
+unsafe fn to_str<'a>(s: *const c_char) -> Result<&'a str, c_int> {
+    if s.is_null() {
+        return Err(fail(SPRINTF_NULL_POINTER, "Null pointer"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| fail(SPRINTF_INVALID_UTF8, "Invalid UTF-8"))
+}
+
+/// Values given by the caller, looked up in order.
+struct Named(Vec<(String, Value)>);
+
+impl Resolver for Named {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(n, _)| n == name).map(|(_, value)| value)
+    }
+}
+
+/// Parses the format string statement `statement` into `*template`.
+///
+/// # Safety
+/// `statement` must be a NUL terminated string and `template` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sprintf_parse(statement: *const c_char, template: *mut *mut SprintfTemplate) -> c_int {
+    guarded(|| {
+        if template.is_null() {
+            return fail(SPRINTF_NULL_POINTER, "Null pointer");
+        }
+        let statement = match to_str(statement) {
+            Ok(statement) => statement,
+            Err(code) => return code,
+        };
+        match parse_format_string(statement) {
+            Ok(parsed) => {
+                *template = Box::into_raw(Box::new(SprintfTemplate(parsed.into_owned())));
+                SPRINTF_OK
+            }
+            Err(error) => fail_with(&error),
+        }
+    })
+}
+
+/// Prints `template` with the `count` values at `values` into `*output`, which has to be released by
+/// [sprintf_string_free].
+///
+/// # Safety
+/// `template` must be created by [sprintf_parse], `values` point to `count` values with valid
+/// strings and `output` be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sprintf_format(
+    template: *const SprintfTemplate,
+    values: *const SprintfValue,
+    count: usize,
+    output: *mut *mut c_char,
+) -> c_int {
+    guarded(|| {
+        if template.is_null() || output.is_null() || (values.is_null() && count > 0) {
+            return fail(SPRINTF_NULL_POINTER, "Null pointer");
+        }
+        let values = match count {
+            0 => &[],
+            _ => slice::from_raw_parts(values, count),
+        };
+
+        let mut resolved = Vec::with_capacity(values.len());
+        for value in values {
+            let name = match to_str(value.name) {
+                Ok(name) => name,
+                Err(code) => return code,
+            };
+            let value = match value.string.is_null() {
+                true => Value::Numeric(value.number),
+                false => match to_str(value.string) {
+                    Ok(string) => Value::String(string.into()),
+                    Err(code) => return code,
+                },
+            };
+            resolved.push((name.to_string(), value));
+        }
+
+        match sprintf(&(*template).0, &Named(resolved)) {
+            Ok(printed) => match CString::new(printed) {
+                Ok(printed) => {
+                    *output = printed.into_raw();
+                    SPRINTF_OK
+                }
+                Err(_) => fail(SPRINTF_ERROR, "The output contains a NUL character"),
+            },
+            Err(error) => fail_with(&error),
+        }
+    })
+}
+
+/// Releases a template created by [sprintf_parse]. Does nothing for null.
+///
+/// # Safety
+/// `template` must be null or created by [sprintf_parse], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sprintf_free(template: *mut SprintfTemplate) {
+    if !template.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(template))));
+    }
+}
+
+/// Releases an output of [sprintf_format]. Does nothing for null.
+///
+/// # Safety
+/// `s` must be null or created by [sprintf_format], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sprintf_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(CString::from_raw(s))));
+    }
+}
+
+/// Describes the last failure of the calling thread. The string is valid until the next call
+/// failing on this thread and must not be released.
+#[no_mangle]
+pub extern "C" fn sprintf_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
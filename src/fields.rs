@@ -0,0 +1,237 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use crate::{
+    args::Numeric,
+    value::{Resolver, Value},
+};
+
+/// A value, or a struct of values, which can be resolved by a [FieldResolver]. Implemented for
+/// numbers, strings and `bool`, and for structs by `#[derive(SprintfResolver)]` with the `derive`
+/// feature. Fields of nested structs are resolved by dotted paths like `user.name`.
+/// ```
+/// use sprintf::{
+///     fields::{join, Field, FieldResolver, Values},
+///     parser::parse_format_string,
+///     printer::sprintf,
+/// };
+///
+/// struct User {
+///     name: String,
+///     logins: u32,
+/// }
+///
+/// impl Field for User {
+///     fn collect(&self, path: &str, values: &mut Values) {
+///         self.name.collect(&join(path, "name"), values);
+///         self.logins.collect(&join(path, "logins"), values);
+///     }
+/// }
+///
+/// struct Request {
+///     user: User,
+///     took: f64,
+///     referrer: Option<String>,
+/// }
+///
+/// impl Field for Request {
+///     fn collect(&self, path: &str, values: &mut Values) {
+///         self.user.collect(&join(path, "user"), values);
+///         self.took.collect(&join(path, "took"), values);
+///         self.referrer.collect(&join(path, "referrer"), values);
+///     }
+/// }
+///
+/// let request = Request {
+///     user: User {
+///         name: "foo".into(),
+///         logins: 3,
+///     },
+///     took: 1.5,
+///     referrer: None,
+/// };
+/// let parsed = parse_format_string(r#""%s (%d) took %.1fs", user.name, user.logins, took"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &FieldResolver::new(&request)).unwrap(), "foo (3) took 1.5s");
+///
+/// // unset options can't be resolved
+/// let parsed = parse_format_string(r#""from %{:-nowhere}s", referrer"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &FieldResolver::new(&request)).unwrap(), "from nowhere");
+/// ```
+pub trait Field {
+    /// Adds the value of `self` as variable `path`, or all its fields below `path`.
+    fn collect(&self, path: &str, values: &mut Values);
+}
+
+/// Variables by their path, as collected by [Field::collect].
+pub type Values = BTreeMap<String, Value>;
+
+/// The path of the field `name` of the struct at `path`.
+pub fn join(path: &str, name: &str) -> String {
+    match path.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", path, name),
+    }
+}
+
+/// Kind of a value made of other values, see [collect_composite].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Composite {
+    /// Collected as [Value::Sequence].
+    Sequence,
+    /// Collected as [Value::Map].
+    Map,
+}
+
+/// Adds the whole composite at `path` as [Value::Sequence] or [Value::Map], made of the values of
+/// its `elements` already collected below `path`. So `%v` of a list or struct prints all of it like
+/// Go does, `%+v` with the field names. Elements without a value, like unset options, are left out.
+/// Nothing is added for the root, which has no name.
+/// ```
+/// use sprintf::{
+///     fields::{collect_composite, join, Composite, Field, FieldResolver, Values},
+///     parser::parse_format_string,
+///     printer::sprintf,
+/// };
+///
+/// struct Server {
+///     name: String,
+///     ports: Vec<u16>,
+///     alias: Option<String>,
+/// }
+///
+/// impl Field for Server {
+///     fn collect(&self, path: &str, values: &mut Values) {
+///         self.name.collect(&join(path, "name"), values);
+///         self.ports.collect(&join(path, "ports"), values);
+///         self.alias.collect(&join(path, "alias"), values);
+///         collect_composite(path, Composite::Map, ["name", "ports", "alias"], values);
+///     }
+/// }
+///
+/// struct Config {
+///     server: Server,
+/// }
+///
+/// impl Field for Config {
+///     fn collect(&self, path: &str, values: &mut Values) {
+///         self.server.collect(&join(path, "server"), values);
+///     }
+/// }
+///
+/// let config = Config {
+///     server: Server {
+///         name: "foo".into(),
+///         ports: vec![80, 443],
+///         alias: None,
+///     },
+/// };
+/// let parsed = parse_format_string(r#""%v on %v, first %d", server, server.ports, server.ports.0"#).unwrap();
+/// assert_eq!(
+///     sprintf(&parsed, &FieldResolver::new(&config)).unwrap(),
+///     "{foo [80 443]} on [80 443], first 80"
+/// );
+///
+/// let parsed = parse_format_string(r#""%+v", server"#).unwrap();
+/// assert_eq!(
+///     sprintf(&parsed, &FieldResolver::new(&config)).unwrap(),
+///     "{name:foo ports:[80 443]}"
+/// );
+/// ```
+pub fn collect_composite<'e>(
+    path: &str,
+    composite: Composite,
+    elements: impl IntoIterator<Item = &'e str>,
+    values: &mut Values,
+) {
+    if path.is_empty() {
+        return;
+    }
+    let elements = elements
+        .into_iter()
+        .filter_map(|element| Some((element, values.get(&join(path, element))?.clone())));
+    let value = match composite {
+        Composite::Sequence => Value::Sequence(elements.map(|(_, value)| value).collect()),
+        Composite::Map => Value::Map(elements.map(|(element, value)| (element.to_string(), value)).collect()),
+    };
+    values.insert(path.to_string(), value);
+}
+
+impl<T: Numeric> Field for T {
+    fn collect(&self, path: &str, values: &mut Values) {
+        let value = self
+            .as_i128()
+            .map_or_else(|| Value::Numeric(self.as_f64()), Value::Integer);
+        values.insert(path.to_string(), value);
+    }
+}
+
+macro_rules! impl_display {
+    ($($t:ty),*) => {
+        $(impl Field for $t {
+            fn collect(&self, path: &str, values: &mut Values) {
+                values.insert(path.to_string(), Value::String(self.to_string()));
+            }
+        })*
+    };
+}
+
+impl_display!(str, &str, String, Cow<'_, str>, bool, char);
+
+impl Field for Value {
+    fn collect(&self, path: &str, values: &mut Values) {
+        values.insert(path.to_string(), self.clone());
+    }
+}
+
+/// Elements are added by their index like `items.0`, and all of them as [Composite::Sequence].
+impl<T: Field> Field for [T] {
+    fn collect(&self, path: &str, values: &mut Values) {
+        let indices: Vec<_> = (0..self.len()).map(|index| index.to_string()).collect();
+        for (element, index) in self.iter().zip(&indices) {
+            element.collect(&join(path, index), values);
+        }
+        collect_composite(path, Composite::Sequence, indices.iter().map(String::as_str), values);
+    }
+}
+
+impl<T: Field> Field for Vec<T> {
+    fn collect(&self, path: &str, values: &mut Values) {
+        self.as_slice().collect(path, values);
+    }
+}
+
+/// Nothing is added for `None`, so the variable can't be resolved.
+impl<T: Field> Field for Option<T> {
+    fn collect(&self, path: &str, values: &mut Values) {
+        if let Some(value) = self {
+            value.collect(path, values);
+        }
+    }
+}
+
+/// Resolves the fields of a [Field], e.g. a struct deriving `SprintfResolver`. As a [Resolver] lends
+/// its values, they are converted once when the resolver is created.
+#[derive(Debug, Clone, Default)]
+pub struct FieldResolver {
+    values: Values,
+}
+
+impl FieldResolver {
+    pub fn new(fields: &(impl Field + ?Sized)) -> Self {
+        let mut values = Values::new();
+        fields.collect("", &mut values);
+        Self { values }
+    }
+}
+
+/// Variables collected elsewhere, e.g. from the command line.
+impl From<Values> for FieldResolver {
+    fn from(values: Values) -> Self {
+        Self { values }
+    }
+}
+
+impl Resolver for FieldResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+}
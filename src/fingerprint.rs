@@ -0,0 +1,95 @@
+use std::hash::{Hash, Hasher};
+
+use crate::parser::{Entry, ParsedFormat};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a with platform independent integer encoding, so fingerprints are stable across builds.
+struct Fnv1a(u64);
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64)
+    }
+}
+
+impl ParsedFormat<'_> {
+    /// Stable hash over the parsed structure of the format string.
+    ///
+    /// Different spellings of the same template, e.g. whitespace in the variable list or text split up
+    /// into several entries, share the same fingerprint, while any change to the text, a placeholder
+    /// or a variable changes it.
+    /// ```
+    /// use sprintf::parser::{parse_format_string, Entry, ParsedFormat};
+    ///
+    /// let a = parse_format_string(r#""%s: %d%%", name, count"#).unwrap();
+    /// let b = parse_format_string(r#""%s: %d%%",name,count"#).unwrap();
+    /// let c = parse_format_string(r#""%s: %d%%", name, total"#).unwrap();
+    /// assert_eq!(a.fingerprint(), b.fingerprint());
+    /// assert_ne!(a.fingerprint(), c.fingerprint());
+    ///
+    /// let d = ParsedFormat::new(
+    ///     vec![Entry::Text("a".into()), Entry::Text("b".into())],
+    ///     vec![],
+    /// );
+    /// let e = ParsedFormat::new(vec![Entry::Text("ab".into())], vec![]);
+    /// assert_eq!(d.fingerprint(), e.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a(FNV_OFFSET_BASIS);
+
+        // adjacent text entries are merged, as they print the same
+        let mut text = String::new();
+        for entry in &self.entries {
+            match entry {
+                Entry::Text(t) => text.push_str(t),
+                Entry::Placeholder(placeholder) => {
+                    if !text.is_empty() {
+                        Entry::Text(std::mem::take(&mut text).into()).hash(&mut hasher);
+                    }
+                    placeholder.hash(&mut hasher);
+                }
+                Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => {
+                    if !text.is_empty() {
+                        Entry::Text(std::mem::take(&mut text).into()).hash(&mut hasher);
+                    }
+                    entry.hash(&mut hasher);
+                }
+            }
+        }
+        if !text.is_empty() {
+            Entry::Text(text.into()).hash(&mut hasher);
+        }
+        self.variables.hash(&mut hasher);
+
+        hasher.finish()
+    }
+}
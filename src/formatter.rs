@@ -0,0 +1,150 @@
+use crate::{
+    error::{Result, SprintfError},
+    options::{ConversionHandler, FilterHandler, ParseOptions, PrintOptions},
+    parser::{parse_format_string_with, ParsedFormat},
+    printer::sprintf_with_options,
+    specifiers::Conversion,
+    types::Filter,
+    value::{Resolver, Value},
+};
+
+/// Registry of custom conversions like `%U` and filters like `%{slug}s`, together with the options to parse and print
+/// templates using them.
+///
+/// Custom conversions must be letters which are no built-in conversion. Their handler gets the
+/// value and the raw flags of the placeholder, e.g. `-8` for `%-8U`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     error::SprintfError,
+///     formatter::Formatter,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut formatter = Formatter::new();
+/// formatter
+///     .register('U', |value, spec| {
+///         let secs = match value {
+///             Value::Numeric(secs) => *secs as u64,
+///             _ => return Err(SprintfError::TypeMismatch { expected: "duration" }),
+///         };
+///         let width = spec.parse().unwrap_or(0);
+///         Ok(format!("{:>width$}", format!("{}m{:02}s", secs / 60, secs % 60)))
+///     })
+///     .unwrap();
+///
+/// let parsed = formatter.parse(r#""%s took %U (%8U)", job, took, took"#).unwrap();
+/// assert_eq!(parsed.entries.len(), 6);
+/// assert_eq!(parsed.to_string(), r#""%s took %U (%8U)", job, took, took"#);
+///
+/// let mut values = HashMap::new();
+/// values.insert("job", Value::String("backup".into()));
+/// values.insert("took", Value::Numeric(90.0));
+/// let values: MapResolver = values.into();
+/// assert_eq!(formatter.sprintf(&parsed, &values).unwrap(), "backup took 1m30s (   1m30s)");
+///
+/// // without the registry
+/// assert!(sprintf::parser::parse_format_string(r#""%U", took"#).is_err());
+/// assert_eq!(
+///     sprintf::printer::sprintf(&parsed, &values).unwrap_err().to_string(),
+///     "placeholder #2 (%U) for variable `took`: No handler registered for conversion 'U'"
+/// );
+///
+/// assert!(formatter.register('d', |value, _| Ok(value.to_string())).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Formatter {
+    parse_options: ParseOptions,
+    print_options: PrintOptions,
+}
+
+impl Formatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts with the given options, e.g. a different quote or locale.
+    pub fn with_options(parse_options: ParseOptions, print_options: PrintOptions) -> Self {
+        let mut formatter = Self {
+            parse_options,
+            print_options,
+        };
+        formatter.parse_options.custom_conversions = formatter.print_options.conversions.keys().copied().collect();
+        formatter
+    }
+
+    /// Registers `handler` for `%c`, replacing a previously registered one. Fails if `c` isn't a
+    /// letter or is a built-in conversion.
+    pub fn register(
+        &mut self,
+        c: char,
+        handler: impl Fn(&Value, &str) -> Result<String> + Send + Sync + 'static,
+    ) -> Result<&mut Self> {
+        if !c.is_alphabetic() || Conversion::from_char(c).is_some() {
+            return Err(SprintfError::InvalidPlaceholder(format!("%{}", c)));
+        }
+        if !self.parse_options.custom_conversions.contains(&c) {
+            self.parse_options.custom_conversions.push(c);
+        }
+        self.print_options
+            .conversions
+            .insert(c, ConversionHandler::new(handler));
+        Ok(self)
+    }
+
+    /// Registers `handler` for the filter `name`, e.g. `slug` for `%{slug}s`, replacing a previously
+    /// registered one. Fails if `name` is no valid filter name or a built-in filter.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     formatter::Formatter,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut formatter = Formatter::new();
+    /// formatter
+    ///     .register_filter("slug", |value| value.to_lowercase().replace(' ', "-"))
+    ///     .unwrap();
+    /// assert!(formatter.register_filter("upper", |value| value.into()).is_err());
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("title", Value::String(" Hello World ".into()));
+    /// let values: MapResolver = values.into();
+    /// let parsed = formatter.parse(r#""/%{trim|slug}s", title"#).unwrap();
+    /// assert_eq!(formatter.sprintf(&parsed, &values).unwrap(), "/hello-world");
+    /// ```
+    pub fn register_filter(
+        &mut self,
+        name: &str,
+        handler: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Result<&mut Self> {
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !valid || Filter::from_name(name) != Filter::Custom(name.into()) {
+            return Err(SprintfError::InvalidPlaceholder(format!("%{{{}}}", name)));
+        }
+        self.print_options
+            .filters
+            .insert(name.into(), FilterHandler::new(handler));
+        Ok(self)
+    }
+
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    pub fn print_options(&self) -> &PrintOptions {
+        &self.print_options
+    }
+
+    /// Like [parse_format_string_with], with the custom conversions.
+    pub fn parse<'a>(&self, input: &'a str) -> Result<ParsedFormat<'a>> {
+        parse_format_string_with(input, &self.parse_options)
+    }
+
+    /// Like [sprintf_with_options], with the handlers of the custom conversions.
+    pub fn sprintf(&self, parsed: &ParsedFormat, resolver: &impl Resolver) -> Result<String> {
+        sprintf_with_options(parsed, resolver, &self.print_options)
+    }
+}
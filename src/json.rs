@@ -0,0 +1,67 @@
+use crate::{
+    fields::{collect_composite, join, Composite, Field, FieldResolver, Values},
+    value::{Resolver, Value},
+};
+
+/// Objects add their fields by dotted paths, arrays their elements by index like `items.0`, both
+/// also as a whole for `%v`, see [collect_composite]. `null` adds nothing, so it can't be resolved.
+impl Field for serde_json::Value {
+    fn collect(&self, path: &str, values: &mut Values) {
+        match self {
+            serde_json::Value::Null => {}
+            serde_json::Value::Bool(boolean) => boolean.collect(path, values),
+            serde_json::Value::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    integer.collect(path, values);
+                } else if let Some(integer) = number.as_u64() {
+                    integer.collect(path, values);
+                } else if let Some(number) = number.as_f64() {
+                    number.collect(path, values);
+                }
+            }
+            serde_json::Value::String(string) => string.collect(path, values),
+            serde_json::Value::Array(elements) => elements.as_slice().collect(path, values),
+            serde_json::Value::Object(fields) => {
+                for (name, field) in fields {
+                    field.collect(&join(path, name), values);
+                }
+                collect_composite(path, Composite::Map, fields.keys().map(String::as_str), values);
+            }
+        }
+    }
+}
+
+/// Resolves variables like `user.name` or `items.0.price` from a JSON document. As a [Resolver]
+/// lends its values, the document is converted once when the resolver is created.
+/// ```
+/// use sprintf::{json::JsonResolver, parser::parse_format_string, printer::sprintf};
+///
+/// let document = r#"{"user": {"name": "foo"}, "items": [{"price": 9.5}, {"price": 0.25}], "note": null}"#;
+/// let resolver = JsonResolver::new(&serde_json::from_str(document).unwrap());
+///
+/// let parsed = parse_format_string(r#""%s paid %.2f", user.name, items.0.price + items.1.price"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo paid 9.75");
+///
+/// let parsed = parse_format_string(r#""%{:-none}s", note"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "none");
+///
+/// let parsed = parse_format_string(r#""%v", items"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "[{9.5} {0.25}]");
+///
+/// let parsed = parse_format_string(r#""%+v", user"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "{name:foo}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonResolver(FieldResolver);
+
+impl JsonResolver {
+    pub fn new(document: &serde_json::Value) -> Self {
+        Self(FieldResolver::new(document))
+    }
+}
+
+impl Resolver for JsonResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.resolve(name)
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{
+    error::{Result, SprintfError},
+    options::{PrintOptions, WidthMode},
+    parser::{parse_format_string, Entry, ParsedFormat, ParsedFormatString, Placeholder},
+    printer::sprintf_with_options,
+    types::{Alignment, DurationFormat, FloatFormat, NumberFormat, Overflow, StringFormat},
+    value::Resolver,
+};
+
+#[cfg(feature = "chrono")]
+use crate::types::DateTimeFormat;
+
+/// How wide the output of an entry will be, in characters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentWidth {
+    /// Always exactly this wide, e.g. literal text or a padded placeholder with an [Overflow] strategy.
+    Fixed(usize),
+    /// Padded to this width, but longer values overflow.
+    Min(usize),
+    /// Depends on the printed value.
+    Unknown,
+}
+
+/// Output region of a single entry of a [ParsedFormatString].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    /// Index into [ParsedFormatString::entries].
+    pub entry: usize,
+    pub width: SegmentWidth,
+}
+
+fn padded_width(nf: &NumberFormat) -> SegmentWidth {
+    match (nf.digits, nf.fill(), nf.overflow) {
+        (Some(digits), Some(_), Overflow::Extend) => SegmentWidth::Min(digits as usize),
+        (Some(digits), Some(_), _) => SegmentWidth::Fixed(digits as usize),
+        _ => SegmentWidth::Unknown,
+    }
+}
+
+fn string_width(sf: &StringFormat) -> SegmentWidth {
+    match (sf.width, sf.overflow) {
+        (Some(width), Overflow::Extend) => SegmentWidth::Min(width as usize),
+        (Some(width), _) => SegmentWidth::Fixed(width as usize),
+        (None, _) => SegmentWidth::Unknown,
+    }
+}
+
+fn float_width(ff: &FloatFormat) -> SegmentWidth {
+    match (ff.base.align, padded_width(&ff.base), ff.fraction.digits) {
+        // the width only covers the integer part, followed by the dot and the fraction
+        (Alignment::Right, SegmentWidth::Fixed(width), Some(fraction)) => {
+            SegmentWidth::Fixed(width + if fraction > 0 { fraction as usize + 1 } else { 0 })
+        }
+        // without a precision the fraction is as long as the value needs
+        (Alignment::Right, SegmentWidth::Fixed(width), None) => SegmentWidth::Min(width),
+        // at least the dot and one digit
+        (Alignment::Right, SegmentWidth::Min(width), Some(_)) => SegmentWidth::Min(width + 2),
+        (_, width, _) => width,
+    }
+}
+
+fn placeholder_width(placeholder: &Placeholder) -> SegmentWidth {
+    match placeholder {
+        Placeholder::Display(sf) | Placeholder::String(sf) => string_width(sf),
+        Placeholder::Number(nf) => padded_width(nf),
+        Placeholder::Hex(hf) => padded_width(&hf.nf),
+        Placeholder::Float(ff) => float_width(ff),
+        // right aligned, the `%` follows the padded number
+        Placeholder::Percent(ff) => match (ff.base.align, float_width(ff)) {
+            (Alignment::Right, SegmentWidth::Fixed(width)) => SegmentWidth::Fixed(width + 1),
+            (Alignment::Right, SegmentWidth::Min(width)) => SegmentWidth::Min(width + 1),
+            (_, width) => width,
+        },
+        Placeholder::Duration(DurationFormat { base: sf, .. }) => string_width(sf),
+        Placeholder::Size(sf) => padded_width(&sf.ff.base),
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(DateTimeFormat { base: sf, .. }) => string_width(sf),
+        Placeholder::Custom(..) => SegmentWidth::Unknown,
+        // C never truncates, its width counts bytes
+        Placeholder::C99(cf) => match cf.width {
+            Some(width) => SegmentWidth::Min(width as usize),
+            None => SegmentWidth::Unknown,
+        },
+        // filters change the value, not its padding
+        Placeholder::Modified(_, inner) => placeholder_width(inner),
+    }
+}
+
+fn entry_width(entry: &Entry) -> SegmentWidth {
+    match entry {
+        Entry::Text(text) => SegmentWidth::Fixed(text.chars().count()),
+        // escape sequences take no space on a terminal
+        Entry::Style(_) | Entry::SectionEnd(_) => SegmentWidth::Fixed(0),
+        // the whole section may be skipped
+        Entry::Section(_) => SegmentWidth::Unknown,
+        Entry::Placeholder(placeholder) => placeholder_width(placeholder),
+    }
+}
+
+/// Assumed length of a value whose placeholder has no width.
+const ESTIMATED_VALUE_LEN: usize = 8;
+
+/// Estimated length of the printed `entries` in bytes, to allocate the output once.
+pub(crate) fn size_hint(entries: &[Entry]) -> usize {
+    entries
+        .iter()
+        .map(|entry| match (entry, entry_width(entry)) {
+            (Entry::Text(text), _) => text.len(),
+            (Entry::Style(styles), _) => styles.len() * 3 + 2,
+            (Entry::Section(_) | Entry::SectionEnd(_), _) => 0,
+            (_, SegmentWidth::Fixed(width) | SegmentWidth::Min(width)) => width,
+            (_, SegmentWidth::Unknown) => ESTIMATED_VALUE_LEN,
+        })
+        .sum()
+}
+
+impl ParsedFormat<'_> {
+    /// Estimated length of the output in bytes: the literal text plus the width of every
+    /// placeholder, or a guess for placeholders without one. Printing to a [String] allocates it
+    /// with this capacity.
+    /// ```
+    /// use sprintf::parser::parse_format_string;
+    ///
+    /// let parsed = parse_format_string(r#""id=%08x name=%-12s note=%s", id, name, note"#).unwrap();
+    /// assert_eq!(parsed.size_hint(), 3 + 8 + 6 + 12 + 6 + 8);
+    /// ```
+    pub fn size_hint(&self) -> usize {
+        size_hint(&self.entries)
+    }
+
+    /// Describes the width of every entry without printing anything, e.g. to reserve table columns.
+    /// ```
+    /// use sprintf::{layout::SegmentWidth, parser::parse_format_string};
+    ///
+    /// let parsed = parse_format_string(r#""id=%08x name=%-12s note=%s", id, name, note"#).unwrap();
+    /// let widths: Vec<_> = parsed.fixed_width_segments().iter().map(|s| s.width).collect();
+    /// assert_eq!(
+    ///     widths,
+    ///     vec![
+    ///         SegmentWidth::Fixed(3),
+    ///         SegmentWidth::Min(8),
+    ///         SegmentWidth::Fixed(6),
+    ///         SegmentWidth::Min(12),
+    ///         SegmentWidth::Fixed(6),
+    ///         SegmentWidth::Unknown,
+    ///     ]
+    /// );
+    /// ```
+    pub fn fixed_width_segments(&self) -> Vec<Segment> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(entry, e)| Segment {
+                entry,
+                width: entry_width(e),
+            })
+            .collect()
+    }
+}
+
+/// A template for fixed-width records like mainframe data files, where every line has the same
+/// length.
+///
+/// Every placeholder needs a width, a fill and an [Overflow] strategy, e.g. `%-!10s` or `%!#06d`,
+/// which is checked when the record is created. Every printed line is checked again.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     layout::FixedRecord,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let record = FixedRecord::parse(r#""%-!8s|%!#06d|%!#04.1f", name, amount, rate"#).unwrap();
+/// assert_eq!(record.length(), 22);
+///
+/// let mut values = HashMap::new();
+/// values.insert("name", Value::String("a long name".into()));
+/// values.insert("amount", Value::Numeric(1234567.0));
+/// values.insert("rate", Value::Numeric(0.5));
+/// let values: MapResolver = values.into();
+/// assert_eq!(record.sprintf(&values).unwrap(), "a long n|######|0000.5");
+///
+/// assert!(FixedRecord::parse(r#""%-!8s|%06d", name, amount"#).is_err());
+/// ```
+#[derive(Debug)]
+pub struct FixedRecord {
+    parsed: ParsedFormatString,
+    length: usize,
+}
+
+impl FixedRecord {
+    /// Fails if any entry of `parsed` has no fixed width.
+    pub fn new(parsed: ParsedFormatString) -> Result<Self> {
+        let mut length = 0;
+        for segment in parsed.fixed_width_segments() {
+            match segment.width {
+                SegmentWidth::Fixed(width) => length += width,
+                SegmentWidth::Min(_) | SegmentWidth::Unknown => {
+                    return Err(SprintfError::InvalidTemplate(format!(
+                        "'{}' has no fixed width",
+                        parsed.entries[segment.entry]
+                    )))
+                }
+            }
+        }
+
+        Ok(Self { parsed, length })
+    }
+
+    /// Parses a statement like [parse_format_string] and checks it like [FixedRecord::new].
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::new(parse_format_string(input)?.into_owned())
+    }
+
+    pub fn parsed(&self) -> &ParsedFormatString {
+        &self.parsed
+    }
+
+    /// Length of every record in characters.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Prints a single record and fails with [SprintfError::RecordLength] if it doesn't have the
+    /// expected length after all. Widths are measured in characters, see [WidthMode::Chars].
+    pub fn sprintf(&self, resolver: &impl Resolver) -> Result<String> {
+        let options = PrintOptions {
+            width_mode: WidthMode::Chars,
+            ..Default::default()
+        };
+        let result = sprintf_with_options(&self.parsed, resolver, &options)?;
+        match result.chars().count() {
+            actual if actual == self.length => Ok(result),
+            actual => Err(SprintfError::RecordLength {
+                expected: self.length,
+                actual,
+            }),
+        }
+    }
+}
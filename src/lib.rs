@@ -1,4 +1,50 @@
+pub mod args;
+#[cfg(feature = "async")]
+pub mod async_resolver;
+pub mod batch;
+pub mod builder;
+pub mod c99;
+pub mod cache;
+mod collections;
+pub mod compiled;
+pub mod document;
+mod dump;
+pub mod editor;
+pub mod env;
+pub mod error;
+pub mod expression;
 pub mod extractor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fields;
+mod fingerprint;
+pub mod formatter;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod layout;
+pub mod lint;
+pub mod mustache;
+pub mod options;
 pub mod parser;
+pub mod partial;
+pub mod policy;
 pub mod printer;
+pub mod resolvers;
+pub mod scanner;
+pub mod source_map;
+pub mod specifiers;
+pub mod std_fmt;
+pub mod stream;
+pub mod tokenizer;
+#[cfg(feature = "toml")]
+pub mod toml;
 pub mod types;
+pub mod validate;
+pub mod value;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+#[cfg(feature = "derive")]
+pub use sprintf_derive::SprintfResolver;
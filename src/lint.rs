@@ -0,0 +1,146 @@
+use crate::{
+    expression::Expression,
+    parser::{Entry, ParsedFormat, Placeholder, Span},
+};
+
+/// Widths above this are most likely typos like `%1000s` for `%100s`.
+const MAX_PLAUSIBLE_WIDTH: u16 = 512;
+
+/// Words in variable names that suggest a number, like the `count` in `retry_count`.
+const NUMERIC_WORDS: &[&str] = &[
+    "age", "amount", "bytes", "count", "duration", "elapsed", "len", "length", "ms", "num", "number", "pct", "percent",
+    "port", "price", "qty", "quantity", "rate", "ratio", "sec", "secs", "seconds", "size", "total",
+];
+
+/// What a [LintWarning] is about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Lint {
+    /// A variable no placeholder is bound to.
+    UnusedVariable(String),
+    /// A variable bound to more than one placeholder, reported for all but the first.
+    DuplicateVariable(String),
+    /// A width of zero like `%0d`, which pads nothing, or an implausibly large one.
+    SuspiciousWidth(u16),
+    /// `%s` bound to a variable whose name suggests a number like `retry_count`, which fails for
+    /// numeric values.
+    StringForNumber(String),
+}
+
+/// A possible problem of a template found by [lint]. Unlike errors, the template still prints.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    pub lint: Lint,
+    /// Source span of the placeholder, `None` if the template wasn't parsed from a string or the
+    /// warning concerns the variable list.
+    pub span: Option<Span>,
+}
+
+/// The width of `placeholder`, if it has one.
+fn width(placeholder: &Placeholder) -> Option<u16> {
+    match placeholder {
+        Placeholder::Display(sf) | Placeholder::String(sf) => sf.width,
+        Placeholder::Number(nf) => nf.digits,
+        Placeholder::Hex(hf) => hf.nf.digits,
+        Placeholder::Float(ff) | Placeholder::Percent(ff) => ff.base.digits,
+        Placeholder::Size(sf) => sf.ff.base.digits,
+        Placeholder::Duration(df) => df.base.width,
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(dt) => dt.base.width,
+        Placeholder::C99(cf) => cf.width,
+        Placeholder::Custom(..) => None,
+        Placeholder::Modified(_, inner) => width(inner),
+    }
+}
+
+/// Whether the last segment of `variable` contains a [NUMERIC_WORDS] word, in snake or camel case.
+fn is_numeric_name(variable: &str) -> bool {
+    let name = variable.rsplit('.').next().unwrap_or(variable);
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() || (c.is_uppercase() && previous_lower) {
+            words.push(std::mem::take(&mut word));
+        }
+        if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        }
+        previous_lower = c.is_lowercase();
+    }
+    words.push(word);
+    words.iter().any(|word| NUMERIC_WORDS.contains(&word.as_str()))
+}
+
+/// Finds likely mistakes in a template which still parses, e.g. to show warnings in an editor:
+/// unused and duplicate variables, suspicious widths and `%s` for variables named like numbers.
+/// ```
+/// use sprintf::{
+///     lint::{lint, Lint},
+///     parser::{parse_format_string, ParsedFormat},
+/// };
+///
+/// let parsed = parse_format_string(r#""%s retried %0d times, %s after %5000dms", user, retryCount, user, wait_ms"#).unwrap();
+/// let warnings = lint(&parsed);
+/// assert_eq!(warnings.len(), 3);
+/// assert_eq!(warnings[0].lint, Lint::SuspiciousWidth(0));
+/// assert_eq!(warnings[0].span, Some(12..15));
+/// assert_eq!(warnings[1].lint, Lint::DuplicateVariable("user".into()));
+/// assert_eq!(warnings[2].lint, Lint::SuspiciousWidth(5000));
+///
+/// let parsed = parse_format_string(r#""%s of %s items, %d bytes", page.number, user.name, total_bytes"#).unwrap();
+/// let warnings = lint(&parsed);
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!(warnings[0].lint, Lint::StringForNumber("page.number".into()));
+///
+/// let parsed = ParsedFormat::new(parse_format_string(r#""%s", user"#).unwrap().entries, vec!["user".into(), "host".into()]);
+/// let warnings = lint(&parsed);
+/// assert_eq!(warnings[0].lint, Lint::UnusedVariable("host".into()));
+/// assert_eq!(warnings[0].span, None);
+/// ```
+pub fn lint(parsed: &ParsedFormat) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    let placeholders = parsed
+        .entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| match entry {
+            Entry::Placeholder(placeholder) => Some((placeholder, parsed.spans.get(index).cloned())),
+            Entry::Text(_) | Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => None,
+        });
+    let mut bound = 0;
+    for (position, ((placeholder, span), (variable, argument))) in placeholders
+        .zip(parsed.variables.iter().zip(&parsed.arguments))
+        .enumerate()
+    {
+        bound += 1;
+        let mut warn = |lint| {
+            warnings.push(LintWarning {
+                lint,
+                span: span.clone(),
+            })
+        };
+        match width(placeholder) {
+            Some(width) if width == 0 || width > MAX_PLAUSIBLE_WIDTH => warn(Lint::SuspiciousWidth(width)),
+            _ => {}
+        }
+        if parsed.variables[..position].contains(variable) {
+            warn(Lint::DuplicateVariable(variable.clone()));
+        }
+        let string = match placeholder {
+            Placeholder::String(_) => true,
+            Placeholder::C99(cf) => cf.conversion == 's',
+            _ => false,
+        };
+        if string && matches!(argument, Expression::Variable(_)) && is_numeric_name(variable) {
+            warn(Lint::StringForNumber(variable.clone()));
+        }
+    }
+    for variable in &parsed.variables[bound..] {
+        warnings.push(LintWarning {
+            lint: Lint::UnusedVariable(variable.clone()),
+            span: None,
+        });
+    }
+
+    warnings
+}
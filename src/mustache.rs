@@ -0,0 +1,105 @@
+use crate::{
+    error::{Result, SprintfError},
+    parser::{Entry, ParsedFormat, Placeholder},
+    types::StringFormat,
+};
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// Parses a mustache-lite template like `Hello {{user.name}}!` into a [ParsedFormat].
+///
+/// Every `{{variable}}` becomes a [Placeholder::Display] bound to `variable`, so the result can be
+/// printed with [crate::printer::sprintf] just like a printf style format string.
+/// `{{! comments }}` are dropped. A section `{{#error}}...{{/error}}` becomes an [Entry::Section],
+/// which is printed only if its variable resolves. Unlike in mustache, sections don't repeat for
+/// lists, and inverted sections (`{{^...}}`) are not supported and result in an error.
+/// ```
+/// use sprintf::{mustache::parse_mustache, parser::*};
+///
+/// let parsed = parse_mustache("Hello {{ user.name }}, you have {{count}} new mails{{! unread }}").unwrap();
+/// assert_eq!(parsed.variables, vec!["user.name".to_string(), "count".to_string()]);
+/// assert_eq!(parsed.entries.len(), 5);
+/// assert_eq!(parsed.entries[0], Entry::Text("Hello ".into()));
+/// assert_eq!(parsed.entries[1], Entry::Placeholder(Placeholder::Display(Default::default())));
+/// assert_eq!(parsed.entries[4], Entry::Text(" new mails".into()));
+///
+/// assert!(parse_mustache("Hello {{user.name").is_err());
+/// assert!(parse_mustache("{{^items}}x{{/items}}").is_err());
+/// assert!(parse_mustache("{{#items}}x").is_err());
+///
+/// let parsed = parse_mustache("{{job}}{{#error}} failed: {{error}}{{/error}}").unwrap();
+/// assert_eq!(parsed.entries[1], Entry::Section("error".into()));
+/// assert_eq!(parsed.entries[4], Entry::SectionEnd("error".into()));
+/// assert_eq!(parsed.names(), vec!["job", "error"]);
+/// ```
+pub fn parse_mustache(input: &str) -> Result<ParsedFormat<'_>> {
+    let mut entries = Vec::new();
+    let mut variables = Vec::new();
+    let mut sections = Vec::new();
+
+    let mut rest = input;
+    while let Some(start) = rest.find(OPEN) {
+        let offset = input.len() - rest.len();
+        if start > 0 {
+            entries.push((Entry::Text(rest[..start].into()), offset..offset + start));
+        }
+        let tag_start = start + OPEN.len();
+        let tag_len = rest[tag_start..]
+            .find(CLOSE)
+            .ok_or_else(|| SprintfError::InvalidTemplate(format!("No terminating '{}' found", CLOSE)))?;
+        let tag = rest[tag_start..tag_start + tag_len].trim();
+
+        let span = offset + start..offset + tag_start + tag_len + CLOSE.len();
+        match tag.chars().next() {
+            None => return Err(SprintfError::InvalidTemplate(format!("Empty tag '{}{}'", OPEN, CLOSE))),
+            Some('!') => {}
+            Some('#' | '/') if tag[1..].trim().is_empty() => {
+                return Err(SprintfError::InvalidTemplate(format!(
+                    "Section without variable: '{}'",
+                    tag
+                )))
+            }
+            Some('#') => {
+                let variable = tag[1..].trim().to_string();
+                sections.push(variable.clone());
+                entries.push((Entry::Section(variable), span));
+            }
+            Some('/') => {
+                let variable = tag[1..].trim();
+                match sections.pop() {
+                    Some(started) if started == variable => entries.push((Entry::SectionEnd(started), span)),
+                    _ => {
+                        return Err(SprintfError::InvalidTemplate(format!(
+                            "section `{}` ends without being started",
+                            variable
+                        )))
+                    }
+                }
+            }
+            Some('^') => {
+                return Err(SprintfError::InvalidTemplate(format!(
+                    "Inverted sections are not supported: '{}'",
+                    tag
+                )))
+            }
+            Some(_) => {
+                entries.push((Entry::Placeholder(Placeholder::Display(StringFormat::default())), span));
+                variables.push(tag.into());
+            }
+        }
+
+        rest = &rest[tag_start + tag_len + CLOSE.len()..];
+    }
+    if let Some(variable) = sections.pop() {
+        return Err(SprintfError::InvalidTemplate(format!(
+            "section `{}` is not closed",
+            variable
+        )));
+    }
+    if !rest.is_empty() {
+        entries.push((Entry::Text(rest.into()), input.len() - rest.len()..input.len()));
+    }
+
+    Ok(ParsedFormat::new_spanned(entries, variables))
+}
@@ -0,0 +1,916 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use crate::{
+    error::{Result, SprintfError},
+    parser::{Entry, Placeholder},
+    value::Value,
+};
+
+/// Where a [TemplateTag] is placed in the printed output.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TagPosition {
+    #[default]
+    Prepend,
+    Append,
+}
+
+/// What a [TemplateTag] identifies the template with.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TagLabel {
+    /// The first 8 hex digits of [crate::parser::ParsedFormatString::fingerprint].
+    #[default]
+    Fingerprint,
+    Name(String),
+}
+
+/// Marks printed output with the template it originates from, e.g. `[1b2c3d4e] Hello world`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateTag {
+    pub label: TagLabel,
+    pub position: TagPosition,
+    /// Text the label is printed with, `{}` is replaced by the label.
+    pub format: String,
+}
+
+impl TemplateTag {
+    pub fn new(label: TagLabel, position: TagPosition) -> Self {
+        let format = match position {
+            TagPosition::Prepend => "[{}] ",
+            TagPosition::Append => " [{}]",
+        };
+        Self {
+            label,
+            position,
+            format: format.into(),
+        }
+    }
+}
+
+impl Default for TemplateTag {
+    fn default() -> Self {
+        Self::new(TagLabel::default(), TagPosition::default())
+    }
+}
+
+pub const DEFAULT_REDACTION_TOKEN: &str = "***";
+
+/// A placeholder which could not be printed, see [IssueHandler].
+#[derive(Debug)]
+pub struct RenderIssue<'a> {
+    pub variable: &'a str,
+    pub placeholder: &'a Placeholder,
+    pub error: &'a SprintfError,
+}
+
+/// Decides what is printed instead of a placeholder which could not be printed, e.g. a standardized
+/// error marker. The callback may also be used to count such issues.
+#[derive(Clone)]
+pub struct IssueHandler(Arc<dyn Fn(&RenderIssue) -> String + Send + Sync>);
+
+impl IssueHandler {
+    pub fn new(handler: impl Fn(&RenderIssue) -> String + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn handle(&self, issue: &RenderIssue) -> String {
+        (self.0)(issue)
+    }
+}
+
+impl fmt::Debug for IssueHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("IssueHandler")
+    }
+}
+
+/// Handlers are equal if they share the same callback.
+impl PartialEq for IssueHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Prints the value of a custom conversion like `%U`, see [crate::formatter::Formatter]. The handler
+/// gets the value and the raw flags between the `%` and the conversion, e.g. `-8` for `%-8U`.
+#[derive(Clone)]
+pub struct ConversionHandler(Arc<ConversionFn>);
+
+type ConversionFn = dyn Fn(&Value, &str) -> Result<String> + Send + Sync;
+
+impl ConversionHandler {
+    pub fn new(handler: impl Fn(&Value, &str) -> Result<String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn handle(&self, value: &Value, spec: &str) -> Result<String> {
+        (self.0)(value, spec)
+    }
+}
+
+impl fmt::Debug for ConversionHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConversionHandler")
+    }
+}
+
+/// Handlers are equal if they share the same callback.
+impl PartialEq for ConversionHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Applies a custom filter like `%{slug}s` to the printed value, see
+/// [crate::formatter::Formatter::register_filter].
+#[derive(Clone)]
+pub struct FilterHandler(Arc<dyn Fn(&str) -> String + Send + Sync>);
+
+impl FilterHandler {
+    pub fn new(handler: impl Fn(&str) -> String + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn handle(&self, value: &str) -> String {
+        (self.0)(value)
+    }
+}
+
+impl fmt::Debug for FilterHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FilterHandler")
+    }
+}
+
+/// Handlers are equal if they share the same callback.
+impl PartialEq for FilterHandler {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Cancels a render from another thread, see [Budget].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens are equal if they are clones of each other.
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Limits how long a render may take. It is checked before every entry, an exceeded budget aborts the
+/// render with [SprintfError::Aborted].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Budget {
+    pub deadline: Option<Instant>,
+    pub cancel: Option<CancelToken>,
+}
+
+impl Budget {
+    /// Why the render has to be aborted, if at all.
+    pub(crate) fn exceeded(&self) -> Option<&'static str> {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return Some("cancelled");
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some("deadline exceeded"),
+            _ => None,
+        }
+    }
+}
+
+/// The numbering system numbers are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Digits {
+    /// `0123456789`
+    #[default]
+    Latin,
+    /// `٠١٢٣٤٥٦٧٨٩`
+    ArabicIndic,
+    /// `०१२३४५६७८९`
+    Devanagari,
+}
+
+impl Digits {
+    /// The digit zero, all other digits follow it in Unicode.
+    pub fn zero(self) -> char {
+        match self {
+            Digits::Latin => '0',
+            Digits::ArabicIndic => '\u{660}',
+            Digits::Devanagari => '\u{966}',
+        }
+    }
+}
+
+/// Decimal point, digit grouping and numbering system of printed numbers.
+///
+/// Thousands are only grouped for placeholders with the `'` flag like `%'d` or `%'.2f`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{Digits, Locale, PrintOptions},
+///     parser::parse_format_string,
+///     printer::{sprintf, sprintf_with_options},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("count", Value::Numeric(-1234567.0));
+/// values.insert("total", Value::Numeric(1234.5));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%'d items, %'.2f EUR, %.1f", count, total, total"#).unwrap();
+///
+/// let options = PrintOptions {
+///     locale: Locale::new(',', '.'),
+///     ..Default::default()
+/// };
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "-1.234.567 items, 1.234,50 EUR, 1234,5");
+///
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "-1234567 items, 1234.50 EUR, 1234.5");
+///
+/// let options = PrintOptions {
+///     locale: Locale {
+///         digits: Digits::ArabicIndic,
+///         ..Locale::new('٫', '٬')
+///     },
+///     ..Default::default()
+/// };
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "-١٬٢٣٤٬٥٦٧ items, ١٬٢٣٤٫٥٠ EUR, ١٢٣٤٫٥");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Locale {
+    pub decimal_point: char,
+    /// `None` never groups, even with the `'` flag.
+    pub thousands_separator: Option<char>,
+    /// Applies to all numeric placeholders, `%x` keeps its Latin letters.
+    pub digits: Digits,
+}
+
+impl Locale {
+    /// The POSIX `C` locale, `1234.5` without any grouping.
+    pub const C: Locale = Locale {
+        decimal_point: '.',
+        thousands_separator: None,
+        digits: Digits::Latin,
+    };
+
+    pub const fn new(decimal_point: char, thousands_separator: char) -> Self {
+        Self {
+            decimal_point,
+            thousands_separator: Some(thousands_separator),
+            digits: Digits::Latin,
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::C
+    }
+}
+
+/// How floats are rounded to the precision of their placeholder.
+///
+/// Rounding works on the shortest decimal representation of a value, so `2.675` is rounded like it
+/// reads, although it is stored as `2.67499999...`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{PrintOptions, Rounding},
+///     parser::parse_format_string,
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("a", Value::Numeric(2.675));
+/// values.insert("b", Value::Numeric(0.125));
+/// values.insert("c", Value::Numeric(-1.001));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%.2f %.2f %.2f", a, b, c"#).unwrap();
+///
+/// let print = |rounding| {
+///     let options = PrintOptions {
+///         rounding,
+///         ..Default::default()
+///     };
+///     sprintf_with_options(&parsed, &values, &options).unwrap()
+/// };
+/// assert_eq!(print(Rounding::HalfUp), "2.68 0.13 -1.00");
+/// assert_eq!(print(Rounding::HalfEven), "2.68 0.12 -1.00");
+/// assert_eq!(print(Rounding::Truncate), "2.67 0.12 -1.00");
+/// assert_eq!(print(Rounding::Ceil), "2.68 0.13 -1.00");
+/// assert_eq!(print(Rounding::Floor), "2.67 0.12 -1.01");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Ties away from zero, `0.125` becomes `0.13`.
+    #[default]
+    HalfUp,
+    /// Ties to the even digit, `0.125` becomes `0.12`. Also known as banker's rounding.
+    HalfEven,
+    /// Towards zero.
+    Truncate,
+    /// Towards positive infinity.
+    Ceil,
+    /// Towards negative infinity.
+    Floor,
+}
+
+/// How zero filled placeholders like `%06d` pad negative numbers.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{NegativePadding, PrintOptions},
+///     parser::parse_format_string,
+///     printer::{sprintf, sprintf_with_options},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("delta", Value::Numeric(-123.0));
+/// values.insert("temp", Value::Numeric(-3.25));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%06d|%03.1f|%-6d|", delta, temp, delta"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "-00123|-03.3|-123  |");
+///
+/// let options = PrintOptions {
+///     negative_padding: NegativePadding::Spaces,
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "  -123| -3.3|-123  |");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NegativePadding {
+    /// Zeros between the sign and the digits like in C, `-00123`.
+    #[default]
+    Zeros,
+    /// Spaces before the sign, `  -123`.
+    Spaces,
+}
+
+/// How `%x` and `%X` print negative numbers.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{HexSign, PrintOptions},
+///     parser::parse_format_string,
+///     printer::{sprintf, sprintf_with_options},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("delta", Value::Numeric(-255.0));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%06X|%*.6x|%-6x|", delta, delta, delta"#).unwrap();
+/// assert!(sprintf(&parsed, &values).unwrap().starts_with("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFF01|"));
+///
+/// let options = PrintOptions {
+///     hex_sign: HexSign::SignMagnitude,
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "-000FF|...-ff|-ff   |");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HexSign {
+    /// The 128 bit two's complement, `-1` is `ffff...ffff`.
+    #[default]
+    TwosComplement,
+    /// A `-` followed by the digits of the absolute value. Zeros pad between the sign and the digits,
+    /// like in C.
+    SignMagnitude,
+}
+
+/// Texts printed by numeric placeholders for values without digits.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{NonFinite, PrintOptions},
+///     parser::parse_format_string,
+///     printer::{sprintf, sprintf_with_options},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("ratio", Value::Numeric(f64::NAN));
+/// values.insert("rate", Value::Numeric(f64::NEG_INFINITY));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%.2f|%5d|%-5x|", ratio, rate, ratio"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "nan| -inf|nan  |");
+///
+/// let options = PrintOptions {
+///     non_finite: NonFinite {
+///         nan: "--".into(),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "--| -inf|--   |");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonFinite {
+    pub nan: String,
+    pub infinity: String,
+    pub negative_infinity: String,
+}
+
+impl NonFinite {
+    /// The text for `number`, which must not be finite.
+    pub(crate) fn text(&self, number: f64) -> &str {
+        match number {
+            n if n.is_nan() => &self.nan,
+            n if n > 0.0 => &self.infinity,
+            _ => &self.negative_infinity,
+        }
+    }
+}
+
+impl Default for NonFinite {
+    fn default() -> Self {
+        Self {
+            nan: "nan".into(),
+            infinity: "inf".into(),
+            negative_infinity: "-inf".into(),
+        }
+    }
+}
+
+/// How the width of a placeholder like `%10s` is measured.
+///
+/// Defaults to [WidthMode::Display] with the `unicode-width` feature and to [WidthMode::Chars]
+/// otherwise. Only strings are measured this way, numbers consist of single column digits anyway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidthMode {
+    /// Every character counts as one column.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     options::{PrintOptions, WidthMode},
+    ///     parser::parse_format_string,
+    ///     printer::sprintf_with_options,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("city", Value::String("東京".into()));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let options = PrintOptions {
+    ///     width_mode: WidthMode::Chars,
+    ///     ..Default::default()
+    /// };
+    /// let parsed = parse_format_string(r#""[%-6s]", city"#).unwrap();
+    /// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "[東京    ]");
+    /// ```
+    Chars,
+    /// Terminal columns: wide characters like CJK and most emoji count as two, combining marks as
+    /// none. Truncated values which end in the middle of a wide character are filled up.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     parser::parse_format_string,
+    ///     printer::sprintf,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("city", Value::String("東京".into()));
+    /// values.insert("name", Value::String("Zoë".into()));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let parsed = parse_format_string(r#""[%-6s|%^6s|%!3s|%-4s]", city, city, city, name"#).unwrap();
+    /// assert_eq!(sprintf(&parsed, &values).unwrap(), "[東京  | 東京 |東 |Zoë ]");
+    /// ```
+    #[cfg(feature = "unicode-width")]
+    Display,
+}
+
+impl Default for WidthMode {
+    fn default() -> Self {
+        #[cfg(feature = "unicode-width")]
+        return WidthMode::Display;
+        #[cfg(not(feature = "unicode-width"))]
+        return WidthMode::Chars;
+    }
+}
+
+impl WidthMode {
+    /// Columns taken by `c`.
+    #[cfg_attr(not(feature = "unicode-width"), allow(unused_variables))]
+    pub fn char_width(self, c: char) -> usize {
+        match self {
+            WidthMode::Chars => 1,
+            // control characters are not printed as such
+            #[cfg(feature = "unicode-width")]
+            WidthMode::Display => unicode_width::UnicodeWidthChar::width(c).unwrap_or(0),
+        }
+    }
+
+    /// Columns taken by `s`.
+    pub fn str_width(self, s: &str) -> usize {
+        s.chars().map(|c| self.char_width(c)).sum()
+    }
+}
+
+/// Escapes the printed values, but not the literal text of a template, for the context the output
+/// is embedded into.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{Escape, PrintOptions},
+///     parser::parse_format_string,
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("name", Value::String(r#"<b>"Tom" & 'Jerry'</b>"#.into()));
+/// values.insert("path", Value::String("it's \\ here\n".into()));
+/// let values: MapResolver = values.into();
+///
+/// let print = |statement, escape| {
+///     let options = PrintOptions {
+///         escape,
+///         ..Default::default()
+///     };
+///     sprintf_with_options(&parse_format_string(statement).unwrap(), &values, &options).unwrap()
+/// };
+/// assert_eq!(
+///     print(r#""<td>%s</td>", name"#, Escape::Html),
+///     "<td>&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;</td>"
+/// );
+/// assert_eq!(print(r#""{\"path\": \"%s\"}", path"#, Escape::Json), r#"{"path": "it's \\ here\n"}"#);
+/// assert_eq!(print(r#""ls %s", path"#, Escape::Shell), "ls 'it'\\''s \\ here\n'");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Escape {
+    #[default]
+    None,
+    /// `&`, `<`, `>`, `"` and `'` as HTML entities.
+    Html,
+    /// The content of a JSON string, without the surrounding quotes.
+    Json,
+    /// A single quoted shell word.
+    Shell,
+}
+
+const DEFAULT_ELLIPSIS: &str = "…";
+
+/// What [MaxLen] truncates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TruncateScope {
+    /// The whole output, including literal text and tags.
+    #[default]
+    Output,
+    /// Every printed value on its own, before it is escaped.
+    Value,
+}
+
+/// Truncates output longer than `chars` characters, e.g. for syslog or UDP with their hard limits.
+/// Truncated output ends with the ellipsis, which counts towards the limit.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{MaxLen, PrintOptions, TruncateScope},
+///     parser::parse_format_string,
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("Bartholomew".into()));
+/// values.insert("action", Value::String("login".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s: %s", user, action"#).unwrap();
+///
+/// let print = |max_len| {
+///     let options = PrintOptions {
+///         max_len: Some(max_len),
+///         ..Default::default()
+///     };
+///     sprintf_with_options(&parsed, &values, &options).unwrap()
+/// };
+/// assert_eq!(print(MaxLen::new(10)), "Bartholom…");
+/// assert_eq!(print(MaxLen::new(18)), "Bartholomew: login");
+/// assert_eq!(
+///     print(MaxLen {
+///         scope: TruncateScope::Value,
+///         ellipsis: "...".into(),
+///         ..MaxLen::new(6)
+///     }),
+///     "Bar...: login"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaxLen {
+    pub chars: usize,
+    /// Replaces the truncated end, defaults to `…`.
+    pub ellipsis: String,
+    pub scope: TruncateScope,
+}
+
+impl MaxLen {
+    /// Truncates the whole output at `chars` characters with the default ellipsis.
+    pub fn new(chars: usize) -> Self {
+        Self {
+            chars,
+            ellipsis: DEFAULT_ELLIPSIS.into(),
+            scope: TruncateScope::Output,
+        }
+    }
+}
+
+/// What is printed for a placeholder whose variable can't be resolved and has no default like
+/// `%{:-unknown}s`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{MissingPolicy, PrintOptions},
+///     parser::parse_format_string,
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("cpu", Value::Numeric(12.5));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""cpu=%.1f mem=%-6d|", cpu, mem"#).unwrap();
+///
+/// let print = |on_missing| {
+///     let options = PrintOptions {
+///         on_missing,
+///         ..Default::default()
+///     };
+///     sprintf_with_options(&parsed, &values, &options)
+/// };
+/// assert!(print(MissingPolicy::Error).is_err());
+/// assert_eq!(print(MissingPolicy::Empty).unwrap(), "cpu=12.5 mem=|");
+/// assert_eq!(print(MissingPolicy::KeepPlaceholder).unwrap(), "cpu=12.5 mem=%-6d|");
+/// assert_eq!(print(MissingPolicy::Custom("{missing}".into())).unwrap(), "cpu=12.5 mem={missing}|");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum MissingPolicy {
+    /// Fail with [SprintfError::UnresolvedVariable], unless [PrintOptions::on_issue] handles it.
+    #[default]
+    Error,
+    /// Print nothing.
+    Empty,
+    /// Print the placeholder as written, e.g. `%-6d`.
+    KeepPlaceholder,
+    /// Print the text.
+    Custom(String),
+}
+
+/// Options for [crate::printer::sprintf_with_options].
+///
+/// Use different options for different sinks, e.g. to redact sensitive values in logs that are
+/// shipped off-site while keeping them in local ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintOptions {
+    pub tag: Option<TemplateTag>,
+    /// Variables whose values are replaced by `redaction_token`.
+    pub redact: Vec<String>,
+    pub redaction_token: String,
+    /// Prints placeholders which fail, e.g. because of an unresolved variable, leniently with the
+    /// text returned by the handler instead of failing the whole output.
+    pub on_issue: Option<IssueHandler>,
+    /// Takes precedence over `on_issue` for unresolved variables.
+    pub on_missing: MissingPolicy,
+    /// Applies to all printed values, including redaction tokens and the texts of `on_issue` and
+    /// `on_missing`.
+    pub escape: Escape,
+    /// Prints styles like `%[bold red]` as nothing instead of ANSI escape sequences, e.g. when the
+    /// output is no terminal.
+    pub strip_styles: bool,
+    pub width_mode: WidthMode,
+    pub max_len: Option<MaxLen>,
+    pub budget: Option<Budget>,
+    pub locale: Locale,
+    pub rounding: Rounding,
+    pub non_finite: NonFinite,
+    pub hex_sign: HexSign,
+    pub negative_padding: NegativePadding,
+    /// Handlers of custom conversions, see [crate::formatter::Formatter].
+    pub conversions: BTreeMap<char, ConversionHandler>,
+    /// Handlers of custom filters by name, see [crate::formatter::Formatter].
+    pub filters: BTreeMap<String, FilterHandler>,
+    /// Checked before printing, see [ParseLimits].
+    pub limits: ParseLimits,
+}
+
+impl PrintOptions {
+    pub(crate) fn is_redacted(&self, variable: &str) -> bool {
+        self.redact.iter().any(|redacted| redacted == variable)
+    }
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            redact: Vec::new(),
+            redaction_token: DEFAULT_REDACTION_TOKEN.into(),
+            on_issue: None,
+            on_missing: MissingPolicy::Error,
+            escape: Escape::None,
+            strip_styles: false,
+            width_mode: WidthMode::default(),
+            max_len: None,
+            budget: None,
+            locale: Locale::C,
+            rounding: Rounding::HalfUp,
+            non_finite: NonFinite::default(),
+            hex_sign: HexSign::TwosComplement,
+            negative_padding: NegativePadding::Zeros,
+            conversions: BTreeMap::new(),
+            filters: BTreeMap::new(),
+            limits: ParseLimits::default(),
+        }
+    }
+}
+
+/// How a `%` which does not start a valid placeholder is handled, e.g. in `100%` or `%0%d`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StrayPercent {
+    /// Fail to parse.
+    #[default]
+    Reject,
+    /// Keep the `%` as literal text and continue behind it.
+    Literal,
+}
+
+/// Syntax of the format text.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dialect {
+    /// Placeholders are bound to the variable list in order, or by position like `%1$s` to reuse a
+    /// variable.
+    #[default]
+    Printf,
+    /// Python's `%(name)s`, every placeholder names its variable inline and there is no variable
+    /// list, e.g. `"%(user.name)s failed %(tries)03d times"`.
+    Python,
+    /// Strict C99 conversions printing exactly like C, without the extensions of this crate, see
+    /// [crate::c99].
+    C99,
+}
+
+/// Limits for templates from untrusted sources like end users, so a hostile template can't cause
+/// huge allocations or long padding loops, e.g. by `%60000d`. The parser checks them with
+/// [ParseOptions::limits], the printer once more with [PrintOptions::limits] for templates that
+/// were built or edited instead of parsed. All limits are off by default.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     error::SprintfError,
+///     options::{ParseLimits, ParseOptions, PrintOptions},
+///     parser::{parse_format_string, parse_format_string_with},
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let options = ParseOptions {
+///     limits: ParseLimits {
+///         max_len: Some(64),
+///         max_placeholders: Some(2),
+///         max_width: Some(20),
+///         max_precision: Some(6),
+///     },
+///     ..Default::default()
+/// };
+/// let parse = |input| parse_format_string_with(input, &options);
+///
+/// assert!(parse(r#""%-20s %8.3f", user, load"#).is_ok());
+/// assert_eq!(
+///     parse(r#""%60000d", id"#).unwrap_err(),
+///     SprintfError::LimitExceeded {
+///         limit: "width",
+///         actual: 60000,
+///         max: 20
+///     }
+/// );
+/// assert!(parse(r#""%.10f", load"#).is_err());
+/// assert!(parse(r#""%s %s %s", a, b, c"#).is_err());
+/// let long = format!(r#""{}""#, "x".repeat(64));
+/// assert!(parse(&long).is_err());
+/// // too large for any width, even without limits
+/// assert!(parse(r#""%999999d", id"#).is_err());
+///
+/// // templates parsed elsewhere are checked again before printing
+/// let parsed = parse_format_string(r#""%60000d", id"#).unwrap();
+/// let print = PrintOptions {
+///     limits: options.limits,
+///     ..Default::default()
+/// };
+/// let values: MapResolver = HashMap::from([("id", Value::Numeric(1.0))]).into();
+/// assert!(sprintf_with_options(&parsed, &values, &print).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseLimits {
+    /// Length of the parsed input in bytes, including the variable list. Only checked by the parser.
+    pub max_len: Option<usize>,
+    pub max_placeholders: Option<usize>,
+    /// Width of a placeholder like the `8` of `%8.3f`.
+    pub max_width: Option<u16>,
+    /// Precision of a placeholder like the `3` of `%8.3f`.
+    pub max_precision: Option<u16>,
+}
+
+impl ParseLimits {
+    pub(crate) fn check_len(&self, len: usize) -> Result<()> {
+        check_limit("length", len, self.max_len)
+    }
+
+    /// Checks the number of placeholders among `entries` and the width and precision of each.
+    pub(crate) fn check(&self, entries: &[Entry]) -> Result<()> {
+        let placeholders = entries.iter().filter_map(|entry| match entry {
+            Entry::Placeholder(placeholder) => Some(placeholder),
+            Entry::Text(_) | Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => None,
+        });
+        check_limit("placeholder count", placeholders.clone().count(), self.max_placeholders)?;
+        for placeholder in placeholders {
+            let (width, precision) = placeholder.width_and_precision();
+            check_limit(
+                "width",
+                width.unwrap_or_default().into(),
+                self.max_width.map(usize::from),
+            )?;
+            check_limit(
+                "precision",
+                precision.unwrap_or_default().into(),
+                self.max_precision.map(usize::from),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn check_limit(limit: &'static str, actual: usize, max: Option<usize>) -> Result<()> {
+    match max {
+        Some(max) if actual > max => Err(SprintfError::LimitExceeded { limit, actual, max }),
+        _ => Ok(()),
+    }
+}
+
+/// Options for [crate::parser::parse_format_string_with].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// Quote around the format text, e.g. `'` or `` ` `` if double quotes are taken by the surrounding
+    /// document.
+    pub quote: char,
+    /// Separator of the variables following the format text.
+    pub separator: char,
+    pub stray_percent: StrayPercent,
+    /// Conversion characters of custom conversions, see [crate::formatter::Formatter].
+    pub custom_conversions: Vec<char>,
+    pub dialect: Dialect,
+    pub limits: ParseLimits,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            quote: '"',
+            separator: ',',
+            stray_percent: StrayPercent::default(),
+            custom_conversions: Vec::new(),
+            dialect: Dialect::default(),
+            limits: ParseLimits::default(),
+        }
+    }
+}
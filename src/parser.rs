@@ -1,28 +1,70 @@
-use anyhow::{anyhow, bail, Result};
+use std::{borrow::Cow, fmt, ops::Range, sync::Arc};
+
+#[cfg(feature = "chrono")]
+use crate::types::DateTimeFormat;
 
 use crate::{
-    extractor::{extract_float_format, extract_number_format},
-    types::{FloatFormat, HexFormat, NumberFormat},
+    c99::CFormat,
+    error::{Result, SprintfError},
+    expression::Expression,
+    extractor::{
+        extract_display_format, extract_float_format, extract_modifiers, extract_number_format, extract_string_format,
+        CENTER_MARKER, DEFAULT_MARKER, FILL_MARKER, FILTER_SEPARATOR, GROUPING_MARKER, JOIN, LEFT_MARKER,
+        MODIFIERS_END, MODIFIERS_START, NAME_END, NAME_START, OVERFLOW_MARKER, PATTERN_MARKER, SECTION_END,
+        SECTION_START, STYLE_END, STYLE_START, VERBOSE_MARKER,
+    },
+    options::{Dialect, ParseOptions, StrayPercent},
+    specifiers::{Conversion, ValueKind},
+    tokenizer::{Token, Tokenizer},
+    types::{
+        Alignment, DurationFormat, DurationUnit, Filter, FloatFormat, HexFormat, Modifiers, NumberFormat, Overflow,
+        SizeFormat, StringFormat, Style,
+    },
 };
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Hash)]
+#[non_exhaustive]
 pub enum Placeholder {
-    Display,              //< %v
-    String,               //< %s
-    Float(FloatFormat),   //< %f
-    Number(NumberFormat), //< %d
-    Hex(HexFormat),       //< %x
+    Display(StringFormat), //< %v
+    String(StringFormat),  //< %s
+    Float(FloatFormat),    //< %f
+    Number(NumberFormat),  //< %d
+    Hex(HexFormat),        //< %x
+    /// A conversion registered with [crate::formatter::Formatter::register] and the raw flags
+    /// between the `%` and the conversion.
+    Custom(char, String),
+    /// `%D`
+    Duration(DurationFormat),
+    /// `%h` and `%H`
+    Size(SizeFormat),
+    /// `%p`, a ratio as percentage: the value times 100 printed like `%f`, followed by `%`.
+    /// ```
+    /// use sprintf::{fields::FieldResolver, parser::parse_format_string, printer::sprintf};
+    ///
+    /// let ratios = vec![0.07, 0.4256, 1.0, -0.5];
+    /// let parsed = parse_format_string(r#""%p %.1p|%-7.1p|%04.0p %p", 0, 1, 1, 2, 3"#).unwrap();
+    /// assert_eq!(sprintf(&parsed, &FieldResolver::new(&ratios)).unwrap(), "7% 42.6%|42.6%  |0100% -50%");
+    /// ```
+    Percent(FloatFormat),
+    /// `%T`
+    #[cfg(feature = "chrono")]
+    DateTime(DateTimeFormat),
+    /// A placeholder with a `{...}` block like `%{upper|trim}s`.
+    Modified(Modifiers, Box<Placeholder>),
+    /// A C99 conversion like `%-+8.3le`, parsed with [Dialect::C99].
+    C99(CFormat),
 }
 
 /// ```
-/// use sprintf::{extractor::*, parser::*, types::*};
+/// use sprintf::{error::SprintfError, extractor::*, parser::*, types::*};
 ///
 /// let res: Placeholder = "%2d".to_string().try_into().unwrap();
 /// assert_eq!(
 ///     res,
 ///     Placeholder::Number(NumberFormat {
 ///         digits: Some(2),
-///         fill_zeros: false
+///         fill_zeros: false,
+///         ..Default::default()
 ///     })
 /// );
 ///
@@ -31,7 +73,8 @@ pub enum Placeholder {
 ///     res,
 ///     Placeholder::Number(NumberFormat {
 ///         digits: Some(4),
-///         fill_zeros: true
+///         fill_zeros: true,
+///         ..Default::default()
 ///     })
 /// );
 ///
@@ -43,6 +86,7 @@ pub enum Placeholder {
 ///         nf: NumberFormat {
 ///             digits: Some(8),
 ///             fill_zeros: true,
+///             ..Default::default()
 ///         },
 ///     })
 /// );
@@ -55,15 +99,48 @@ pub enum Placeholder {
 ///         nf: NumberFormat {
 ///             digits: Some(2),
 ///             fill_zeros: false,
+///             ..Default::default()
 ///         },
 ///     })
 /// );
 ///
+/// let res: Placeholder = "%*.8d".to_string().try_into().unwrap();
+/// assert_eq!(
+///     res,
+///     Placeholder::Number(NumberFormat {
+///         digits: Some(8),
+///         fill_char: Some('.'),
+///         ..Default::default()
+///     })
+/// );
+///
 /// let res: Placeholder = "%v".to_string().try_into().unwrap();
-/// assert_eq!(res, Placeholder::Display);
+/// assert_eq!(res, Placeholder::Display(StringFormat::default()));
+///
+/// let res: Placeholder = "%+v".to_string().try_into().unwrap();
+/// assert_eq!(
+///     res,
+///     Placeholder::Display(StringFormat {
+///         verbose: true,
+///         ..Default::default()
+///     })
+/// );
+///
+/// let res: Result<Placeholder, _> = "%+s".to_string().try_into();
+/// assert_eq!(res, Err(SprintfError::InvalidPlaceholder("%+s".to_string())));
 ///
 /// let res: Placeholder = "%s".to_string().try_into().unwrap();
-/// assert_eq!(res, Placeholder::String);
+/// assert_eq!(res, Placeholder::String(StringFormat::default()));
+///
+/// let res: Placeholder = "%^12s".to_string().try_into().unwrap();
+/// assert_eq!(
+///     res,
+///     Placeholder::String(StringFormat {
+///         width: Some(12),
+///         align: Alignment::Center,
+///         ..Default::default()
+///     })
+/// );
 ///
 /// let res: Placeholder = "%.02f".to_string().try_into().unwrap();
 /// assert_eq!(
@@ -71,72 +148,748 @@ pub enum Placeholder {
 ///     Placeholder::Float(FloatFormat {
 ///         fraction: NumberFormat {
 ///             digits: Some(2),
-///             fill_zeros: true
+///             fill_zeros: true,
+///             ..Default::default()
 ///         },
 ///         ..Default::default()
 ///     })
 /// );
+///
+/// let res: Result<Placeholder, _> = "%".to_string().try_into();
+/// assert_eq!(res, Err(SprintfError::InvalidPlaceholder("%".to_string())));
+///
+/// let res: Result<Placeholder, _> = "%5q".to_string().try_into();
+/// assert_eq!(res.unwrap_err().to_string(), "invalid conversion 'q' at column 3");
 /// ```
 impl TryFrom<String> for Placeholder {
-    type Error = anyhow::Error;
+    type Error = SprintfError;
 
     fn try_from(s: String) -> Result<Self, Self::Error> {
-        if s.len() < 2 {
-            panic!("Placeholder must be at least 2 characters long");
-        }
-        if s.chars().next().ok_or_else(|| anyhow!("Unexpected end of input"))? != '%' {
-            bail!("Placeholder have to begin with '%'")
+        match s.starts_with("%{") {
+            true => parse_placeholder(&s, &ParseOptions::default()),
+            false => Self::parse_builtin(&s),
         }
+    }
+}
 
-        let what = s
-            .chars()
-            .rev()
-            .next()
-            .ok_or_else(|| anyhow!("Unexpected end of input"))?;
-        let cutted_s = &s[1..s.len() - 1];
-        match what {
-            'v' => Ok(Self::Display),
-            's' => Ok(Self::String),
-            'd' => Ok(Self::Number(extract_number_format(cutted_s)?)),
-            'x' | 'X' => Ok(Self::Hex(HexFormat {
+impl Placeholder {
+    /// Parses a built-in placeholder without a `{...}` block.
+    fn parse_builtin(s: &str) -> Result<Self> {
+        let (Some(cutted_s), Some(what)) = (s.strip_prefix('%'), s.chars().next_back()) else {
+            return Err(SprintfError::InvalidPlaceholder(s.into()));
+        };
+        let Some(cutted_s) = cutted_s.strip_suffix(what) else {
+            return Err(SprintfError::InvalidPlaceholder(s.into()));
+        };
+        let Some(conversion) = Conversion::from_char(what) else {
+            return Err(SprintfError::UnknownConversion {
+                conversion: what,
+                column: s.chars().count(),
+                span: 0..s.len(),
+            });
+        };
+        // `+` is a flag of `%v` only, custom conversions get all flags
+        if !matches!(conversion, Conversion::Display | Conversion::Custom(_)) && cutted_s.starts_with(VERBOSE_MARKER) {
+            return Err(SprintfError::InvalidPlaceholder(s.into()));
+        }
+        match conversion {
+            Conversion::Display => Ok(Self::Display(extract_display_format(cutted_s)?)),
+            Conversion::String => Ok(Self::String(extract_string_format(cutted_s)?)),
+            Conversion::Number => Ok(Self::Number(extract_number_format(cutted_s)?)),
+            Conversion::Hex | Conversion::UpperHex => Ok(Self::Hex(HexFormat {
                 nf: extract_number_format(cutted_s)?,
-                uppercase: what == 'X',
+                uppercase: conversion == Conversion::UpperHex,
+            })),
+            Conversion::Float => Ok(Self::Float(extract_float_format(cutted_s)?)),
+            Conversion::BinarySize | Conversion::DecimalSize => Ok(Self::Size(SizeFormat {
+                si: conversion == Conversion::DecimalSize,
+                ff: extract_float_format(cutted_s)?,
+            })),
+            Conversion::Percent => Ok(Self::Percent(extract_float_format(cutted_s)?)),
+            Conversion::Duration => Ok(Self::Duration(DurationFormat {
+                base: extract_string_format(cutted_s)?,
+                unit: DurationUnit::default(),
+            })),
+            #[cfg(feature = "chrono")]
+            Conversion::DateTime => Ok(Self::DateTime(DateTimeFormat {
+                base: extract_string_format(cutted_s)?,
+                pattern: None,
             })),
-            'f' => Ok(Self::Float(extract_float_format(cutted_s)?)),
-            _ => bail!("Placeholder '{}' unknown", what),
+            Conversion::Custom(c) => Ok(Self::Custom(c, cutted_s.to_string())),
+        }
+    }
+
+    /// The [Conversion] this placeholder was parsed from.
+    pub fn conversion(&self) -> Conversion {
+        match self {
+            Placeholder::Display(_) => Conversion::Display,
+            Placeholder::String(_) => Conversion::String,
+            Placeholder::Float(_) => Conversion::Float,
+            Placeholder::Number(_) => Conversion::Number,
+            Placeholder::Hex(hf) if hf.uppercase => Conversion::UpperHex,
+            Placeholder::Hex(_) => Conversion::Hex,
+            Placeholder::Duration(_) => Conversion::Duration,
+            Placeholder::Size(sf) if sf.si => Conversion::DecimalSize,
+            Placeholder::Size(_) => Conversion::BinarySize,
+            Placeholder::Percent(_) => Conversion::Percent,
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(_) => Conversion::DateTime,
+            Placeholder::Custom(c, _) => Conversion::Custom(*c),
+            Placeholder::Modified(_, inner) => inner.conversion(),
+            Placeholder::C99(cf) => cf.closest_conversion(),
+        }
+    }
+
+    /// The kind of value this placeholder prints, e.g. to build typed input forms. Filters print the
+    /// value as string first, so placeholders with filters take [ValueKind::Any].
+    /// ```
+    /// use sprintf::{
+    ///     options::{Dialect, ParseOptions},
+    ///     parser::{parse_format_string_with, Placeholder},
+    ///     specifiers::ValueKind,
+    /// };
+    ///
+    /// let kind = |placeholder: &str| Placeholder::try_from(placeholder.to_string()).unwrap().expected_kind();
+    /// assert_eq!(kind("%-8s"), ValueKind::String);
+    /// assert_eq!(kind("%08X"), ValueKind::Integer);
+    /// assert_eq!(kind("%.2h"), ValueKind::Float);
+    /// assert_eq!(kind("%v"), ValueKind::Any);
+    /// assert_eq!(kind("%{:-0}d"), ValueKind::Integer);
+    /// assert_eq!(kind("%{trim}d"), ValueKind::Any);
+    ///
+    /// let options = ParseOptions {
+    ///     dialect: Dialect::C99,
+    ///     ..Default::default()
+    /// };
+    /// let parsed = parse_format_string_with(r#""%lu %.3e", count, ratio"#, &options).unwrap();
+    /// let kinds: Vec<_> = parsed.placeholders().map(|(_, placeholder, _, _)| placeholder.expected_kind()).collect();
+    /// assert_eq!(kinds, vec![ValueKind::Integer, ValueKind::Float]);
+    /// ```
+    pub fn expected_kind(&self) -> ValueKind {
+        match self {
+            Placeholder::Display(_) | Placeholder::Custom(..) => ValueKind::Any,
+            Placeholder::String(_) => ValueKind::String,
+            Placeholder::Number(_) | Placeholder::Hex(_) => ValueKind::Integer,
+            Placeholder::Float(_) | Placeholder::Size(_) | Placeholder::Percent(_) | Placeholder::Duration(_) => {
+                ValueKind::Float
+            }
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(_) => ValueKind::Float,
+            Placeholder::Modified(modifiers, inner) if modifiers.filters.is_empty() => inner.expected_kind(),
+            Placeholder::Modified(..) => ValueKind::Any,
+            Placeholder::C99(cf) => match cf.closest_conversion() {
+                Conversion::Display => ValueKind::Any,
+                Conversion::String => ValueKind::String,
+                Conversion::Float => ValueKind::Float,
+                _ => ValueKind::Integer,
+            },
+        }
+    }
+
+    /// The pattern of the conversion, like the strftime pattern of `%{:%H:%M}T`.
+    pub fn pattern(&self) -> Option<&str> {
+        match self {
+            Placeholder::Duration(df) if df.unit != DurationUnit::default() => Some(df.unit.name()),
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(dt) => dt.pattern.as_deref(),
+            Placeholder::Modified(_, inner) => inner.pattern(),
+            _ => None,
         }
     }
+
+    /// Sets the pattern of the conversion, if it has any.
+    fn set_pattern(&mut self, pattern: String) -> Result<()> {
+        match self {
+            Placeholder::Duration(df) => {
+                df.unit = DurationUnit::from_name(&pattern).ok_or(SprintfError::InvalidPlaceholder(pattern))?;
+                Ok(())
+            }
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(dt) => {
+                use chrono::format::{Item, StrftimeItems};
+
+                if StrftimeItems::new(&pattern).any(|item| item == Item::Error) {
+                    return Err(SprintfError::InvalidPlaceholder(pattern));
+                }
+                dt.pattern = Some(pattern);
+                Ok(())
+            }
+            Placeholder::Modified(_, inner) => inner.set_pattern(pattern),
+            placeholder => Err(SprintfError::InvalidPlaceholder(format!(
+                "{}{}{}",
+                placeholder, PATTERN_MARKER, pattern
+            ))),
+        }
+    }
+
+    /// The value printed if the bound variable can't be resolved, e.g. `unknown` for `%{:-unknown}s`.
+    /// Filters apply to the default as well.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     parser::parse_format_string,
+    ///     printer::sprintf,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("host", Value::String("db1".into()));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let statement = r#""%{:-unknown}s load=%{:-0}.1f user=%{upper:-\"nobody\"}s", host, load, user"#;
+    /// let parsed = parse_format_string(statement).unwrap();
+    /// assert_eq!(parsed.to_string(), statement);
+    /// assert!(parsed.validate(&values).is_ok());
+    /// assert_eq!(sprintf(&parsed, &values).unwrap(), r#"db1 load=0.0 user="NOBODY""#);
+    ///
+    /// let parsed = parse_format_string(r#""%s %s", host, user"#).unwrap();
+    /// assert!(sprintf(&parsed, &values).is_err());
+    /// ```
+    pub fn default_value(&self) -> Option<&str> {
+        match self {
+            Placeholder::Modified(modifiers, _) => modifiers.default.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The separator of a placeholder printing every element of a sequence variable like
+    /// `%{join{, }}d`. Sequences are resolved by their elements `ports.0`, `ports.1`, ... as
+    /// collected by [crate::fields::Field] and the JSON, YAML and TOML resolvers. Every element is
+    /// printed with the placeholder, filters apply to each of them. An empty sequence is unresolved.
+    /// ```
+    /// use sprintf::{
+    ///     fields::{join, Field, FieldResolver, Values},
+    ///     parser::parse_format_string,
+    ///     printer::sprintf,
+    /// };
+    ///
+    /// struct Server {
+    ///     ports: Vec<u16>,
+    ///     tags: Vec<String>,
+    /// }
+    ///
+    /// impl Field for Server {
+    ///     fn collect(&self, path: &str, values: &mut Values) {
+    ///         self.ports.collect(&join(path, "ports"), values);
+    ///         self.tags.collect(&join(path, "tags"), values);
+    ///     }
+    /// }
+    ///
+    /// let server = Server {
+    ///     ports: vec![80, 443, 8080],
+    ///     tags: vec!["eu".into(), "prod".into()],
+    /// };
+    /// let statement = r#""[%{join{, }}04d] %{join{ }|upper}s %{join{,}:-none}s", ports, tags, ips"#;
+    /// let parsed = parse_format_string(statement).unwrap();
+    /// assert_eq!(parsed.to_string(), statement);
+    /// assert_eq!(parsed.placeholders().next().unwrap().1.join(), Some(", "));
+    /// assert_eq!(sprintf(&parsed, &FieldResolver::new(&server)).unwrap(), "[0080, 0443, 8080] EU PROD none");
+    /// ```
+    pub fn join(&self) -> Option<&str> {
+        match self {
+            Placeholder::Modified(modifiers, _) => modifiers.join.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The width and the precision, like `8` and `2` of `%8.2f`, as checked by
+    /// [crate::options::ParseLimits].
+    pub(crate) fn width_and_precision(&self) -> (Option<u16>, Option<u16>) {
+        match self {
+            Placeholder::Display(sf) | Placeholder::String(sf) => (sf.width, None),
+            Placeholder::Float(ff) | Placeholder::Size(SizeFormat { ff, .. }) | Placeholder::Percent(ff) => {
+                (ff.base.digits, ff.fraction.digits)
+            }
+            Placeholder::Number(nf) | Placeholder::Hex(HexFormat { nf, .. }) => (nf.digits, None),
+            Placeholder::Custom(..) => (None, None),
+            Placeholder::Duration(DurationFormat { base, .. }) => (base.width, None),
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(DateTimeFormat { base, .. }) => (base.width, None),
+            Placeholder::Modified(_, inner) => inner.width_and_precision(),
+            Placeholder::C99(cf) => (cf.width, cf.precision),
+        }
+    }
+
+    /// Writes everything between the `%` and the conversion.
+    fn write_spec(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placeholder::Modified(modifiers, _) => write_block(
+                f,
+                modifiers.join.as_deref(),
+                &modifiers.filters,
+                self.pattern(),
+                modifiers.default.as_deref(),
+            )?,
+            _ => write_block(f, None, &[], self.pattern(), None)?,
+        }
+        self.write_flags(f)
+    }
+
+    /// Writes the flags following the `{...}` block.
+    fn write_flags(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Placeholder::Display(sf) | Placeholder::String(sf) => write_string_flags(f, sf)?,
+            Placeholder::Number(nf) | Placeholder::Hex(HexFormat { nf, .. }) => {
+                write_flags(f, nf.align, nf.grouping, nf.fill_char, nf.overflow)?;
+                write_digits(f, nf)?;
+            }
+            Placeholder::Float(ff) | Placeholder::Size(SizeFormat { ff, .. }) | Placeholder::Percent(ff) => {
+                write_flags(f, ff.base.align, ff.base.grouping, ff.base.fill_char, ff.base.overflow)?;
+                write_digits(f, &ff.base)?;
+                if ff.fraction != NumberFormat::default() {
+                    write!(f, ".")?;
+                    write_digits(f, &ff.fraction)?;
+                }
+            }
+            Placeholder::Duration(DurationFormat { base: sf, .. }) => write_string_flags(f, sf)?,
+            #[cfg(feature = "chrono")]
+            Placeholder::DateTime(DateTimeFormat { base: sf, .. }) => write_string_flags(f, sf)?,
+            Placeholder::Custom(_, spec) => write!(f, "{}", spec)?,
+            Placeholder::C99(cf) => cf.write_spec(f)?,
+            Placeholder::Modified(_, inner) => inner.write_flags(f)?,
+        }
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Entry {
-    Text(String),
+/// Writes the `{filters:pattern:-default}` block, if there is anything to write.
+fn write_block(
+    f: &mut fmt::Formatter<'_>,
+    join: Option<&str>,
+    filters: &[Filter],
+    pattern: Option<&str>,
+    default: Option<&str>,
+) -> fmt::Result {
+    if join.is_none() && filters.is_empty() && pattern.is_none() && default.is_none() {
+        return Ok(());
+    }
+    write!(f, "{}", MODIFIERS_START)?;
+    if let Some(separator) = join {
+        write!(f, "{}{}{}{}", JOIN, MODIFIERS_START, separator, MODIFIERS_END)?;
+    }
+    for (i, filter) in filters.iter().enumerate() {
+        if i > 0 || join.is_some() {
+            write!(f, "{}", FILTER_SEPARATOR)?;
+        }
+        write!(f, "{}", filter.name())?;
+        if let Filter::Plural(branches) | Filter::Select(branches) = filter {
+            write!(f, "{}", PATTERN_MARKER)?;
+            for (i, branch) in branches.iter().enumerate() {
+                let separator = if i > 0 { " " } else { "" };
+                write!(
+                    f,
+                    "{}{}{}{}{}",
+                    separator, branch.key, MODIFIERS_START, branch.message, MODIFIERS_END
+                )?;
+            }
+        }
+    }
+    if let Some(pattern) = pattern {
+        write!(f, "{}{}", PATTERN_MARKER, pattern)?;
+    }
+    if let Some(default) = default {
+        write!(f, "{}{}", DEFAULT_MARKER, default)?;
+    }
+    write!(f, "{}", MODIFIERS_END)
+}
+
+/// Writes the `[-^]['][*c][!c]` flags shared by all placeholders.
+fn write_flags(
+    f: &mut fmt::Formatter<'_>,
+    align: Alignment,
+    grouping: bool,
+    fill_char: Option<char>,
+    overflow: Overflow,
+) -> fmt::Result {
+    match align {
+        Alignment::Left => write!(f, "{}", LEFT_MARKER)?,
+        Alignment::Center => write!(f, "{}", CENTER_MARKER)?,
+        Alignment::Right => {}
+    }
+    if grouping {
+        write!(f, "{}", GROUPING_MARKER)?;
+    }
+    if let Some(fill) = fill_char {
+        write!(f, "{}{}", FILL_MARKER, fill)?;
+    }
+    match overflow {
+        Overflow::Extend => Ok(()),
+        Overflow::Truncate => write!(f, "{}", OVERFLOW_MARKER),
+        Overflow::Fill(fill) => write!(f, "{}{}", OVERFLOW_MARKER, fill),
+    }
+}
+
+fn write_string_flags(f: &mut fmt::Formatter<'_>, sf: &StringFormat) -> fmt::Result {
+    if sf.verbose {
+        write!(f, "{}", VERBOSE_MARKER)?;
+    }
+    write_flags(f, sf.align, false, sf.fill_char, sf.overflow)?;
+    match sf.width {
+        Some(width) => write!(f, "{}", width),
+        None => Ok(()),
+    }
+}
+
+fn write_digits(f: &mut fmt::Formatter<'_>, nf: &NumberFormat) -> fmt::Result {
+    match (nf.digits, nf.fill_zeros) {
+        (Some(digits), true) => write!(f, "0{}", digits),
+        (Some(digits), false) => write!(f, "{}", digits),
+        (None, _) => Ok(()),
+    }
+}
+
+/// Reconstructs the placeholder in its printf form.
+/// ```
+/// use sprintf::parser::Placeholder;
+///
+/// for placeholder in ["%06.2f", "%.02f", "%-*.12s", "%^8v", "%+-12v", "%08X", "%*x10d", "%-'12d", "%'.2f", "%!8s", "%-*.!#6d"] {
+///     let parsed: Placeholder = placeholder.to_string().try_into().unwrap();
+///     assert_eq!(parsed.to_string(), placeholder);
+/// }
+/// ```
+impl fmt::Display for Placeholder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%")?;
+        self.write_spec(f)?;
+        match self {
+            Placeholder::C99(cf) => write!(f, "{}", cf.conversion),
+            _ => write!(f, "{}", self.conversion().char()),
+        }
+    }
+}
+
+/// A part of a template. Text parsed without escape sequences borrows from the input.
+///
+/// A section like `%{?error} failed: %s%{/error}` is printed only if its variable resolves.
+/// Sections may be nested, every section has to be ended by the `%{/...}` of its variable.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::*,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let statement = r#""%s%{?error} failed: %(error)s%{?code} (%d)%{/code}%{/error}", job, code"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.entries[1], Entry::Section("error".into()));
+/// assert_eq!(parsed.variables, vec!["job", "error", "code"]);
+///
+/// let mut values = HashMap::new();
+/// values.insert("job", Value::String("backup".into()));
+/// let ok: MapResolver = values.clone().into();
+/// assert_eq!(sprintf(&parsed, &ok).unwrap(), "backup");
+/// assert!(parsed.validate(&ok).is_ok());
+/// assert_eq!(parsed.compile().unwrap().sprintf(&ok).unwrap(), "backup");
+///
+/// values.insert("error", Value::String("disk full".into()));
+/// values.insert("code", Value::Numeric(28.0));
+/// let failed: MapResolver = values.into();
+/// assert_eq!(sprintf(&parsed, &failed).unwrap(), "backup failed: disk full (28)");
+/// assert_eq!(parsed.compile().unwrap().sprintf(&failed).unwrap(), "backup failed: disk full (28)");
+///
+/// assert!(parse_format_string(r#""%{?error}%s", error"#).is_err());
+/// assert!(parse_format_string(r#""%{?error}%{?code}%{/error}%{/code}""#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub enum Entry<'a> {
+    Text(Cow<'a, str>),
     Placeholder(Placeholder),
+    /// ANSI styles like `%[bold red]`, which are not bound to a variable.
+    Style(Vec<Style>),
+    /// Starts a section like `%{?error}`, which is printed only if its variable resolves. The
+    /// placeholders of a skipped section still take their variables.
+    Section(String),
+    /// Ends the section of a variable, like `%{/error}`.
+    SectionEnd(String),
+}
+
+/// Writes `text` escaped for a quoted format text, a `%` only if it is literal text.
+fn write_escaped(f: &mut fmt::Formatter<'_>, text: &str, literal: bool) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '\n' => write!(f, "\\n")?,
+            '\t' => write!(f, "\\t")?,
+            '"' | '\\' => write!(f, "\\{}", c)?,
+            '%' if literal => write!(f, "%%")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    Ok(())
 }
 
-impl TryFrom<String> for Entry {
-    type Error = anyhow::Error;
+/// Writes the entry as part of a quoted format text, i.e. with escaped text.
+impl fmt::Display for Entry<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Text(text) => write_escaped(f, text, true),
+            // fill characters and defaults may need escaping, too
+            Entry::Placeholder(placeholder) => write_escaped(f, &placeholder.to_string(), false),
+            Entry::Style(styles) => {
+                let names: Vec<_> = styles.iter().map(|style| style.name()).collect();
+                write!(f, "%{}{}{}", STYLE_START, names.join(" "), STYLE_END)
+            }
+            Entry::Section(variable) => write!(f, "%{}{}{}{}", MODIFIERS_START, SECTION_START, variable, MODIFIERS_END),
+            Entry::SectionEnd(variable) => {
+                write!(f, "%{}{}{}{}", MODIFIERS_START, SECTION_END, variable, MODIFIERS_END)
+            }
+        }
+    }
+}
+
+impl Entry<'_> {
+    /// Copies borrowed text, so the entry no longer borrows from the parsed input.
+    pub fn into_owned(self) -> Entry<'static> {
+        match self {
+            Entry::Text(text) => Entry::Text(Cow::Owned(text.into_owned())),
+            Entry::Placeholder(placeholder) => Entry::Placeholder(placeholder),
+            Entry::Style(styles) => Entry::Style(styles),
+            Entry::Section(variable) => Entry::Section(variable),
+            Entry::SectionEnd(variable) => Entry::SectionEnd(variable),
+        }
+    }
+}
+
+impl TryFrom<String> for Entry<'static> {
+    type Error = SprintfError;
 
     fn try_from(s: String) -> Result<Self, Self::Error> {
         if s.starts_with("%%") {
-            return Ok(Self::Text(s[1..].into()));
+            return Ok(Self::Text(s[1..].to_string().into()));
         }
-        match s.chars().next().ok_or_else(|| anyhow!("Empty input string"))? {
-            '%' => Ok(Self::Placeholder(s.try_into()?)),
-            _ => Ok(Self::Text(s)),
+        match s.chars().next() {
+            Some('%') => Ok(Self::Placeholder(s.try_into()?)),
+            _ => Ok(Self::Text(s.into())),
         }
     }
 }
 
-#[derive(Debug)]
-pub struct ParsedFormatString {
-    pub entries: Vec<Entry>,
+/// Byte range within the parsed input.
+pub type Span = Range<usize>;
+
+/// A parsed template, which is [Send] and [Sync]. Its text borrows from the parsed input where
+/// possible, [ParsedFormat::into_owned] makes it independent of the input. Wrap it into a
+/// [SharedFormatString] to share it between threads without copying it.
+/// ```
+/// use std::{collections::HashMap, thread};
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let parsed = parse_format_string(r#""worker %d", id"#).unwrap().into_shared();
+/// let workers: Vec<_> = (0..4)
+///     .map(|id| {
+///         let parsed = parsed.clone();
+///         thread::spawn(move || {
+///             let mut values = HashMap::new();
+///             values.insert("id", Value::Numeric(id as f64));
+///             let values: MapResolver = values.into();
+///             sprintf(&parsed, &values).unwrap()
+///         })
+///     })
+///     .collect();
+/// let printed: Vec<_> = workers.into_iter().map(|worker| worker.join().unwrap()).collect();
+/// assert_eq!(printed, vec!["worker 0", "worker 1", "worker 2", "worker 3"]);
+///
+/// let copy = (*parsed).clone();
+/// assert_eq!(copy, *parsed);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedFormat<'a> {
+    pub entries: Vec<Entry<'a>>,
     pub variables: Vec<String>,
+    /// The parsed `variables`, which may also be expressions like `req.total_ms / 1000`.
+    pub arguments: Vec<Expression>,
+    /// Source [Span] of every entry, empty if the entries were not parsed from a string.
+    pub spans: Vec<Span>,
 }
 
-impl ParsedFormatString {
-    pub fn new(entries: Vec<Entry>, variables: Vec<String>) -> Self {
-        Self { entries, variables }
+/// A [ParsedFormat] which owns all its text.
+pub type ParsedFormatString = ParsedFormat<'static>;
+
+/// A [ParsedFormatString] shared by reference counting, cloning it is cheap.
+pub type SharedFormatString = Arc<ParsedFormatString>;
+
+// parsed templates have to stay shareable between threads
+const _: () = {
+    fn assert_send_sync<T: Send + Sync>() {}
+    let _ = assert_send_sync::<ParsedFormatString>;
+};
+
+impl<'a> ParsedFormat<'a> {
+    pub fn new(entries: Vec<Entry<'a>>, variables: Vec<String>) -> Self {
+        let arguments = variables.iter().map(|v| Expression::from_argument(v)).collect();
+        Self {
+            entries,
+            variables,
+            arguments,
+            spans: Vec::new(),
+        }
+    }
+
+    /// Copies all borrowed text, so the template no longer borrows from the parsed input.
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use sprintf::parser::*;
+    ///
+    /// let owned = {
+    ///     let statement = String::from(r#""took %.2fs", duration"#);
+    ///     let parsed = parse_format_string(&statement).unwrap();
+    ///     assert!(matches!(&parsed.entries[0], Entry::Text(Cow::Borrowed("took "))));
+    ///     parsed.into_owned()
+    /// };
+    /// assert_eq!(owned.to_string(), r#""took %.2fs", duration"#);
+    ///
+    /// // text with escape sequences can't borrow
+    /// let parsed = parse_format_string(r#""took\t%.2fs", duration"#).unwrap();
+    /// assert!(matches!(&parsed.entries[0], Entry::Text(Cow::Owned(text)) if text == "took\t"));
+    /// ```
+    pub fn into_owned(self) -> ParsedFormatString {
+        ParsedFormat {
+            entries: self.entries.into_iter().map(Entry::into_owned).collect(),
+            variables: self.variables,
+            arguments: self.arguments,
+            spans: self.spans,
+        }
+    }
+
+    pub fn into_shared(self) -> SharedFormatString {
+        Arc::new(self.into_owned())
+    }
+
+    /// Like [ParsedFormat::new], but with the source span of every entry.
+    pub(crate) fn new_spanned(spanned: Vec<(Entry<'a>, Span)>, variables: Vec<String>) -> Self {
+        let (entries, spans) = spanned.into_iter().unzip();
+        Self {
+            spans,
+            ..Self::new(entries, variables)
+        }
+    }
+
+    /// Iterates over all placeholders with their entry index, bound variable and source span, e.g. to
+    /// highlight them in an editor. The span is `None` if the entries were not parsed from a string.
+    /// ```
+    /// use sprintf::parser::*;
+    ///
+    /// let statement = r#""\t%-8s took %.2fs", user, duration"#;
+    /// let parsed = parse_format_string(statement).unwrap();
+    /// let placeholders: Vec<_> = parsed
+    ///     .placeholders()
+    ///     .map(|(index, _, variable, span)| (index, variable, &statement[span.unwrap()]))
+    ///     .collect();
+    /// assert_eq!(placeholders, vec![(1, "user", "%-8s"), (3, "duration", "%.2f")]);
+    /// ```
+    pub fn placeholders(&self) -> impl Iterator<Item = (usize, &Placeholder, &str, Option<Span>)> {
+        let placeholders = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Entry::Placeholder(placeholder) => Some((index, placeholder)),
+                Entry::Text(_) | Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => None,
+            });
+        placeholders
+            .zip(&self.variables)
+            .map(|((index, placeholder), variable)| {
+                (index, placeholder, variable.as_str(), self.spans.get(index).cloned())
+            })
+    }
+
+    /// Whether the template contains only literal text and styles, i.e. prints the same without any
+    /// variables.
+    pub fn is_static(&self) -> bool {
+        self.entries.iter().all(|entry| !matches!(entry, Entry::Placeholder(_)))
+    }
+
+    /// The distinct names of the variables the arguments and sections refer to, e.g. to fetch only
+    /// those.
+    /// ```
+    /// use sprintf::parser::parse_format_string;
+    ///
+    /// let parsed = parse_format_string(r#""%s: %.2f of %.2f", user, used / total, total"#).unwrap();
+    /// assert_eq!(parsed.names(), vec!["user", "used", "total"]);
+    ///
+    /// let parsed = parse_format_string(r#""%s%{?error} failed%{/error}", job"#).unwrap();
+    /// assert_eq!(parsed.names(), vec!["job", "error"]);
+    /// ```
+    pub fn names(&self) -> Vec<&str> {
+        let sections = self.entries.iter().filter_map(|entry| match entry {
+            Entry::Section(variable) => Some(variable.as_str()),
+            _ => None,
+        });
+        let mut names = Vec::new();
+        for name in self.arguments.iter().flat_map(Expression::variables).chain(sections) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The distinct variables with the kind of value they take, in order of appearance. Variables of
+    /// expressions like `used / total` take floats, variables bound to several placeholders the
+    /// [ValueKind::narrow]ed kind.
+    /// ```
+    /// use sprintf::{parser::parse_format_string, specifiers::ValueKind};
+    ///
+    /// let parsed = parse_format_string(r#""%s: %d of %.2f (%v), %d%%", user, used, used / total, total, total"#).unwrap();
+    /// assert_eq!(
+    ///     parsed.signature(),
+    ///     vec![("user", ValueKind::String), ("used", ValueKind::Integer), ("total", ValueKind::Integer)]
+    /// );
+    /// ```
+    pub fn signature(&self) -> Vec<(&str, ValueKind)> {
+        let mut signature: Vec<(&str, ValueKind)> = Vec::new();
+        for ((_, placeholder, _, _), argument) in self.placeholders().zip(&self.arguments) {
+            let kind = match argument {
+                Expression::Variable(_) => placeholder.expected_kind(),
+                _ => ValueKind::Float,
+            };
+            for name in argument.variables() {
+                match signature.iter_mut().find(|(known, _)| *known == name) {
+                    Some((_, known)) => *known = known.narrow(kind),
+                    None => signature.push((name, kind)),
+                }
+            }
+        }
+        signature
+    }
+
+    /// Builds a [ParsedFormat] from a bare format string as accepted by [parse_format] and its
+    /// variables, e.g. as produced by `template_concat!` of the `sprintf-macros` crate.
+    pub fn from_format(format: &'a str, variables: &[&str]) -> Result<Self> {
+        let entries = parse_format(format)?;
+        check_arity(&entries, variables)?;
+
+        Ok(Self::new(entries, variables.iter().map(|v| v.to_string()).collect()))
+    }
+}
+
+/// Reconstructs a `"...", var, var` statement as accepted by [parse_format_string].
+/// ```
+/// use sprintf::parser::parse_format_string;
+///
+/// let statement = r#""%-8s took %06.2fs\n(100%% \"done\")", user, req.total_ms / 1000"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// ```
+impl fmt::Display for ParsedFormat<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for entry in &self.entries {
+            write!(f, "{}", entry)?;
+        }
+        write!(f, "\"")?;
+        for variable in &self.variables {
+            write!(f, ", {}", variable)?;
+        }
+        Ok(())
     }
 }
 
@@ -148,81 +901,741 @@ impl ParsedFormatString {
 /// println!("{:?}", x);
 /// assert_eq!(x.len(), 7);
 /// assert!(matches!(x[0], Entry::Placeholder(_)));
-/// assert_eq!(x[1], Entry::Text(" this is a test ".to_string()));
+/// assert_eq!(x[1], Entry::Text(" this is a test ".into()));
 /// assert!(matches!(x[2], Entry::Placeholder(_)));
-/// assert_eq!(x[3], Entry::Text(" hello ".to_string()));
+/// assert_eq!(x[3], Entry::Text(" hello ".into()));
 /// assert!(matches!(x[4], Entry::Placeholder(_)));
-/// assert_eq!(x[5], Entry::Text("h with 42".to_string()));
-/// assert_eq!(x[6], Entry::Text("% foo".to_string()));
-/// ```
-pub fn explode(input: &str) -> Result<Vec<Entry>> {
-    let mut result: Vec<Entry> = Vec::new();
-    let mut in_placeholder = false;
-    let mut buffer = String::new();
-    for c in input.chars() {
-        match c {
-            '%' if in_placeholder => {
-                // %%
-                in_placeholder = false;
-                buffer.push(c);
-            }
-            '%' => {
-                if buffer.len() > 0 {
-                    result.push(buffer.try_into()?);
-                }
-                buffer = String::new();
-                buffer.push(c);
-                in_placeholder = true
-            }
-            'a'..='z' | 'A'..='Z' => {
-                buffer.push(c);
-                if in_placeholder {
-                    in_placeholder = false;
-                    result.push(buffer.try_into()?);
-                    buffer = String::new();
-                }
-            }
-            _ => buffer.push(c),
+/// assert_eq!(x[5], Entry::Text("h with 42".into()));
+/// assert_eq!(x[6], Entry::Text("% foo".into()));
+///
+/// let x = explode("%*x10d|%*%4d").unwrap();
+/// assert_eq!(x.len(), 3);
+/// assert!(matches!(x[0], Entry::Placeholder(Placeholder::Number(_))));
+/// assert_eq!(x[1], Entry::Text("|".into()));
+/// assert!(matches!(x[2], Entry::Placeholder(Placeholder::Number(_))));
+///
+/// let err = explode("Hello %s, you have %q mails").unwrap_err();
+/// assert_eq!(err.to_string(), "invalid conversion 'q' at column 21");
+/// ```
+pub fn explode(input: &str) -> Result<Vec<Entry<'_>>> {
+    explode_with(input, &ParseOptions::default())
+}
+
+/// Like [explode], but handles a `%` which does not start a valid placeholder as configured by
+/// [ParseOptions::stray_percent].
+/// ```
+/// use sprintf::{options::*, parser::*};
+///
+/// let lenient = ParseOptions {
+///     stray_percent: StrayPercent::Literal,
+///     ..Default::default()
+/// };
+/// let text = |s: &str| Entry::Text(s.to_string().into());
+///
+/// // input, strict, lenient
+/// let matrix = [
+///     ("100%", None, vec![text("100%")]),
+///     ("%0%d", None, vec![text("%0"), Entry::Placeholder("%d".to_string().try_into().unwrap())]),
+///     ("50%q", None, vec![text("50%q")]),
+///     ("%%", Some(vec![text("%")]), vec![text("%")]),
+/// ];
+/// for (input, strict, lenient_result) in matrix {
+///     assert_eq!(explode_with(input, &ParseOptions::default()).ok(), strict, "{}", input);
+///     assert_eq!(explode_with(input, &lenient).unwrap(), lenient_result, "{}", input);
+/// }
+/// ```
+pub fn explode_with<'a>(input: &'a str, options: &ParseOptions) -> Result<Vec<Entry<'a>>> {
+    Ok(explode_spanned(input, options)?
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect())
+}
+
+/// Parses a built-in placeholder or one of [ParseOptions::custom_conversions].
+fn parse_placeholder(placeholder: &str, options: &ParseOptions) -> Result<Placeholder> {
+    if options.dialect == Dialect::C99 {
+        return Ok(Placeholder::C99(CFormat::parse(placeholder)?));
+    }
+    if let Some(block) = extract_modifiers(&placeholder[1..])? {
+        let mut inner = parse_placeholder(&format!("%{}", block.rest), options)?;
+        if let Some(pattern) = block.pattern {
+            inner.set_pattern(pattern)?;
         }
+        return Ok(match block.modifiers == Modifiers::default() {
+            // just a pattern
+            true => inner,
+            false => Placeholder::Modified(block.modifiers, Box::new(inner)),
+        });
     }
-    if buffer.len() > 0 {
-        result.push(buffer.try_into()?);
+    match placeholder.chars().next_back() {
+        Some(c) if Conversion::from_char(c).is_none() && options.custom_conversions.contains(&c) => {
+            let spec = &placeholder[1..placeholder.len() - c.len_utf8()];
+            Ok(Placeholder::Custom(c, spec.to_string()))
+        }
+        _ => Placeholder::parse_builtin(placeholder),
     }
+}
 
-    Ok(result)
+/// Parses the styles of a style token like `%[bold red]`.
+fn parse_styles(token: &str) -> Result<Vec<Style>> {
+    let names = &token[2..token.len() - STYLE_END.len_utf8()];
+    let styles: Option<Vec<_>> = names.split_whitespace().map(Style::from_name).collect();
+    match styles {
+        Some(styles) if !styles.is_empty() => Ok(styles),
+        _ => Err(SprintfError::InvalidPlaceholder(token.into())),
+    }
 }
 
-pub fn parse_format_string(input: &str) -> Result<ParsedFormatString> {
-    let mut variables = Vec::<String>::new();
+/// Parses a section token like `%{?error}` or `%{/error}`, ends have to match the innermost of the
+/// `open` sections.
+fn parse_section(token: &str, open: &mut Vec<String>) -> Result<Entry<'static>> {
+    let variable = token[3..token.len() - MODIFIERS_END.len_utf8()].trim();
+    if variable.is_empty() {
+        return Err(SprintfError::InvalidPlaceholder(token.into()));
+    }
+    if token[2..].starts_with(SECTION_START) {
+        open.push(variable.to_string());
+        return Ok(Entry::Section(variable.to_string()));
+    }
+    match open.pop() {
+        Some(started) if started == variable => Ok(Entry::SectionEnd(started)),
+        _ => Err(SprintfError::InvalidTemplate(format!(
+            "section `{}` ends without being started",
+            variable
+        ))),
+    }
+}
 
-    let text_start = input.find('\"').unwrap_or(0) + 1;
-    let text_end = input[text_start + 1..]
-        .find('\"')
-        .ok_or(anyhow!("No terminating quote found"))?
-        + 1;
+/// Literal text collected from consecutive tokens. It borrows from the input as long as the text
+/// is contiguous there, `%%` contributes its second `%`.
+#[derive(Default)]
+struct TextRun<'a> {
+    text: Cow<'a, str>,
+    /// Where the source of the run starts within the input, including a leading `%%`.
+    start: usize,
+    /// Where the borrowed text ends within the input.
+    end: usize,
+}
 
-    let placeholder = explode(&input[text_start..text_start + text_end])?;
+impl<'a> TextRun<'a> {
+    /// Appends `input[range]`, the text of a token whose source starts at `source`.
+    fn push(&mut self, input: &'a str, range: Range<usize>, source: usize) {
+        match &self.text {
+            text if text.is_empty() => {
+                self.start = source;
+                self.text = Cow::Borrowed(&input[range.clone()]);
+            }
+            Cow::Borrowed(text) if self.end == range.start => {
+                self.text = Cow::Borrowed(&input[range.start - text.len()..range.end]);
+            }
+            _ => self.text.to_mut().push_str(&input[range.clone()]),
+        }
+        self.end = range.end;
+    }
 
-    let vars = input[2 + text_end..].split(",");
-    for var in vars {
-        // var could also be the maybe existing comma behind the text
-        if var.len() > 0 {
-            variables.push(var.trim().into());
+    /// Moves the collected text into `result`, its span ending at `end`.
+    fn flush(&mut self, result: &mut Vec<(Entry<'a>, Span)>, end: usize) {
+        if !self.text.is_empty() {
+            result.push((Entry::Text(std::mem::take(&mut self.text)), self.start..end));
         }
     }
+}
 
-    let placeholder_count = placeholder
+/// Like [explode_with], but also returns the [Span] of every entry within `input`.
+fn explode_spanned<'a>(input: &'a str, options: &ParseOptions) -> Result<Vec<(Entry<'a>, Span)>> {
+    let mut result = Vec::new();
+    let mut run = TextRun::default();
+    // variables of the sections not closed yet
+    let mut sections = Vec::new();
+    let tokenizer = match options.dialect {
+        Dialect::C99 => Tokenizer::c99(input),
+        Dialect::Printf | Dialect::Python => Tokenizer::with_conversions(input, &options.custom_conversions),
+    };
+    for token in tokenizer {
+        let (token, span) = match (token, options.stray_percent) {
+            (Ok(token), _) => token,
+            // the tokenizer continues behind the '%'
+            (
+                Err(e @ (SprintfError::UnknownConversion { .. } | SprintfError::UnterminatedFormat { .. })),
+                StrayPercent::Literal,
+            ) => {
+                let start = e.span().unwrap_or_default().start;
+                (Token::Text("%"), start..start + 1)
+            }
+            (Err(e), _) => return Err(e),
+        };
+        match token {
+            // a text token is the input at its span, also the '%' of a stray percent
+            Token::Text(_) => run.push(input, span.clone(), span.start),
+            Token::Percent => {
+                // %% starts a new text entry
+                run.flush(&mut result, span.start);
+                run.push(input, span.start + 1..span.end, span.start);
+            }
+            Token::Style(token) => {
+                run.flush(&mut result, span.start);
+                result.push((Entry::Style(parse_styles(token)?), span));
+            }
+            Token::Section(token) => {
+                run.flush(&mut result, span.start);
+                result.push((parse_section(token, &mut sections)?, span));
+            }
+            Token::Placeholder(placeholder) => {
+                run.flush(&mut result, span.start);
+                result.push((Entry::Placeholder(parse_placeholder(placeholder, options)?), span));
+            }
+        }
+    }
+    run.flush(&mut result, input.len());
+    if let Some(variable) = sections.pop() {
+        return Err(SprintfError::InvalidTemplate(format!(
+            "section `{}` is not closed",
+            variable
+        )));
+    }
+
+    Ok(result)
+}
+
+/// Parses a bare printf style format string like `%s took %.2fs` without quotes and variable list.
+/// Use [crate::printer::sprintf_entries] to print the result with separately given variables.
+/// ```
+/// use sprintf::parser::*;
+///
+/// let entries = parse_format("%s took %.2fs").unwrap();
+/// assert_eq!(entries.len(), 4);
+/// assert!(matches!(entries[0], Entry::Placeholder(Placeholder::String(_))));
+/// assert_eq!(entries[3], Entry::Text("s".into()));
+/// ```
+pub fn parse_format(input: &str) -> Result<Vec<Entry<'_>>> {
+    explode(input)
+}
+
+/// Fails if the number of placeholders in `entries` differs from the number of `variables`.
+pub(crate) fn check_arity(entries: &[Entry], variables: &[impl AsRef<str>]) -> Result<()> {
+    let placeholder_count = entries
         .iter()
         .filter(|item| matches!(item, Entry::Placeholder(_)))
         .count();
 
     if variables.len() != placeholder_count {
-        bail!(
-            "Unmatched variables({}) and placeholders({})",
-            variables.len(),
-            placeholder_count
-        );
+        return Err(SprintfError::ArityMismatch {
+            variables: variables.len(),
+            placeholders: placeholder_count,
+        });
+    }
+
+    Ok(())
+}
+
+/// Byte range of the quoted format text within a `"...", var, var` statement.
+/// Escaped quotes (`\"`) do not terminate the text.
+pub(crate) fn literal_range(input: &str, options: &ParseOptions) -> Result<Range<usize>> {
+    let text_start = input
+        .find(options.quote)
+        .map(|start| start + options.quote.len_utf8())
+        .ok_or(SprintfError::MissingQuote)?;
+
+    let mut chars = input[text_start..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            c if c == options.quote => return Ok(text_start..text_start + i),
+            _ => {}
+        }
+    }
+
+    Err(SprintfError::UnterminatedQuote)
+}
+
+/// Resolves the escape sequences `\n`, `\t`, `\"`, `\\` and `\u{...}` of a quoted format text.
+/// The configured quote can be escaped as well, e.g. `\'`.
+///
+/// Also returns the offset within `literal` every byte of the result originates from, followed by
+/// the length of `literal`. Text without escape sequences is returned as is, without offsets.
+fn unescape<'a>(literal: &'a str, options: &ParseOptions) -> Result<(Cow<'a, str>, Vec<usize>)> {
+    if !literal.contains('\\') {
+        return Ok((Cow::Borrowed(literal), Vec::new()));
+    }
+    let mut result = String::with_capacity(literal.len());
+    let mut offsets = Vec::with_capacity(literal.len() + 1);
+    let mut chars = literal.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            offsets.resize(result.len(), offset);
+            continue;
+        }
+        match chars
+            .next()
+            .map(|(_, c)| c)
+            .ok_or_else(|| SprintfError::InvalidEscape("\\".into()))?
+        {
+            'n' => result.push('\n'),
+            't' => result.push('\t'),
+            '"' => result.push('"'),
+            '\\' => result.push('\\'),
+            c if c == options.quote => result.push(c),
+            'u' => {
+                let code = chars
+                    .as_str()
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(code, _)| code)
+                    .ok_or_else(|| SprintfError::InvalidEscape("\\u".into()))?;
+                let c = u32::from_str_radix(code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| SprintfError::InvalidEscape(format!("\\u{{{}}}", code)))?;
+                result.push(c);
+                // skip "{code}"
+                chars.nth(code.len() + 1);
+            }
+            c => return Err(SprintfError::InvalidEscape(format!("\\{}", c))),
+        }
+        offsets.resize(result.len(), offset);
+    }
+    offsets.push(literal.len());
+
+    Ok((Cow::Owned(result), offsets))
+}
+
+/// Parses a statement like `"%s took %.2fs", user, duration` into a [ParsedFormatString].
+///
+/// The variables may also be arithmetic expressions like `req.total_ms / 1000`, see [Expression].
+///
+/// The quoted text may contain the escape sequences `\n`, `\t`, `\"`, `\\` and `\u{...}`.
+/// ```
+/// use sprintf::parser::*;
+///
+/// let parsed = parse_format_string(r#""say \"%s\"\n\tand \u{1F600}\\", what"#).unwrap();
+/// assert_eq!(parsed.variables, vec!["what".to_string()]);
+/// assert_eq!(parsed.entries[0], Entry::Text("say \"".into()));
+/// assert_eq!(parsed.entries[2], Entry::Text("\"\n\tand \u{1F600}\\".into()));
+///
+/// assert!(parse_format_string(r#""\q", what"#).is_err());
+///
+/// // empty and literal only statements have no variables, a trailing separator is fine
+/// for statement in [r#""""#, r#""", "#, r#""just text""#, r#""just text","#] {
+///     let parsed = parse_format_string(statement).unwrap();
+///     assert!(parsed.variables.is_empty());
+///     assert!(parsed.is_static());
+/// }
+/// assert!(parse_format_string(r#""""#).unwrap().entries.is_empty());
+/// assert!(parse_format_string("").is_err());
+/// assert!(parse_format_string(r#""\u{110000}", what"#).is_err());
+/// ```
+///
+/// Placeholders may refer to a variable by its 1-based position like `%1$s` to print it several
+/// times, or name it inline like `%(attempts)d`. Sequential placeholders take the listed variables
+/// in order, positional ones may refer to any of them. Every listed variable has to be bound.
+/// ```
+/// use sprintf::{error::SprintfError, parser::*};
+///
+/// let statement = r#""%1$s is %2$d, %1$-6s!", name, age"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.variables, vec!["name", "age", "name"]);
+/// assert_eq!(parsed.entries[4], Entry::Placeholder("%-6s".to_string().try_into().unwrap()));
+/// assert_eq!(&statement[parsed.spans[4].clone()], "%1$-6s");
+///
+/// assert_eq!(
+///     parse_format_string(r#""%1$s and %1$s", name, age"#).unwrap_err(),
+///     SprintfError::ArityMismatch { variables: 2, placeholders: 1 }
+/// );
+/// assert!(parse_format_string(r#""%1$s and %3$s", name, age"#).is_err());
+///
+/// let statement = r#""%s did %(attempts)d in %2$.1fs, bye %1$s", user, elapsed"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.variables, vec!["user", "attempts", "elapsed", "user"]);
+/// assert_eq!(&statement[parsed.spans[2].clone()], "%(attempts)d");
+/// assert_eq!(parsed.to_string(), r#""%s did %d in %.1fs, bye %s", user, attempts, elapsed, user"#);
+/// assert!(parse_format_string(r#""%s did %(attempts)d", user, attempts"#).is_err());
+/// ```
+pub fn parse_format_string(input: &str) -> Result<ParsedFormat<'_>> {
+    parse_format_string_with(input, &ParseOptions::default())
+}
+
+/// Like [parse_format_string], but with a different quote, variable separator or [Dialect].
+/// ```
+/// use sprintf::{
+///     error::SprintfError,
+///     options::{Dialect, ParseOptions},
+///     parser::*,
+/// };
+///
+/// let options = ParseOptions {
+///     quote: '\'',
+///     separator: ';',
+///     ..Default::default()
+/// };
+/// let parsed = parse_format_string_with(r#"'%s said "%s" at \'%s\'';user;text;place"#, &options).unwrap();
+/// assert_eq!(parsed.variables, vec!["user", "text", "place"]);
+/// assert_eq!(parsed.entries[1], Entry::Text(" said \"".into()));
+/// assert_eq!(parsed.entries[3], Entry::Text("\" at '".into()));
+///
+/// let options = ParseOptions {
+///     quote: '`',
+///     ..Default::default()
+/// };
+/// let parsed = parse_format_string_with("`%d%%`, load", &options).unwrap();
+/// assert_eq!(parsed.variables, vec!["load"]);
+///
+/// let options = ParseOptions {
+///     quote: '«',
+///     ..Default::default()
+/// };
+/// let parsed = parse_format_string_with("«%s took«, user", &options).unwrap();
+/// assert_eq!(parsed.variables, vec!["user"]);
+/// assert_eq!(parsed.entries[1], Entry::Text(" took".into()));
+/// assert_eq!(
+///     parse_format_string_with("%s took, user", &options).unwrap_err(),
+///     SprintfError::MissingQuote
+/// );
+///
+/// let python = ParseOptions {
+///     dialect: Dialect::Python,
+///     ..Default::default()
+/// };
+/// let statement = r#""%(user.name)s failed %(tries)03d times (100%%)""#;
+/// let parsed = parse_format_string_with(statement, &python).unwrap();
+/// assert_eq!(parsed.variables, vec!["user.name", "tries"]);
+/// assert_eq!(parsed.entries[0], Entry::Placeholder("%s".to_string().try_into().unwrap()));
+/// assert_eq!(&statement[parsed.spans[2].clone()], "%(tries)03d");
+///
+/// assert!(parse_format_string_with(r#""%(user)s", user"#, &python).is_err());
+/// assert!(parse_format_string_with(r#""%(user)s and %s""#, &python).is_err());
+/// assert!(parse_format_string_with(r#""%(user"#, &python).is_err());
+/// ```
+pub fn parse_format_string_with<'a>(input: &'a str, options: &ParseOptions) -> Result<ParsedFormat<'a>> {
+    options.limits.check_len(input.len())?;
+    let literal = literal_range(input, options)?;
+    let (placeholder, bindings) = parse_literal(input, literal.clone(), options)?;
+    let variables = parse_variables(&input[literal.end + options.quote.len_utf8()..], options);
+    let variables = bind_variables(bindings, variables, options)?;
+
+    let parsed = ParsedFormat::new_spanned(placeholder, variables);
+    check_arity(&parsed.entries, &parsed.variables)?;
+    options.limits.check(&parsed.entries)?;
+
+    Ok(parsed)
+}
+
+/// Entries with their [Span].
+type Spanned<'a> = Vec<(Entry<'a>, Span)>;
+
+/// How a placeholder is bound to its variable.
+#[derive(Debug, Clone, PartialEq)]
+enum Binding {
+    /// The next variable of the list.
+    Next,
+    /// The variable at a 1-based position of the list, like `%2$s`.
+    Position(usize),
+    /// A variable named inline, like `%(user)s`.
+    Name(String),
+}
+
+/// Explodes the quoted text at `literal` within `input`, with spans relative to `input`. Also
+/// returns the [Binding] of every placeholder, or none if all take the next variable.
+fn parse_literal<'a>(
+    input: &'a str,
+    literal: Range<usize>,
+    options: &ParseOptions,
+) -> Result<(Spanned<'a>, Vec<Binding>)> {
+    let shift = |(entry, span): (Entry<'a>, Span)| (entry, literal.start + span.start..literal.start + span.end);
+    let (text, offsets) = unescape(&input[literal.clone()], options)?;
+    let (text, offsets, bindings) = match options.dialect {
+        Dialect::Printf => strip_bindings(text, offsets, options)?,
+        Dialect::C99 => (text, offsets, Vec::new()),
+        Dialect::Python => {
+            let (text, offsets, names) = strip_names(text, offsets)?;
+            (text, offsets, names.into_iter().map(Binding::Name).collect())
+        }
+    };
+    let entries = match text {
+        Cow::Borrowed(text) => explode_spanned(text, options)?.into_iter().map(shift).collect(),
+        // the entries can't borrow from the unescaped copy
+        Cow::Owned(text) => {
+            let source = |offset: usize| literal.start + offsets[offset];
+            explode_spanned(&text, options)?
+                .into_iter()
+                .map(|(entry, span)| (entry.into_owned(), source(span.start)..source(span.end)))
+                .collect()
+        }
+    };
+    Ok((entries, bindings))
+}
+
+/// Removes the binding of placeholders like `%2$s` or `%(user)s` from `text`, whose bytes originate
+/// from `offsets` as returned by [unescape]. Returns the remaining text with its offsets and the
+/// [Binding] of every placeholder, or none if all placeholders take the next variable.
+fn strip_bindings<'a>(
+    text: Cow<'a, str>,
+    offsets: Vec<usize>,
+    options: &ParseOptions,
+) -> Result<(Cow<'a, str>, Vec<usize>, Vec<Binding>)> {
+    if !text.contains('$') && !text.contains(NAME_START) {
+        return Ok((text, offsets, Vec::new()));
+    }
+    let mut bindings = Vec::new();
+    // byte ranges of the "N$" and "(name)" to remove, errors are reported when exploding the text
+    let mut removed = Vec::new();
+    for (token, span) in Tokenizer::with_conversions(&text, &options.custom_conversions).flatten() {
+        let Token::Placeholder(placeholder) = token else {
+            continue;
+        };
+        if let Some(name) = placeholder[1..].strip_prefix(NAME_START) {
+            let name = name.split_once(NAME_END).map(|(name, _)| name).unwrap_or_default();
+            if name.trim().is_empty() {
+                return Err(SprintfError::InvalidPlaceholder(placeholder.into()));
+            }
+            bindings.push(Binding::Name(name.trim().to_string()));
+            removed.push(span.start + 1..span.start + 3 + name.len());
+            continue;
+        }
+        let digits = placeholder[1..]
+            .split_once('$')
+            .map(|(digits, _)| digits)
+            .filter(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()));
+        match digits {
+            Some(digits) => {
+                let position = digits
+                    .parse()
+                    .ok()
+                    .filter(|&position| position > 0)
+                    .ok_or_else(|| SprintfError::InvalidPlaceholder(placeholder.into()))?;
+                bindings.push(Binding::Position(position));
+                removed.push(span.start + 1..span.start + 2 + digits.len());
+            }
+            None => bindings.push(Binding::Next),
+        }
+    }
+    if removed.is_empty() {
+        return Ok((text, offsets, Vec::new()));
+    }
+
+    let origin = |offset: usize| match offsets.is_empty() {
+        true => offset,
+        false => offsets[offset],
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut result_offsets = Vec::with_capacity(text.len() + 1);
+    let mut copied = 0;
+    for range in removed {
+        result.push_str(&text[copied..range.start]);
+        result_offsets.extend((copied..range.start).map(origin));
+        copied = range.end;
+    }
+    result.push_str(&text[copied..]);
+    result_offsets.extend((copied..text.len()).map(origin));
+    result_offsets.push(origin(text.len()));
+
+    Ok((Cow::Owned(result), result_offsets, bindings))
+}
+
+/// Removes the `(name)` of Python style placeholders like `%(name)s` from `text`, whose bytes
+/// originate from `offsets` as returned by [unescape]. Returns the remaining text with its offsets
+/// and the names in order.
+fn strip_names<'a>(text: Cow<'a, str>, offsets: Vec<usize>) -> Result<(Cow<'a, str>, Vec<usize>, Vec<String>)> {
+    if !text.contains("%(") {
+        return Ok((text, offsets, Vec::new()));
+    }
+    let origin = |offset: usize| match offsets.is_empty() {
+        true => offset,
+        false => offsets[offset],
+    };
+    let mut result = String::with_capacity(text.len());
+    let mut result_offsets = Vec::with_capacity(text.len() + 1);
+    let mut names = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        result.push(c);
+        result_offsets.resize(result.len(), origin(offset));
+        match (c, chars.peek()) {
+            ('%', Some(&(percent, '%'))) => {
+                chars.next();
+                result.push('%');
+                result_offsets.resize(result.len(), origin(percent));
+            }
+            ('%', Some(&(open, '('))) => {
+                let name = text[open + 1..]
+                    .split_once(')')
+                    .map(|(name, _)| name.trim())
+                    .filter(|name| !name.is_empty())
+                    .ok_or_else(|| SprintfError::InvalidPlaceholder(text[offset..].chars().take(16).collect()))?;
+                names.push(name.to_string());
+                // skip "(name)"
+                let close = open + text[open..].find(')').unwrap_or_default();
+                while chars.next_if(|&(i, _)| i <= close).is_some() {}
+            }
+            _ => {}
+        }
+    }
+    result_offsets.push(origin(text.len()));
+
+    Ok((Cow::Owned(result), result_offsets, names))
+}
+
+/// The variable of every placeholder: the `listed` ones in order, the ones referred to by position
+/// and the ones named inline. Python style templates can't have a variable list.
+fn bind_variables(bindings: Vec<Binding>, listed: Vec<String>, options: &ParseOptions) -> Result<Vec<String>> {
+    match options.dialect {
+        Dialect::Printf | Dialect::C99 if bindings.is_empty() => Ok(listed),
+        Dialect::Printf | Dialect::C99 => bind_mixed(bindings, &listed),
+        Dialect::Python if listed.is_empty() => Ok(bindings
+            .into_iter()
+            .filter_map(|binding| match binding {
+                Binding::Name(name) => Some(name),
+                Binding::Next | Binding::Position(_) => None,
+            })
+            .collect()),
+        Dialect::Python => Err(SprintfError::InvalidTemplate(
+            "Python style templates name their variables inline, there is no variable list".into(),
+        )),
+    }
+}
+
+/// The variable of every placeholder of a template with positional or inline named placeholders,
+/// like `user`, `attempts` and `elapsed` for `"%s did %(attempts)d in %2$f s", user, elapsed`.
+///
+/// Sequential placeholders take the `listed` variables in order, positional ones may refer to any.
+/// Every listed variable has to be bound. A variable named inline can't be listed as well, as it
+/// would be unclear which placeholder the listed one belongs to.
+fn bind_mixed(bindings: Vec<Binding>, listed: &[String]) -> Result<Vec<String>> {
+    let sequential = bindings.iter().filter(|binding| **binding == Binding::Next).count();
+    let mut referenced: Vec<usize> = (1..=sequential).collect();
+    for binding in &bindings {
+        match binding {
+            Binding::Position(position) if !referenced.contains(position) => referenced.push(*position),
+            Binding::Name(name) if listed.contains(name) => {
+                return Err(SprintfError::InvalidTemplate(format!(
+                    "variable `{}` is named inline and listed",
+                    name
+                )))
+            }
+            Binding::Next | Binding::Position(_) | Binding::Name(_) => {}
+        }
+    }
+    if referenced.len() != listed.len() {
+        return Err(SprintfError::ArityMismatch {
+            variables: listed.len(),
+            placeholders: referenced.len(),
+        });
+    }
+
+    let mut in_order = listed.iter();
+    bindings
+        .into_iter()
+        .map(|binding| match binding {
+            Binding::Next => in_order.next().cloned().ok_or(SprintfError::ArityMismatch {
+                variables: listed.len(),
+                placeholders: sequential,
+            }),
+            Binding::Position(position) => listed.get(position - 1).cloned().ok_or_else(|| {
+                SprintfError::InvalidTemplate(format!(
+                    "placeholder refers to variable {} of {}",
+                    position,
+                    listed.len()
+                ))
+            }),
+            Binding::Name(name) => Ok(name),
+        })
+        .collect()
+}
+
+/// Splits the variable list behind the quoted text.
+fn parse_variables(input: &str, options: &ParseOptions) -> Vec<String> {
+    input
+        .split(options.separator)
+        .map(str::trim)
+        // var could also be the maybe existing comma behind the text
+        .filter(|var| !var.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Parses a block of several quoted templates sharing one trailing variable list, like
+/// `"== %s ==", "%d items", "by %s", title, count, author`.
+///
+/// The variables are bound in order: every template takes as many variables as it has placeholders.
+/// ```
+/// use sprintf::{options::ParseOptions, parser::*};
+///
+/// let block = r#""== %s ==", "%d items",
+///     "by %s", title, count, author"#;
+/// let parsed = parse_format_block(block, &ParseOptions::default()).unwrap();
+/// assert_eq!(parsed.len(), 3);
+/// assert_eq!(parsed[0].variables, vec!["title"]);
+/// assert_eq!(parsed[1].variables, vec!["count"]);
+/// assert_eq!(parsed[2].variables, vec!["author"]);
+/// assert_eq!(&block[parsed[2].spans[1].clone()], "%s");
+///
+/// assert!(parse_format_block(r#""%s", "%s", title"#, &ParseOptions::default()).is_err());
+/// ```
+pub fn parse_format_block<'a>(input: &'a str, options: &ParseOptions) -> Result<Vec<ParsedFormat<'a>>> {
+    options.limits.check_len(input.len())?;
+    let mut templates = Vec::new();
+    let mut end = 0;
+    loop {
+        let rest = input[end..].trim_start_matches(|c: char| c.is_whitespace() || c == options.separator);
+        if !rest.starts_with(options.quote) {
+            break;
+        }
+        let start = input.len() - rest.len();
+        let literal = literal_range(rest, options)?;
+        let literal = start + literal.start..start + literal.end;
+        templates.push(parse_literal(input, literal.clone(), options)?);
+        end = literal.end + options.quote.len_utf8();
+    }
+    if templates.is_empty() {
+        return Err(SprintfError::UnterminatedQuote);
+    }
+
+    let variables = parse_variables(&input[end..], options);
+    if options.dialect == Dialect::Python {
+        bind_variables(Vec::new(), variables, options)?;
+        return templates
+            .into_iter()
+            .map(|(template, bindings)| {
+                let parsed = ParsedFormat::new_spanned(template, bind_variables(bindings, Vec::new(), options)?);
+                check_arity(&parsed.entries, &parsed.variables)?;
+                options.limits.check(&parsed.entries)?;
+                Ok(parsed)
+            })
+            .collect();
+    }
+    if templates.iter().any(|(_, bindings)| !bindings.is_empty()) {
+        return Err(SprintfError::InvalidTemplate(
+            "positional placeholders like `%1$s` need a variable list of their own".into(),
+        ));
+    }
+    let templates: Vec<_> = templates.into_iter().map(|(template, _)| template).collect();
+    let counts: Vec<_> = templates
+        .iter()
+        .map(|template| {
+            template
+                .iter()
+                .filter(|(entry, _)| matches!(entry, Entry::Placeholder(_)))
+                .count()
+        })
+        .collect();
+    let placeholders = counts.iter().sum();
+    if variables.len() != placeholders {
+        return Err(SprintfError::ArityMismatch {
+            variables: variables.len(),
+            placeholders,
+        });
     }
 
-    Ok(ParsedFormatString::new(placeholder, variables))
+    let mut variables = variables.into_iter();
+    templates
+        .into_iter()
+        .zip(counts)
+        .map(|(template, count)| {
+            let parsed = ParsedFormat::new_spanned(template, variables.by_ref().take(count).collect());
+            options.limits.check(&parsed.entries)?;
+            Ok(parsed)
+        })
+        .collect()
 }
@@ -0,0 +1,157 @@
+use std::{borrow::Cow, mem};
+
+use crate::{
+    error::Result,
+    options::PrintOptions,
+    parser::{check_arity, Entry, ParsedFormat, ParsedFormatString},
+    printer::{resolve_argument, sprintf, write_field, write_joined},
+    value::{Resolver, Value},
+};
+
+/// The values given to [ParsedFormat::bind].
+struct Bound<'a>(&'a [(&'a str, Value)]);
+
+impl Resolver for Bound<'_> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(bound, _)| *bound == name).map(|(_, value)| value)
+    }
+}
+
+/// A template with some of its variables printed into the text already, see [ParsedFormat::bind].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialFormat {
+    parsed: ParsedFormatString,
+}
+
+impl PartialFormat {
+    /// The template left, with placeholders for the variables which weren't bound only.
+    pub fn parsed(&self) -> &ParsedFormatString {
+        &self.parsed
+    }
+
+    pub fn into_parsed(self) -> ParsedFormatString {
+        self.parsed
+    }
+
+    /// Prints the template with the remaining variables resolved by `resolver`.
+    pub fn sprintf(&self, resolver: &impl Resolver) -> Result<String> {
+        sprintf(&self.parsed, resolver)
+    }
+}
+
+impl ParsedFormat<'_> {
+    /// Prints the placeholders of the variables in `values` into the text, e.g. static values of a
+    /// deployment, and keeps the others for later. Placeholders bound to an expression are printed
+    /// if all its variables are in `values`, sections if their variable is.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     parser::{parse_format_string, Entry},
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let statement = r#""[%s/%d] %{?region}%s: %{/region}%s took %.1fs", host, pid, region, user, ms / 1000"#;
+    /// let parsed = parse_format_string(statement).unwrap();
+    /// let partial = parsed
+    ///     .bind(&[
+    ///         ("host", Value::String("web-1".into())),
+    ///         ("pid", Value::Numeric(4711.0)),
+    ///         ("region", Value::String("eu".into())),
+    ///     ])
+    ///     .unwrap();
+    /// assert_eq!(partial.parsed().variables, vec!["user", "ms / 1000"]);
+    /// assert_eq!(partial.parsed().entries[0], Entry::Text("[web-1/4711] eu: ".into()));
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("user", Value::String("foo".into()));
+    /// values.insert("ms", Value::Numeric(1500.0));
+    /// let values: MapResolver = values.into();
+    /// assert_eq!(partial.sprintf(&values).unwrap(), "[web-1/4711] eu: foo took 1.5s");
+    ///
+    /// // a bound value still has to fit its placeholder
+    /// let parsed = parse_format_string(r#""%d", pid"#).unwrap();
+    /// assert!(parsed.bind(&[("pid", Value::String("none".into()))]).is_err());
+    /// ```
+    pub fn bind(&self, values: &[(&str, Value)]) -> Result<PartialFormat> {
+        self.bind_with(values, &PrintOptions::default())
+    }
+
+    /// Like [ParsedFormat::bind], but prints the bound values with `options`. Options like
+    /// [PrintOptions::escape] don't apply to them anymore when printing the [PartialFormat] later.
+    pub fn bind_with(&self, values: &[(&str, Value)], options: &PrintOptions) -> Result<PartialFormat> {
+        check_arity(&self.entries, &self.variables)?;
+
+        let bound = Bound(values);
+        let mut entries = Vec::new();
+        let mut variables = Vec::new();
+        let mut arguments = Vec::new();
+        let mut literal = String::new();
+        // whether each section not ended yet is kept
+        let mut sections = Vec::new();
+        let mut vars = self.variables.iter().zip(&self.arguments);
+        let mut position = 0;
+        for entry in &self.entries {
+            let entry = match entry {
+                Entry::Text(text) => {
+                    literal.push_str(text);
+                    continue;
+                }
+                Entry::Section(variable) => {
+                    let kept = bound.resolve(variable).is_none();
+                    sections.push(kept);
+                    if !kept {
+                        continue;
+                    }
+                    Entry::Section(variable.clone())
+                }
+                Entry::SectionEnd(variable) => {
+                    if sections.pop() == Some(false) {
+                        continue;
+                    }
+                    Entry::SectionEnd(variable.clone())
+                }
+                Entry::Style(styles) => Entry::Style(styles.clone()),
+                Entry::Placeholder(format) => {
+                    // check_arity made sure there is a variable for every placeholder
+                    let Some((variable, argument)) = vars.next() else {
+                        break;
+                    };
+                    position += 1;
+                    let printed = match format.join() {
+                        Some(separator) if bound.resolve(&format!("{}.0", variable)).is_some() => {
+                            Some(write_joined(&mut literal, format, separator, variable, &bound, options))
+                        }
+                        Some(_) => None,
+                        None => resolve_argument(variable, argument, &bound)
+                            .ok()
+                            .map(|value| write_field(&mut literal, format, variable, Ok(value), options)),
+                    };
+                    if let Some(printed) = printed {
+                        printed.map_err(|error| error.in_placeholder(position, format, variable))?;
+                        continue;
+                    }
+                    variables.push(variable.clone());
+                    arguments.push(argument.clone());
+                    Entry::Placeholder(format.clone())
+                }
+            };
+            if !literal.is_empty() {
+                entries.push(Entry::Text(Cow::Owned(mem::take(&mut literal))));
+            }
+            entries.push(entry);
+        }
+        if !literal.is_empty() {
+            entries.push(Entry::Text(Cow::Owned(literal)));
+        }
+
+        Ok(PartialFormat {
+            parsed: ParsedFormat {
+                entries,
+                variables,
+                arguments,
+                spans: Vec::new(),
+            },
+        })
+    }
+}
@@ -0,0 +1,172 @@
+use std::ops::Range;
+
+use crate::{
+    options::ParseOptions,
+    parser::{literal_range, parse_format_string_with},
+    tokenizer::{Token, Tokenizer},
+};
+
+/// What a [Violation] complains about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// The statement could not be parsed at all.
+    Invalid(String),
+    /// A line of the format text is longer than [Policy::max_line_length].
+    LineTooLong { length: usize, max: usize },
+    /// A variable listed in [Policy::required_variables] is not used.
+    MissingVariable(String),
+    /// A conversion listed in [Policy::forbidden_specifiers] is used.
+    ForbiddenSpecifier(char),
+    /// A literal `%` that is not written as `%%`.
+    UnescapedPercent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: Rule,
+    /// Byte range within the checked statement.
+    pub span: Range<usize>,
+}
+
+/// Configurable style guide for format statements like `"%s took %dms", name, duration`.
+///
+/// All rules are off by default.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub max_line_length: Option<usize>,
+    /// Variables every statement has to print, e.g. a timestamp.
+    pub required_variables: Vec<String>,
+    /// Conversion characters like `v` that must not be used.
+    pub forbidden_specifiers: Vec<char>,
+    /// Literal percent signs must be escaped as `%%` instead of relying on lenient parsing.
+    pub require_escaped_percent: bool,
+    /// How the checked statements are parsed.
+    pub parse_options: ParseOptions,
+}
+
+impl Policy {
+    /// Checks a single statement and returns all violations in order of appearance.
+    /// ```
+    /// use sprintf::policy::{Policy, Rule};
+    ///
+    /// let policy = Policy {
+    ///     max_line_length: Some(20),
+    ///     required_variables: vec!["ts".into()],
+    ///     forbidden_specifiers: vec!['v'],
+    ///     require_escaped_percent: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(policy.check(r#""[%s] took %dms", ts, duration"#).is_empty());
+    ///
+    /// let violations = policy.check(r#""%v is at 50% load", host"#);
+    /// assert_eq!(violations.len(), 3);
+    /// assert_eq!(violations[0].rule, Rule::ForbiddenSpecifier('v'));
+    /// assert_eq!(violations[0].span, 1..3);
+    /// assert_eq!(violations[1].rule, Rule::UnescapedPercent);
+    /// assert_eq!(violations[1].span, 12..14);
+    /// assert!(matches!(violations[2].rule, Rule::Invalid(_)));
+    ///
+    /// let violations = policy.check(r#""%s took %dms", host, duration"#);
+    /// assert_eq!(violations.len(), 1);
+    /// assert_eq!(violations[0].rule, Rule::MissingVariable("ts".into()));
+    ///
+    /// let violations = policy.check(r#""this is a rather long message", ts"#);
+    /// assert_eq!(violations[0].rule, Rule::LineTooLong { length: 29, max: 20 });
+    /// ```
+    pub fn check(&self, statement: &str) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        let literal = match literal_range(statement, &self.parse_options) {
+            Ok(literal) => literal,
+            Err(e) => {
+                violations.push(Violation {
+                    rule: Rule::Invalid(e.to_string()),
+                    span: 0..statement.len(),
+                });
+                return violations;
+            }
+        };
+
+        if let Some(max) = self.max_line_length {
+            let mut offset = literal.start;
+            for line in statement[literal.clone()].split('\n') {
+                let length = line.chars().count();
+                if length > max {
+                    violations.push(Violation {
+                        rule: Rule::LineTooLong { length, max },
+                        span: offset..offset + line.len(),
+                    });
+                }
+                offset += line.len() + 1;
+            }
+        }
+
+        for token in Tokenizer::new(&statement[literal.clone()]) {
+            match token {
+                Ok((Token::Placeholder(placeholder), span)) => {
+                    let conversion = placeholder.chars().next_back();
+                    if let Some(conversion) = conversion.filter(|c| self.forbidden_specifiers.contains(c)) {
+                        violations.push(Violation {
+                            rule: Rule::ForbiddenSpecifier(conversion),
+                            span: literal.start + span.start..literal.start + span.end,
+                        });
+                    }
+                }
+                // most likely a literal '%' which is not followed by a valid placeholder
+                Err(e) if self.require_escaped_percent => {
+                    let span = e.span().unwrap_or_default();
+                    violations.push(Violation {
+                        rule: Rule::UnescapedPercent,
+                        span: literal.start + span.start..literal.start + span.end,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        match parse_format_string_with(statement, &self.parse_options) {
+            Ok(parsed) => {
+                for required in &self.required_variables {
+                    if !parsed.variables.contains(required) {
+                        violations.push(Violation {
+                            rule: Rule::MissingVariable(required.clone()),
+                            span: literal.end + self.parse_options.quote.len_utf8()..statement.len(),
+                        });
+                    }
+                }
+            }
+            Err(e) => violations.push(Violation {
+                rule: Rule::Invalid(e.to_string()),
+                span: 0..statement.len(),
+            }),
+        }
+
+        violations
+    }
+
+    /// Checks every named statement of a catalog, e.g. all messages of an application.
+    /// ```
+    /// use sprintf::policy::{Policy, Rule};
+    ///
+    /// let policy = Policy {
+    ///     forbidden_specifiers: vec!['x'],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let catalog = [("greeting", r#""Hello %s", name"#), ("address", r#""at 0x%x", addr"#)];
+    /// let violations = policy.check_catalog(catalog);
+    /// assert_eq!(violations.len(), 1);
+    /// assert_eq!(violations[0].0, "address");
+    /// assert_eq!(violations[0].1.rule, Rule::ForbiddenSpecifier('x'));
+    /// ```
+    pub fn check_catalog<'a>(
+        &self,
+        catalog: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Vec<(&'a str, Violation)> {
+        catalog
+            .into_iter()
+            .flat_map(|(name, statement)| self.check(statement).into_iter().map(move |v| (name, v)))
+            .collect()
+    }
+}
@@ -1,79 +1,911 @@
-use std::fmt::{Display, LowerHex, UpperHex};
-
-use anyhow::{anyhow, bail, Result};
-use metrics_evaluation::{Resolver, Value};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{self, Display, Write},
+    io,
+    ops::Range,
+};
 
 use crate::{
-    parser::{Entry, ParsedFormatString, Placeholder},
-    types::{HexFormat, NumberFormat},
+    collections::Map,
+    error::{Result, SprintfError},
+    expression::Expression,
+    layout::size_hint,
+    options::{
+        Budget, Digits, Escape, HexSign, MaxLen, MissingPolicy, NegativePadding, PrintOptions, RenderIssue, Rounding,
+        TagLabel, TagPosition, TemplateTag, TruncateScope, WidthMode,
+    },
+    parser::{check_arity, parse_format_string, Entry, ParsedFormat, Placeholder},
+    source_map::{OutputSpan, Segment, SourceMap},
+    specifiers::Conversion,
+    types::{
+        Alignment, DurationFormat, DurationUnit, Filter, FloatFormat, HexFormat, Modifiers, NumberFormat, Overflow,
+        SizeFormat, StringFormat, Style,
+    },
+    value::{Resolver, Value},
 };
 
-const DEFAULT_FRACT_DIGITS: u16 = 2;
+#[cfg(feature = "chrono")]
+use crate::types::DateTimeFormat;
+
+const DEFAULT_SIZE_DIGITS: u16 = 1;
+#[cfg(feature = "chrono")]
+const DEFAULT_DATETIME_PATTERN: &str = "%Y-%m-%d %H:%M:%S";
+
+pub(crate) fn get_string(value: &Value) -> Result<&String> {
+    match value {
+        Value::String(s) | Value::Decimal(s) => Ok(s),
+        _ => Err(SprintfError::TypeMismatch { expected: "string" }),
+    }
+}
+
+pub(crate) fn get_number(value: &Value) -> Result<f64> {
+    match value {
+        Value::Numeric(n) => Ok(*n),
+        Value::Integer(n) => Ok(*n as f64),
+        Value::Decimal(digits) => digits
+            .parse()
+            .map_err(|_| SprintfError::TypeMismatch { expected: "numeric" }),
+        _ => Err(SprintfError::TypeMismatch { expected: "numeric" }),
+    }
+}
 
-fn get_string(value: &Value) -> Result<&String> {
+/// The integer for `%d` and `%x`, [Value::Integer]s print exactly.
+pub(crate) fn get_integer(value: &Value) -> Result<i128> {
     match value {
-        Value::String(s) => Ok(s),
-        _ => bail!("Not a string value"),
+        Value::Integer(n) => Ok(*n),
+        Value::Numeric(n) => Ok(n.trunc() as i128),
+        Value::Decimal(_) => exact_decimal(value)
+            .and_then(|decimal| {
+                let integer: i128 = decimal.integer.parse().ok()?;
+                Some(if decimal.negative { -integer } else { integer })
+            })
+            .ok_or(SprintfError::TypeMismatch { expected: "numeric" }),
+        _ => Err(SprintfError::TypeMismatch { expected: "numeric" }),
+    }
+}
+
+/// Wraps a [num_bigint::BigInt] for `%d` and `%f` so it prints exactly.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use num_bigint::BigInt;
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::{bigint_value, sprintf},
+///     value::{MapResolver, Value},
+/// };
+///
+/// let big: BigInt = "-123456789012345678901234567890123456789012".parse().unwrap();
+/// let mut values = HashMap::new();
+/// values.insert("big", bigint_value(&big));
+/// let values: MapResolver = values.into();
+///
+/// let parsed = parse_format_string(r#""%d %.1f", big, big"#).unwrap();
+/// assert_eq!(
+///     sprintf(&parsed, &values).unwrap(),
+///     "-123456789012345678901234567890123456789012 -123456789012345678901234567890123456789012.0"
+/// );
+///
+/// // strings of digits are no numbers, with or without the feature
+/// let parsed = parse_format_string(r#""%d", zip"#).unwrap();
+/// let mut values = HashMap::new();
+/// values.insert("zip", Value::String("01234".into()));
+/// assert!(sprintf(&parsed, &MapResolver::from(values)).is_err());
+/// ```
+#[cfg(feature = "bignum")]
+pub fn bigint_value(value: &num_bigint::BigInt) -> Value {
+    Value::Decimal(value.to_string())
+}
+
+/// Wraps a [rust_decimal::Decimal] for `%d` and `%f` so it prints and rounds exactly.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use rust_decimal::Decimal;
+/// use sprintf::{
+///     options::{PrintOptions, Rounding},
+///     parser::parse_format_string,
+///     printer::{decimal_value, sprintf_with_options},
+///     value::MapResolver,
+/// };
+///
+/// let price: Decimal = "1234567.005".parse().unwrap();
+/// let mut values = HashMap::new();
+/// values.insert("price", decimal_value(&price));
+/// let values: MapResolver = values.into();
+///
+/// let parsed = parse_format_string(r#""%d %.2f", price, price"#).unwrap();
+/// let options = PrintOptions {
+///     rounding: Rounding::HalfEven,
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "1234567 1234567.00");
+/// ```
+#[cfg(feature = "bignum")]
+pub fn decimal_value(value: &rust_decimal::Decimal) -> Value {
+    Value::Decimal(value.to_string())
+}
+
+/// An exact decimal number held as its digits, without a sign.
+struct Decimal<'a> {
+    negative: bool,
+    integer: &'a str,
+    fraction: &'a str,
+}
+
+impl<'a> Decimal<'a> {
+    /// Splits `digits` like `123.45` at the decimal point.
+    fn split(negative: bool, digits: &'a str) -> Self {
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        Self {
+            negative,
+            integer,
+            fraction,
+        }
+    }
+
+    /// The digits of the number times 100, like `42.5` for `0.425`. Unlike multiplying floats this
+    /// is exact, `0.07` becomes `7` instead of `7.000000000000001`.
+    fn percent(&self) -> String {
+        let fraction = format!("{:0<2}", self.fraction);
+        let (shifted, fraction) = fraction.split_at(2);
+        let integer = format!("{}{}", self.integer, shifted);
+        let integer = integer.trim_start_matches('0');
+        format!("{}.{}", if integer.is_empty() { "0" } else { integer }, fraction)
+    }
+
+    /// Parses a plain decimal like `-0123.450`, without exponent.
+    fn parse(s: &'a str) -> Option<Self> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let decimal = Self::split(negative, digits);
+        let is_digits = |digits: &str| digits.bytes().all(|digit| digit.is_ascii_digit());
+        if decimal.integer.is_empty() || !is_digits(decimal.integer) || !is_digits(decimal.fraction) {
+            return None;
+        }
+        let integer = decimal.integer.trim_start_matches('0');
+
+        Some(Self {
+            integer: if integer.is_empty() { "0" } else { integer },
+            ..decimal
+        })
     }
 }
 
-fn get_number(value: &Value) -> Result<&f64> {
+/// Whether `text` is a plain decimal like `-0123.450`, which a [Value::Decimal] can hold.
+pub(crate) fn is_decimal(text: &str) -> bool {
+    Decimal::parse(text).is_some()
+}
+
+/// `value` as an exact decimal, if it is a [Value::Decimal] holding one.
+fn exact_decimal(value: &Value) -> Option<Decimal<'_>> {
     match value {
-        Value::Numeric(n) => Ok(n),
-        _ => bail!("Not a numeric value"),
+        Value::Decimal(digits) => Decimal::parse(digits),
+        _ => None,
+    }
+}
+
+/// `value` as byte size like `1.5 MiB`, in the largest unit it reaches.
+fn print_size(format: &SizeFormat, value: &Value, options: &PrintOptions) -> Result<String> {
+    let (base, units) = match format.si {
+        true => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB", "EB"]),
+        false => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+    };
+    let precision = format.ff.fraction.digits.unwrap_or(DEFAULT_SIZE_DIGITS);
+    let mut scaled = get_number(value)?;
+    let mut unit = 0;
+    // plain bytes have no fraction, and a value rounding up to the base moves on to the next unit
+    let digits = |unit| if unit == 0 { 0 } else { precision };
+    let rounded = |scaled: f64, unit| {
+        let factor = 10f64.powi(digits(unit).into());
+        (scaled.abs() * factor).round() / factor
+    };
+    while unit + 1 < units.len() && rounded(scaled, unit) >= base {
+        scaled /= base;
+        unit += 1;
+    }
+
+    let number = Placeholder::Float(FloatFormat {
+        base: NumberFormat {
+            grouping: format.ff.base.grouping,
+            ..Default::default()
+        },
+        fraction: NumberFormat {
+            digits: Some(digits(unit)),
+            ..Default::default()
+        },
+    });
+    let mut result = String::new();
+    write_latin(&mut result, &number, &Value::Numeric(scaled), options)?;
+    let _ = write!(result, " {}", units[unit]);
+    Ok(result)
+}
+
+/// `value` as duration like `1h 23m 45s`, `2.3s` or `150ms`.
+fn print_duration(format: &DurationFormat, value: &Value, options: &PrintOptions) -> Result<String> {
+    let number = get_number(value)?;
+    if !number.is_finite() {
+        return Ok(options.non_finite.text(number).into());
+    }
+    let millis = match format.unit {
+        DurationUnit::Seconds => number * 1000.0,
+        DurationUnit::Milliseconds => number,
+    };
+    let sign = if millis < 0.0 { "-" } else { "" };
+    let millis = millis.abs();
+    if millis.round() < 1000.0 {
+        return Ok(format!("{}{}ms", sign, millis.round()));
+    }
+    // below a minute with one fractional digit
+    let tenths = (millis / 100.0).round() as u64;
+    if tenths < 600 {
+        return Ok(match tenths % 10 {
+            0 => format!("{}{}s", sign, tenths / 10),
+            tenth => format!("{}{}{}{}s", sign, tenths / 10, options.locale.decimal_point, tenth),
+        });
+    }
+
+    let mut secs = (millis / 1000.0).round() as u64;
+    let mut result = String::from(sign);
+    for (unit, length) in [("d", 86400), ("h", 3600), ("m", 60), ("s", 1)] {
+        let count = secs / length;
+        secs %= length;
+        if count > 0 {
+            if result.len() > sign.len() {
+                result.push(' ');
+            }
+            let _ = write!(result, "{}{}", count, unit);
+        }
+    }
+    Ok(result)
+}
+
+/// `value` as unix timestamp in UTC, printed with the pattern of `format`.
+#[cfg(feature = "chrono")]
+fn print_datetime<'a>(format: &'a DateTimeFormat, value: &Value) -> Result<impl Display + 'a> {
+    let timestamp = get_number(value)?;
+    let secs = timestamp.floor();
+    let datetime = match timestamp.is_finite() {
+        true => chrono::DateTime::from_timestamp(secs as i64, ((timestamp - secs) * 1e9) as u32),
+        false => None,
+    };
+    let datetime = datetime.ok_or(SprintfError::TypeMismatch { expected: "timestamp" })?;
+
+    Ok(datetime.format(format.pattern.as_deref().unwrap_or(DEFAULT_DATETIME_PATTERN)))
+}
+
+/// `value` printed as string and passed through the filters of `modifiers`, as value for `format`.
+fn apply_filters(modifiers: &Modifiers, format: &Placeholder, value: &Value, options: &PrintOptions) -> Result<Value> {
+    if modifiers.filters.is_empty() {
+        return Ok(value.clone());
+    }
+    let mut text = value.to_string();
+    for filter in &modifiers.filters {
+        text = match filter {
+            Filter::Upper => text.to_uppercase(),
+            Filter::Lower => text.to_lowercase(),
+            Filter::Trim => text.trim().to_string(),
+            Filter::Reverse => text.chars().rev().collect(),
+            Filter::Custom(name) => match options.filters.get(name) {
+                Some(handler) => handler.handle(&text),
+                None => return Err(SprintfError::UnknownFilter(name.clone())),
+            },
+            Filter::Plural(branches) => {
+                let number = text
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| SprintfError::TypeMismatch { expected: "numeric" })?;
+                let category = if number == 1.0 { "one" } else { "other" };
+                let branch = branches
+                    .iter()
+                    .find(|branch| branch.key.strip_prefix('=').and_then(|n| n.parse::<f64>().ok()) == Some(number))
+                    .or_else(|| branches.iter().find(|branch| branch.key == category))
+                    .or_else(|| branches.iter().find(|branch| branch.key == "other"));
+                branch.map_or(String::new(), |branch| branch.message.replace('#', &text))
+            }
+            Filter::Select(branches) => branches
+                .iter()
+                .find(|branch| branch.key == text)
+                .or_else(|| branches.iter().find(|branch| branch.key == "other"))
+                .map_or(String::new(), |branch| branch.message.clone()),
+        };
+    }
+
+    Ok(text_value(format, &text))
+}
+
+/// Fails if `value` can't be printed by `format`.
+pub(crate) fn check_value(format: &Placeholder, value: &Value) -> Result<()> {
+    if exact_decimal(value).is_some()
+        && matches!(
+            format,
+            Placeholder::Number(_) | Placeholder::Float(_) | Placeholder::Percent(_)
+        )
+    {
+        return Ok(());
+    }
+    match format {
+        Placeholder::Display(_) => Ok(()),
+        Placeholder::String(_) => get_string(value).map(|_| ()),
+        Placeholder::Number(_) | Placeholder::Hex(_) => get_integer(value).map(|_| ()),
+        Placeholder::Float(_) | Placeholder::Percent(_) => get_number(value).map(|_| ()),
+        Placeholder::Duration(_) | Placeholder::Size(_) => get_number(value).map(|_| ()),
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(dt) => print_datetime(dt, value).map(|_| ()),
+        Placeholder::C99(cf) => cf.print(value).map(|_| ()),
+        // only the handler knows
+        Placeholder::Custom(..) => Ok(()),
+        // filters print the value as string, which may parse differently
+        Placeholder::Modified(..) => Ok(()),
     }
 }
 
+#[derive(Clone, Copy)]
 enum FillStyle {
     Prepend,
     Append,
+    Center,
+}
+
+impl From<Alignment> for FillStyle {
+    fn from(align: Alignment) -> Self {
+        match align {
+            Alignment::Right => FillStyle::Prepend,
+            Alignment::Left => FillStyle::Append,
+            Alignment::Center => FillStyle::Center,
+        }
+    }
+}
+
+/// Rounds `number` to `precision` fraction digits and returns the digits of its integer and
+/// fraction part, without a sign.
+fn round(number: &Decimal, precision: usize, rounding: Rounding) -> (String, String) {
+    let Decimal { integer, fraction, .. } = number;
+    let mut digits: Vec<u8> = integer
+        .bytes()
+        .chain(fraction.bytes().chain(std::iter::repeat(b'0')).take(precision))
+        .collect();
+
+    let dropped = fraction.get(precision..).unwrap_or_default();
+    let inexact = dropped.bytes().any(|digit| digit != b'0');
+    let round_up = match rounding {
+        Rounding::HalfUp => dropped >= "5",
+        Rounding::HalfEven => match dropped.as_bytes().first() {
+            Some(b'5') if dropped[1..].bytes().all(|digit| digit == b'0') => {
+                digits.last().is_some_and(|digit| (digit - b'0') % 2 == 1)
+            }
+            _ => dropped >= "5",
+        },
+        Rounding::Truncate => false,
+        Rounding::Ceil => inexact && !number.negative,
+        Rounding::Floor => inexact && number.negative,
+    };
+
+    if round_up {
+        match digits.iter().rposition(|digit| *digit != b'9') {
+            Some(last) => {
+                digits[last] += 1;
+                digits[last + 1..].fill(b'0');
+            }
+            None => {
+                digits.fill(b'0');
+                digits.insert(0, b'1');
+            }
+        }
+    }
+
+    let fraction = digits.split_off(digits.len() - precision);
+    // only ASCII digits
+    let text = |digits: Vec<u8>| String::from_utf8(digits).unwrap_or_default();
+    (text(digits), text(fraction))
+}
+
+/// Counts the columns written to it.
+struct ColumnCount {
+    mode: WidthMode,
+    count: usize,
+}
+
+impl ColumnCount {
+    fn of(value: impl Display, mode: WidthMode) -> Result<usize, fmt::Error> {
+        let mut count = ColumnCount { mode, count: 0 };
+        write!(count, "{}", value)?;
+        Ok(count.count)
+    }
+}
+
+impl Write for ColumnCount {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.count += self.mode.str_width(s);
+        Ok(())
+    }
+}
+
+/// Passes only the first `remaining` columns on.
+struct Truncated<'a, W> {
+    out: &'a mut W,
+    remaining: usize,
+    mode: WidthMode,
+    /// Set by the first character which didn't fit, everything after it is dropped.
+    full: bool,
+}
+
+impl<W: Write> Write for Truncated<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut end = 0;
+        for (index, c) in s.char_indices() {
+            let width = self.mode.char_width(c);
+            if self.full || width > self.remaining {
+                self.full = true;
+                break;
+            }
+            self.remaining -= width;
+            end = index + c.len_utf8();
+        }
+        self.out.write_str(&s[..end])
+    }
+}
+
+/// Whether `value` has at most `width` characters.
+fn fits(value: impl Display, width: u16) -> bool {
+    ColumnCount::of(value, WidthMode::Chars).is_ok_and(|count| count <= width as usize)
+}
+
+/// Displays `value` padded with `fill` up to `width` columns, or as configured by `overflow` if it
+/// is longer.
+struct Padded<T> {
+    value: T,
+    width: Option<u16>,
+    fill: Option<char>,
+    fill_style: FillStyle,
+    overflow: Overflow,
+    mode: WidthMode,
+}
+
+impl<T: Display> Display for Padded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = match self.width {
+            Some(width) if self.fill.is_some() || self.overflow != Overflow::Extend => width as usize,
+            _ => return write!(f, "{}", self.value),
+        };
+        let count = ColumnCount::of(&self.value, self.mode)?;
+        if count > width {
+            return match self.overflow {
+                Overflow::Extend => write!(f, "{}", self.value),
+                Overflow::Truncate => {
+                    let mut truncated = Truncated {
+                        out: &mut *f,
+                        remaining: width,
+                        mode: self.mode,
+                        full: false,
+                    };
+                    write!(truncated, "{}", self.value)?;
+                    // a wide character didn't fit into the last column
+                    let remaining = truncated.remaining;
+                    let fill = self.fill.unwrap_or(' ');
+                    (0..remaining).try_for_each(|_| f.write_char(fill))
+                }
+                Overflow::Fill(fill) => (0..width).try_for_each(|_| f.write_char(fill)),
+            };
+        }
+        let Some(fill) = self.fill else {
+            return write!(f, "{}", self.value);
+        };
+        let missing = width - count;
+
+        let (before, after) = match self.fill_style {
+            FillStyle::Prepend => (missing, 0),
+            FillStyle::Append => (0, missing),
+            FillStyle::Center => (missing / 2, missing - missing / 2),
+        };
+        (0..before).try_for_each(|_| f.write_char(fill))?;
+        write!(f, "{}", self.value)?;
+        (0..after).try_for_each(|_| f.write_char(fill))
+    }
+}
+
+fn pad<T: Display>(
+    value: T,
+    width: Option<u16>,
+    fill: Option<char>,
+    fill_style: FillStyle,
+    overflow: Overflow,
+) -> Padded<T> {
+    Padded {
+        value,
+        width,
+        fill,
+        fill_style,
+        overflow,
+        mode: WidthMode::Chars,
+    }
+}
+
+/// Displays a value with a leading `-` if `negative`, padded with `fill` up to `width` characters.
+/// Zeros pad between the sign and the value.
+struct SignPadded<T> {
+    negative: bool,
+    magnitude: T,
+    width: Option<u16>,
+    fill: Option<char>,
+    fill_style: FillStyle,
+    overflow: Overflow,
+}
+
+impl<T: Display> Display for SignPadded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.negative { "-" } else { "" };
+        let width = self.width.map(|width| width.saturating_sub(sign.len() as u16));
+        match (self.fill, self.fill_style, width) {
+            // an overflowing value is truncated or replaced including its sign
+            (Some('0'), FillStyle::Prepend, Some(width))
+                if self.overflow == Overflow::Extend || fits(&self.magnitude, width) =>
+            {
+                let magnitude = pad(&self.magnitude, Some(width), self.fill, self.fill_style, self.overflow);
+                write!(f, "{}{}", sign, magnitude)
+            }
+            _ => write!(
+                f,
+                "{}",
+                pad(
+                    format_args!("{}{}", sign, self.magnitude),
+                    self.width,
+                    self.fill,
+                    self.fill_style,
+                    self.overflow
+                )
+            ),
+        }
+    }
+}
+
+struct Hex {
+    value: u128,
+    uppercase: bool,
 }
 
-fn round(number: f64, decimals: u16) -> f64 {
-    let y = 10i32.pow(decimals as u32) as f64;
-    (number * y).round() / y
+impl Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.uppercase {
+            write!(f, "{:X}", self.value)
+        } else {
+            write!(f, "{:x}", self.value)
+        }
+    }
+}
+
+/// Escapes everything written into `out` as configured by [PrintOptions::escape].
+struct Escaped<'a, W> {
+    out: &'a mut W,
+    escape: Escape,
+    /// Whether the opening quote of a shell word is written.
+    quoted: bool,
+}
+
+impl<W: Write> Escaped<'_, W> {
+    /// Closes a shell word, which is quoted even if empty.
+    fn finish(self) -> fmt::Result {
+        if self.escape == Escape::Shell {
+            if !self.quoted {
+                self.out.write_char('\'')?;
+            }
+            self.out.write_char('\'')?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Escaped<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.escape == Escape::Shell && !self.quoted {
+            self.out.write_char('\'')?;
+            self.quoted = true;
+        }
+        for c in s.chars() {
+            match (self.escape, c) {
+                (Escape::Html, '&') => self.out.write_str("&amp;")?,
+                (Escape::Html, '<') => self.out.write_str("&lt;")?,
+                (Escape::Html, '>') => self.out.write_str("&gt;")?,
+                (Escape::Html, '"') => self.out.write_str("&quot;")?,
+                (Escape::Html, '\'') => self.out.write_str("&#39;")?,
+                (Escape::Json, '"' | '\\') => write!(self.out, "\\{}", c)?,
+                (Escape::Json, '\n') => self.out.write_str("\\n")?,
+                (Escape::Json, '\r') => self.out.write_str("\\r")?,
+                (Escape::Json, '\t') => self.out.write_str("\\t")?,
+                (Escape::Json, c) if c.is_control() => write!(self.out, "\\u{:04x}", c as u32)?,
+                // end the quoted word, add an escaped quote and start a new one
+                (Escape::Shell, '\'') => self.out.write_str("'\\''")?,
+                (_, c) => self.out.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Passes at most [MaxLen::chars] characters on to `out` and ends truncated output with the
+/// ellipsis, which is only known once everything is written, see [Limited::finish].
+pub(crate) struct Limited<'a> {
+    out: &'a mut dyn Write,
+    max: &'a MaxLen,
+    /// Characters passed on so far.
+    count: usize,
+    /// Characters which don't fit together with the ellipsis, printed if nothing follows them.
+    held: String,
+    truncated: bool,
+}
+
+impl<'a> Limited<'a> {
+    pub(crate) fn new(out: &'a mut dyn Write, max: &'a MaxLen) -> Self {
+        Self {
+            out,
+            max,
+            count: 0,
+            held: String::new(),
+            truncated: false,
+        }
+    }
+
+    /// Characters which are passed on right away.
+    fn keep(&self) -> usize {
+        kept_chars(self.max)
+    }
+
+    pub(crate) fn finish(self) -> fmt::Result {
+        match self.truncated {
+            true => {
+                let room = self.max.chars - self.keep();
+                let ellipsis = self.max.ellipsis.chars().take(room);
+                ellipsis.into_iter().try_for_each(|c| self.out.write_char(c))
+            }
+            false => self.out.write_str(&self.held),
+        }
+    }
+}
+
+/// Characters a [Limited] passes on before the ellipsis of a truncated output.
+fn kept_chars(max: &MaxLen) -> usize {
+    max.chars.saturating_sub(max.ellipsis.chars().count())
+}
+
+/// Bytes of `output` written by a [Limited] which were passed on as they were written to it, i.e.
+/// without the ellipsis. `chars` are the characters written to it.
+fn unlimited_len(max: &MaxLen, output: &str, chars: usize) -> usize {
+    match chars > max.chars {
+        true => output
+            .char_indices()
+            .nth(kept_chars(max))
+            .map_or(output.len(), |(end, _)| end),
+        false => output.len(),
+    }
+}
+
+impl Write for Limited<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Ok(());
+        }
+        let keep = self.keep();
+        let end = s.char_indices().nth(keep - self.count).map_or(s.len(), |(end, _)| end);
+        self.out.write_str(&s[..end])?;
+        self.count += s[..end].chars().count();
+
+        let room = self.max.chars - keep;
+        for c in s[end..].chars() {
+            if self.held.chars().count() == room {
+                self.truncated = true;
+                break;
+            }
+            self.held.push(c);
+        }
+        Ok(())
+    }
+}
+
+/// Passes everything written to `out` with the ASCII digits replaced by the ones starting at `zero`.
+struct ShapeDigits<'a, W> {
+    out: &'a mut W,
+    zero: char,
+}
+
+impl<W: Write> Write for ShapeDigits<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let shaped = match c.to_digit(10) {
+                Some(digit) => char::from_u32(self.zero as u32 + digit).unwrap_or(c),
+                None => c,
+            };
+            self.out.write_char(shaped)?;
+        }
+        Ok(())
+    }
+}
+
+/// `00` to `99`, to emit two digits per division.
+const DIGIT_PAIRS: &[u8; 200] = b"00010203040506070809101112131415161718192021222324252627282930313233343536373839404142434445464748495051525354555657585960616263646566676869707172737475767778798081828384858687888990919293949596979899";
+
+/// The decimal digits of an integer, emitted into a stack buffer without any allocation.
+struct IntegerDigits {
+    /// `u128::MAX` has 39 digits.
+    buffer: [u8; 39],
+    start: usize,
+}
+
+impl IntegerDigits {
+    fn new(value: u128) -> Self {
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+
+        let mut digits = Self {
+            buffer: [b'0'; 39],
+            start: 39,
+        };
+        // the slow 128 bit division only for the upper digits, in chunks of 19 digits
+        let mut value = value;
+        while value > u64::MAX as u128 {
+            let end = digits.start;
+            digits.push_u64((value % CHUNK) as u64);
+            digits.start = end - 19;
+            value /= CHUNK;
+        }
+        digits.push_u64(value as u64);
+        digits
+    }
+
+    fn push_u64(&mut self, mut value: u64) {
+        while value >= 100 {
+            let pair = (value % 100) as usize * 2;
+            value /= 100;
+            self.start -= 2;
+            self.buffer[self.start..self.start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+        }
+        if value >= 10 {
+            let pair = value as usize * 2;
+            self.start -= 2;
+            self.buffer[self.start..self.start + 2].copy_from_slice(&DIGIT_PAIRS[pair..pair + 2]);
+        } else {
+            self.start -= 1;
+            self.buffer[self.start] = b'0' + value as u8;
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        // only ASCII digits
+        std::str::from_utf8(&self.buffer[self.start..]).unwrap_or_default()
+    }
+}
+
+/// Displays the fraction digits of a float following the decimal point, or nothing without digits.
+struct Fraction<'a> {
+    point: char,
+    digits: &'a str,
+}
+
+impl Display for Fraction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return Ok(());
+        }
+        f.write_char(self.point)?;
+        f.write_str(self.digits)
+    }
+}
+
+/// Displays the digits of an integer with its thousands separated by `separator`.
+struct Grouped<'a> {
+    digits: &'a str,
+    separator: Option<char>,
+}
+
+impl Display for Grouped<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(separator) = self.separator else {
+            return f.write_str(self.digits);
+        };
+        // the leading group has one to three digits, all others exactly three
+        let (leading, rest) = self
+            .digits
+            .split_at(self.digits.len() - (self.digits.len() - 1) / 3 * 3);
+        f.write_str(leading)?;
+        for group in rest.as_bytes().chunks(3) {
+            f.write_char(separator)?;
+            group.iter().try_for_each(|digit| f.write_char(*digit as char))?;
+        }
+        Ok(())
+    }
+}
+
+/// The thousands separator for `format`, if it groups at all.
+fn separator(format: &NumberFormat, options: &PrintOptions) -> Option<char> {
+    options.locale.thousands_separator.filter(|_| format.grouping)
 }
 
-fn print_number(format: &NumberFormat, value: impl Display, fill_style: FillStyle) -> String {
+fn print_number<T: Display>(
+    format: &NumberFormat,
+    negative: bool,
+    magnitude: T,
+    fill_style: FillStyle,
+    options: &PrintOptions,
+) -> SignPadded<T> {
     // Prepend
     // 08d for 123 = 00000123
+    // 08d for -123 = -0000123
     // 02d for 123 = 123
     // 2d for 123 = 123
 
-    let mut repr = format!("{}", value);
-    if let (Some(digits), true) = (format.digits, format.fill_zeros) {
-        match fill_style {
-            FillStyle::Prepend => (repr.len() as u16..digits).for_each(|_| repr.insert(0, '0')),
-            FillStyle::Append => (repr.len() as u16..digits).for_each(|_| repr.push('0')),
-        }
+    // *.8d for 123 = .....123
+    // -8d for 123 = "123     "
+    // ^8d for 123 = "  123   "
+
+    let fill = match (format.fill(), options.negative_padding) {
+        (Some('0'), NegativePadding::Spaces) if negative => Some(' '),
+        (fill, _) => fill,
+    };
+    SignPadded {
+        negative,
+        magnitude,
+        width: format.digits,
+        fill,
+        fill_style,
+        overflow: format.overflow,
     }
-    repr
 }
 
-fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
-    let mut repr = if format.uppercase {
-        format!("{:X}", value)
-    } else {
-        format!("{:x}", value)
+fn print_hex(format: &HexFormat, value: i128, options: &PrintOptions) -> SignPadded<Hex> {
+    let (negative, value) = match options.hex_sign {
+        HexSign::TwosComplement => (false, value as u128),
+        HexSign::SignMagnitude => (value < 0, value.unsigned_abs()),
     };
-    if let (Some(digits), true) = (format.nf.digits, format.nf.fill_zeros) {
-        (repr.len() as u16..digits).for_each(|_| repr.insert(0, '0'));
+    let hex = Hex {
+        value,
+        uppercase: format.uppercase,
+    };
+    print_number(&format.nf, negative, hex, format.nf.align.into(), options)
+}
+
+fn print_string<T: Display>(format: &StringFormat, value: T, options: &PrintOptions) -> Padded<T> {
+    // 8s for "abc" = "     abc"
+    // ^8s for "abc" = "  abc   "
+
+    Padded {
+        mode: options.width_mode,
+        ..pad(
+            value,
+            format.width,
+            Some(format.fill_char.unwrap_or(' ')),
+            format.align.into(),
+            format.overflow,
+        )
     }
-    repr
+}
+
+pub(crate) fn written(result: fmt::Result) -> Result<()> {
+    result.map_err(|_| SprintfError::Write)
+}
+
+/// The ANSI escape sequence of `styles`, e.g. `\x1b[1;31m` for `%[bold red]`.
+pub(crate) fn style_sequence(styles: &[Style], options: &PrintOptions) -> String {
+    if options.strip_styles {
+        return String::new();
+    }
+    let codes: Vec<_> = styles.iter().map(|style| style.code().to_string()).collect();
+    format!("\x1b[{}m", codes.join(";"))
 }
 
 /// ```
-/// use metrics_evaluation::Value;
 /// use sprintf::{
 ///     parser::Placeholder,
 ///     printer::print_value,
-///     types::{FloatFormat, NumberFormat},
+///     types::{Alignment, FloatFormat, NumberFormat, StringFormat},
+///     value::Value,
 /// };
 ///
 /// let s = print_value(
 ///     &Placeholder::Number(NumberFormat {
 ///         digits: Some(3),
 ///         fill_zeros: false,
+///         ..Default::default()
 ///     }),
 ///     &123.into(),
 /// )
@@ -84,6 +916,7 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///     &Placeholder::Number(NumberFormat {
 ///         digits: Some(3),
 ///         fill_zeros: false,
+///         ..Default::default()
 ///     }),
 ///     &Value::Numeric(-123f64),
 /// )
@@ -94,6 +927,7 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///     &Placeholder::Number(NumberFormat {
 ///         digits: Some(3),
 ///         fill_zeros: true,
+///         ..Default::default()
 ///     }),
 ///     &123.into(),
 /// )
@@ -104,16 +938,76 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///     &Placeholder::Number(NumberFormat {
 ///         digits: Some(5),
 ///         fill_zeros: true,
+///         ..Default::default()
 ///     }),
 ///     &123.into(),
 /// )
 /// .unwrap();
 /// assert_eq!(s, "00123".to_string());
 ///
+/// let s = print_value(
+///     &Placeholder::Number(NumberFormat {
+///         digits: Some(6),
+///         fill_char: Some('.'),
+///         ..Default::default()
+///     }),
+///     &123.into(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, "...123".to_string());
+///
+/// let s = print_value(
+///     &Placeholder::Number(NumberFormat {
+///         digits: Some(8),
+///         align: Alignment::Center,
+///         ..Default::default()
+///     }),
+///     &123.into(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, "  123   ".to_string());
+///
+/// let s = print_value(
+///     &Placeholder::String(StringFormat {
+///         width: Some(7),
+///         align: Alignment::Center,
+///         fill_char: Some('='),
+///         ..Default::default()
+///     }),
+///     &"abc".into(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, "==abc==".to_string());
+///
+/// let s = print_value(
+///     &Placeholder::Display(StringFormat {
+///         width: Some(5),
+///         ..Default::default()
+///     }),
+///     &42.into(),
+/// )
+/// .unwrap();
+/// assert_eq!(s, "   42".to_string());
+///
+/// // without a precision, the shortest output that reads back as the same number
 /// let s = print_value(&Placeholder::Float(FloatFormat::default()), &42.123.into()).unwrap();
-/// assert_eq!(s, "42.12".to_string());
+/// assert_eq!(s, "42.123".to_string());
+///
+/// let s = print_value(&Placeholder::Float(FloatFormat::default()), &(0.1 + 0.2).into()).unwrap();
+/// assert_eq!(s, "0.30000000000000004".to_string());
+/// assert_eq!(s.parse::<f64>().unwrap(), 0.1 + 0.2);
 ///
-/// let s = print_value(&Placeholder::Float(FloatFormat::default()), &42.125.into()).unwrap();
+/// let s = print_value(
+///     &Placeholder::Float(FloatFormat {
+///         fraction: NumberFormat {
+///             digits: Some(2),
+///             ..Default::default()
+///         },
+///         ..Default::default()
+///     }),
+///     &42.125.into(),
+/// )
+/// .unwrap();
 /// assert_eq!(s, "42.13".to_string());
 ///
 /// let s = print_value(
@@ -121,6 +1015,7 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///         fraction: NumberFormat {
 ///             digits: Some(1),
 ///             fill_zeros: false,
+///             ..Default::default()
 ///         },
 ///         ..Default::default()
 ///     }),
@@ -134,6 +1029,7 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///         fraction: NumberFormat {
 ///             digits: Some(4),
 ///             fill_zeros: true,
+///             ..Default::default()
 ///         },
 ///         ..Default::default()
 ///     }),
@@ -148,53 +1044,1355 @@ fn print_hex(format: &HexFormat, value: impl UpperHex + LowerHex) -> String {
 ///         fraction: NumberFormat {
 ///             digits: Some(5),
 ///             fill_zeros: true,
+///             ..Default::default()
 ///         },
 ///     }),
 ///     &42.1.into(),
 /// )
 /// .unwrap();
 /// assert_eq!(s, "42.10000".to_string());
-/// ```
-pub fn print_value(format: &Placeholder, value: &Value) -> Result<String> {
-    let result = match format {
-        Placeholder::Display => format!("{}", value),
-        Placeholder::String => format!("{}", get_string(value)?),
-        Placeholder::Number(nf) => print_number(nf, get_number(value)?.trunc() as i128, FillStyle::Prepend),
-        Placeholder::Hex(hf) => print_hex(hf, get_number(value)?.trunc() as i128),
-        Placeholder::Float(ff) => {
-            let base = print_number(&ff.base, get_number(value)?.trunc() as i128, FillStyle::Prepend);
-            let digits: u16 = ff.fraction.digits.unwrap_or_else(|| DEFAULT_FRACT_DIGITS);
-            let fract = get_number(value)?.fract();
-            // let value = (fract * 10f64.powf(digits as f64)).trunc() as i128;
-            let value = (round(fract, digits) * 10f64.powf(digits as f64)).trunc() as i128;
-            let exponent = print_number(&ff.fraction, value, FillStyle::Append);
-            format!("{}.{}", base, exponent)
+///
+/// let float = |format: &str, value: f64| {
+///     let placeholder: Placeholder = format.to_string().try_into().unwrap();
+///     print_value(&placeholder, &value.into()).unwrap()
+/// };
+/// assert_eq!(float("%.2f", 1.05), "1.05");
+/// assert_eq!(float("%.2f", 0.999), "1.00");
+/// assert_eq!(float("%.2f", -0.5), "-0.50");
+/// assert_eq!(float("%.0f", 2.5), "3");
+/// assert_eq!(float("%.20f", 0.1), "0.10000000000000000000");
+/// assert_eq!(float("%6.2f", f64::NAN), "   nan");
+///
+/// // values longer than their width
+/// assert_eq!(float("%2d", 12345.0), "12345");
+/// assert_eq!(float("%!2d", 12345.0), "12");
+/// assert_eq!(float("%!#04d", -12345.0), "####");
+/// assert_eq!(float("%!#06d", -1234.0), "-01234");
+/// assert_eq!(float("%-!4.1f", 123.25), "123.");
+/// let s = print_value(&"%^!6s".to_string().try_into().unwrap(), &"truncated".into()).unwrap();
+/// assert_eq!(s, "trunca");
+/// ```
+pub fn print_value(format: &Placeholder, value: &Value) -> Result<String> {
+    let mut result = String::new();
+    write_value(&mut result, format, value, &PrintOptions::default())?;
+    Ok(result)
+}
+
+/// Like [print_value], but writes into `out` and prints numbers with the locale and rounding of
+/// `options`. Nothing is written if `value` doesn't fit `format`.
+fn write_value(out: &mut impl Write, format: &Placeholder, value: &Value, options: &PrintOptions) -> Result<()> {
+    match (format, options.locale.digits) {
+        (Placeholder::Display(_) | Placeholder::String(_) | Placeholder::Custom(..) | Placeholder::Modified(..), _)
+        | (_, Digits::Latin) => write_latin(out, format, value, options),
+        // C prints Latin digits only
+        (Placeholder::C99(_), _) => write_latin(out, format, value, options),
+        // dates are printed with Latin digits
+        #[cfg(feature = "chrono")]
+        (Placeholder::DateTime(_), _) => write_latin(out, format, value, options),
+        (_, digits) => write_latin(
+            &mut ShapeDigits {
+                out,
+                zero: digits.zero(),
+            },
+            format,
+            value,
+            options,
+        ),
+    }
+}
+
+/// The format of the integer part of numeric placeholders.
+fn base_format(format: &Placeholder) -> Option<&NumberFormat> {
+    match format {
+        Placeholder::Number(nf) | Placeholder::Hex(HexFormat { nf, .. }) => Some(nf),
+        Placeholder::Float(ff) | Placeholder::Size(SizeFormat { ff, .. }) | Placeholder::Percent(ff) => Some(&ff.base),
+        Placeholder::Display(_) | Placeholder::String(_) | Placeholder::Custom(..) | Placeholder::Modified(..) => None,
+        Placeholder::Duration(_) | Placeholder::C99(_) => None,
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(_) => None,
+    }
+}
+
+/// Like [write_value], but always with Latin digits.
+fn write_latin(out: &mut impl Write, format: &Placeholder, value: &Value, options: &PrintOptions) -> Result<()> {
+    if let (Some(nf), &Value::Numeric(number)) = (base_format(format), value) {
+        if !number.is_finite() {
+            // nothing to round or group, and never zero filled
+            let fill = nf.digits.map(|_| nf.fill_char.unwrap_or(' '));
+            let text = options.non_finite.text(number);
+            return written(write!(
+                out,
+                "{}",
+                pad(text, nf.digits, fill, nf.align.into(), nf.overflow)
+            ));
+        }
+    }
+
+    written(match format {
+        Placeholder::Display(sf) if sf.verbose => write!(out, "{}", print_string(sf, format!("{value:#}"), options)),
+        Placeholder::Display(sf) => write!(out, "{}", print_string(sf, value, options)),
+        Placeholder::String(sf) => write!(out, "{}", print_string(sf, get_string(value)?, options)),
+        Placeholder::Number(nf) => {
+            if let Some(decimal) = exact_decimal(value) {
+                // fractions are truncated like for any other number
+                let grouped = Grouped {
+                    digits: decimal.integer,
+                    separator: separator(nf, options),
+                };
+                return written(write!(
+                    out,
+                    "{}",
+                    print_number(nf, decimal.negative, grouped, nf.align.into(), options)
+                ));
+            }
+            let number = get_integer(value)?;
+            let digits = IntegerDigits::new(number.unsigned_abs());
+            let grouped = Grouped {
+                digits: digits.as_str(),
+                separator: separator(nf, options),
+            };
+            write!(
+                out,
+                "{}",
+                print_number(nf, number < 0, grouped, nf.align.into(), options)
+            )
+        }
+        Placeholder::Hex(hf) => write!(out, "{}", print_hex(hf, get_integer(value)?, options)),
+        Placeholder::Duration(df) => write!(
+            out,
+            "{}",
+            print_string(&df.base, print_duration(df, value, options)?, options)
+        ),
+        Placeholder::Size(sf) => {
+            let nf = &sf.ff.base;
+            let size = print_size(sf, value, options)?;
+            write!(out, "{}", pad(size, nf.digits, nf.fill(), nf.align.into(), nf.overflow))
         }
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(dt) => write!(out, "{}", print_string(&dt.base, print_datetime(dt, value)?, options)),
+        Placeholder::C99(cf) => write!(out, "{}", cf.print(value)?),
+        Placeholder::Custom(c, spec) => match options.conversions.get(c) {
+            Some(handler) => write!(out, "{}", handler.handle(value, spec)?),
+            None => return Err(SprintfError::UnregisteredConversion(*c)),
+        },
+        Placeholder::Modified(modifiers, inner) => {
+            // a trait object ends the recursion of the generic writers
+            let mut out: &mut dyn Write = out;
+            return write_value(
+                &mut out,
+                inner,
+                &apply_filters(modifiers, inner, value, options)?,
+                options,
+            );
+        }
+        Placeholder::Float(ff) | Placeholder::Percent(ff) => {
+            let (printed, scaled);
+            let mut number = match (exact_decimal(value), value) {
+                (Some(decimal), _) => decimal,
+                (None, Value::Integer(number)) => {
+                    printed = number.unsigned_abs().to_string();
+                    Decimal::split(*number < 0, &printed)
+                }
+                (None, _) => {
+                    let number = get_number(value)?;
+                    // the shortest representation that reads back as `number`, never in exponent
+                    // notation
+                    printed = number.abs().to_string();
+                    Decimal::split(number < 0.0, &printed)
+                }
+            };
+            let suffix = match format {
+                Placeholder::Percent(_) => {
+                    scaled = number.percent();
+                    number = Decimal::split(number.negative, &scaled);
+                    "%"
+                }
+                _ => "",
+            };
+            // without a precision all digits are kept, so the output reads back as the same number
+            let precision = ff.fraction.digits.map_or(number.fraction.len(), usize::from);
+            let (integer, fraction) = round(&number, precision, options.rounding);
+            let negative = number.negative;
+            let base = Grouped {
+                digits: &integer,
+                separator: separator(&ff.base, options),
+            };
+            // like in C, a precision of 0 prints no decimal point
+            let fraction = Fraction {
+                point: options.locale.decimal_point,
+                digits: &fraction,
+            };
+            match ff.base.align {
+                // the width of right aligned floats only covers the integer part
+                Alignment::Right => write!(
+                    out,
+                    "{}{}{}",
+                    print_number(&ff.base, negative, base, FillStyle::Prepend, options),
+                    fraction,
+                    suffix
+                ),
+                align => write!(
+                    out,
+                    "{}",
+                    print_number(
+                        &ff.base,
+                        negative,
+                        format_args!("{}{}{}", base, fraction, suffix),
+                        align.into(),
+                        options
+                    )
+                ),
+            }
+        }
+    })
+}
+
+pub(crate) fn print_tag(tag: &TemplateTag, parsed: &ParsedFormat) -> String {
+    let label = match &tag.label {
+        TagLabel::Fingerprint => format!("{:08x}", parsed.fingerprint() >> 32),
+        TagLabel::Name(name) => name.clone(),
     };
+    tag.format.replace("{}", &label)
+}
 
-    Ok(result)
+/// Prints `parsed` with all variables resolved by `resolver`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("req.count", Value::Numeric(42.0));
+/// values.insert("req.total_ms", Value::Numeric(1234.0));
+/// let values: MapResolver = values.into();
+///
+/// let parsed = parse_format_string(r#""%d requests in %.2fs", req.count, req.total_ms / 1000"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "42 requests in 1.23s");
+///
+/// let empty: MapResolver = HashMap::new().into();
+/// assert_eq!(sprintf(&parse_format_string(r#""""#).unwrap(), &empty).unwrap(), "");
+/// assert_eq!(sprintf(&parse_format_string(r#""100%%""#).unwrap(), &empty).unwrap(), "100%");
+/// assert!(sprintf(&parsed, &empty).is_err());
+/// ```
+pub fn sprintf(parsed: &ParsedFormat, resolver: &impl Resolver) -> Result<String> {
+    sprintf_with_options(parsed, resolver, &PrintOptions::default())
+}
+
+/// Like [sprintf], but customized by `options`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{IssueHandler, PrintOptions, TagLabel, TagPosition, TemplateTag},
+///     parser::parse_format_string,
+///     printer::sprintf_with_options,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""Hello %s", user"#).unwrap();
+///
+/// let options = PrintOptions {
+///     tag: Some(TemplateTag::new(TagLabel::Name("greeting".into()), TagPosition::Append)),
+///     ..Default::default()
+/// };
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "Hello foo [greeting]");
+///
+/// let options = PrintOptions {
+///     tag: Some(TemplateTag {
+///         format: "{}| ".into(),
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, format!("{:08x}| Hello foo", parsed.fingerprint() >> 32));
+///
+/// let options = PrintOptions {
+///     redact: vec!["user".into()],
+///     ..Default::default()
+/// };
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "Hello ***");
+///
+/// let options = PrintOptions {
+///     on_issue: Some(IssueHandler::new(|issue| format!("<{}?>", issue.variable))),
+///     ..Default::default()
+/// };
+/// let parsed = parse_format_string(r#""Hello %s from %s, %d", user, place, user"#).unwrap();
+/// let s = sprintf_with_options(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "Hello foo from <place?>, <user?>");
+/// ```
+pub fn sprintf_with_options(parsed: &ParsedFormat, resolver: &impl Resolver, options: &PrintOptions) -> Result<String> {
+    print_to_string(parsed.size_hint(), |out| {
+        write_output(out, parsed, resolver, options, None)
+    })
+}
+
+/// Like [sprintf], but writes into `out`, e.g. an existing buffer or a [fmt::Formatter], instead of
+/// returning a new [String].
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_to,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""Hello %-5s|", user"#).unwrap();
+///
+/// let mut out = String::from("> ");
+/// sprintf_to(&mut out, &parsed, &values).unwrap();
+/// assert_eq!(out, "> Hello foo  |");
+/// ```
+pub fn sprintf_to(out: &mut impl Write, parsed: &ParsedFormat, resolver: &impl Resolver) -> Result<()> {
+    write_output(out, parsed, resolver, &PrintOptions::default(), None)
+}
+
+/// Like [sprintf], but prints into the caller owned `out`, which is cleared first. Reusing the same
+/// buffer in a hot loop saves the allocation of a new [String] per call. On error, `out` keeps the
+/// output printed so far.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_into,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("n", Value::Numeric(0.0));
+/// let mut values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""line %d", n"#).unwrap();
+///
+/// let mut out = String::with_capacity(64);
+/// sprintf_into(&parsed, &values, &mut out).unwrap();
+/// assert_eq!(out, "line 0");
+///
+/// let mut next = HashMap::new();
+/// next.insert("n", Value::Numeric(1.0));
+/// values = next.into();
+/// sprintf_into(&parsed, &values, &mut out).unwrap();
+/// assert_eq!(out, "line 1");
+/// assert_eq!(out.capacity(), 64);
+/// ```
+pub fn sprintf_into(parsed: &ParsedFormat, resolver: &impl Resolver, out: &mut String) -> Result<()> {
+    out.clear();
+    write_output(out, parsed, resolver, &PrintOptions::default(), None)
+}
+
+/// Prints a template only when it is displayed, e.g. as the message of a `log` or `tracing` record
+/// which may be filtered out. As displaying can't fail, errors are displayed as `<error: ...>`
+/// instead. The output is padded and aligned like a string, e.g. by `{:>20}`.
+/// ```
+/// use std::cell::Cell;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::LazyFormat,
+///     value::{Resolver, Value},
+/// };
+///
+/// struct Counted {
+///     user: Value,
+///     resolved: Cell<usize>,
+/// }
+///
+/// impl Resolver for Counted {
+///     fn resolve(&self, name: &str) -> Option<&Value> {
+///         self.resolved.set(self.resolved.get() + 1);
+///         (name == "user").then_some(&self.user)
+///     }
+/// }
+///
+/// let values = Counted {
+///     user: Value::String("foo".into()),
+///     resolved: Cell::new(0),
+/// };
+/// let parsed = parse_format_string(r#""Hello %s", user"#).unwrap();
+/// let message = LazyFormat::new(&parsed, &values);
+/// assert_eq!(values.resolved.get(), 0);
+/// assert_eq!(format!("[{:>10}]", message), "[ Hello foo]");
+/// assert_eq!(values.resolved.get(), 1);
+///
+/// let parsed = parse_format_string(r#""Hello %s", name"#).unwrap();
+/// let message = LazyFormat::new(&parsed, &values);
+/// assert_eq!(message.to_string(), r#"<error: placeholder #1 (%s) for variable `name`: Unable to resolve variable "name">"#);
+/// ```
+pub struct LazyFormat<'a, R> {
+    parsed: &'a ParsedFormat<'a>,
+    resolver: &'a R,
+}
+
+impl<'a, R: Resolver> LazyFormat<'a, R> {
+    pub fn new(parsed: &'a ParsedFormat<'a>, resolver: &'a R) -> Self {
+        Self { parsed, resolver }
+    }
+}
+
+impl<R: Resolver> Display for LazyFormat<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match sprintf(self.parsed, self.resolver) {
+            Ok(printed) => f.pad(&printed),
+            Err(error) => write!(f, "<error: {}>", error),
+        }
+    }
+}
+
+/// Passes everything written to an [io::Write] and counts the written bytes.
+struct IoWriter<W> {
+    inner: W,
+    written: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.inner.write_all(s.as_bytes()) {
+            Ok(()) => {
+                self.written += s.len();
+                Ok(())
+            }
+            Err(error) => {
+                self.error = Some(error);
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Like [sprintf], but writes into `out`, e.g. a socket or a file, without building the whole output
+/// in memory first. Returns the number of bytes written.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_io,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""Hello %s\n", user"#).unwrap();
+///
+/// let mut out = Vec::new();
+/// assert_eq!(sprintf_io(&mut out, &parsed, &values).unwrap(), 10);
+/// assert_eq!(out, b"Hello foo\n");
+/// ```
+///
+/// Values are written straight from the resolver into `out`, large ones like stack traces are
+/// never copied:
+/// ```
+/// use std::{
+///     alloc::{GlobalAlloc, Layout, System},
+///     collections::HashMap,
+///     sync::atomic::{AtomicUsize, Ordering},
+/// };
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_io,
+///     value::{MapResolver, Value},
+/// };
+///
+/// struct Counting;
+/// static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+///
+/// unsafe impl GlobalAlloc for Counting {
+///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+///         ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+///         System.alloc(layout)
+///     }
+///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+///         System.dealloc(ptr, layout)
+///     }
+/// }
+///
+/// #[global_allocator]
+/// static GLOBAL: Counting = Counting;
+///
+/// fn main() {
+///     let mut values = HashMap::new();
+///     values.insert("trace", Value::String("x".repeat(1 << 20)));
+///     let values: MapResolver = values.into();
+///     let parsed = parse_format_string(r#""trace: %s", trace"#).unwrap();
+///     let mut out = std::io::sink();
+///
+///     let before = ALLOCATED.load(Ordering::Relaxed);
+///     assert_eq!(sprintf_io(&mut out, &parsed, &values).unwrap(), 7 + (1 << 20));
+///     assert!(ALLOCATED.load(Ordering::Relaxed) - before < 1024);
+/// }
+/// ```
+pub fn sprintf_io(out: &mut impl io::Write, parsed: &ParsedFormat, resolver: &impl Resolver) -> Result<usize> {
+    let mut writer = IoWriter {
+        inner: out,
+        written: 0,
+        error: None,
+    };
+    match write_output(&mut writer, parsed, resolver, &PrintOptions::default(), None) {
+        Ok(()) => Ok(writer.written),
+        Err(SprintfError::Write) => Err(writer.error.map_or(SprintfError::Write, SprintfError::from)),
+        Err(error) => Err(error),
+    }
+}
+
+fn write_output(
+    out: &mut impl Write,
+    parsed: &ParsedFormat,
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+    on_entry: Option<OnEntry>,
+) -> Result<()> {
+    match &options.max_len {
+        Some(max) if max.scope == TruncateScope::Output => {
+            let mut limited = Limited::new(out, max);
+            write_tagged(&mut limited, parsed, resolver, options, on_entry)?;
+            written(limited.finish())
+        }
+        _ => write_tagged(out, parsed, resolver, options, on_entry),
+    }
+}
+
+/// Writes the entries of `parsed` together with the [PrintOptions::tag].
+fn write_tagged(
+    out: &mut impl Write,
+    parsed: &ParsedFormat,
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+    on_entry: Option<OnEntry>,
+) -> Result<()> {
+    let tag = options.tag.as_ref().map(|tag| (tag.position, print_tag(tag, parsed)));
+
+    if let Some((TagPosition::Prepend, tag)) = &tag {
+        written(out.write_str(tag))?;
+    }
+    write_entries(
+        out,
+        &parsed.entries,
+        &parsed.variables,
+        &parsed.arguments,
+        resolver,
+        options,
+        on_entry,
+    )?;
+    if let Some((TagPosition::Append, tag)) = &tag {
+        written(out.write_str(tag))?;
+    }
+
+    Ok(())
+}
+
+/// Like [sprintf], but aborts with [SprintfError::Aborted] once `budget` is exceeded, so a runaway
+/// render can't stall the calling thread.
+/// ```
+/// use std::{collections::HashMap, time::Instant};
+///
+/// use sprintf::{
+///     error::SprintfError,
+///     options::{Budget, CancelToken},
+///     parser::parse_format_string,
+///     printer::sprintf_with_budget,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""Hello %s", user"#).unwrap();
+///
+/// let cancel = CancelToken::new();
+/// let budget = Budget {
+///     cancel: Some(cancel.clone()),
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_budget(&parsed, &values, &budget).unwrap(), "Hello foo");
+///
+/// cancel.cancel();
+/// let err = sprintf_with_budget(&parsed, &values, &budget).unwrap_err();
+/// assert!(matches!(err, SprintfError::Aborted { reason: "cancelled", .. }));
+///
+/// let budget = Budget {
+///     deadline: Some(Instant::now()),
+///     ..Default::default()
+/// };
+/// assert!(sprintf_with_budget(&parsed, &values, &budget).is_err());
+///
+/// // templates without variables are aborted, too
+/// let parsed = parse_format_string(r#""Hello""#).unwrap();
+/// assert!(sprintf_with_budget(&parsed, &values, &budget).is_err());
+/// ```
+pub fn sprintf_with_budget(parsed: &ParsedFormat, resolver: &impl Resolver, budget: &Budget) -> Result<String> {
+    let options = PrintOptions {
+        budget: Some(budget.clone()),
+        ..Default::default()
+    };
+    sprintf_with_options(parsed, resolver, &options)
 }
 
-pub fn sprintf(parsed: &ParsedFormatString, resolver: &impl Resolver) -> Result<String> {
-    let mut result: String = String::new();
+/// Prints `parsed` with the placeholders bound to `values` in order, ignoring the variable names.
+/// ```
+/// use sprintf::{parser::parse_format_string, printer::vsprintf, value::Value};
+///
+/// let parsed = parse_format_string(r#""%s took %.2fs", user, duration"#).unwrap();
+/// let s = vsprintf(&parsed, &[Value::String("foo".into()), Value::Numeric(1.2345)]).unwrap();
+/// assert_eq!(s, "foo took 1.23s");
+///
+/// assert!(vsprintf(&parsed, &[Value::String("foo".into())]).is_err());
+/// ```
+pub fn vsprintf(parsed: &ParsedFormat, values: &[Value]) -> Result<String> {
+    let placeholders: Vec<_> = parsed
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            Entry::Placeholder(placeholder) => Some(placeholder),
+            Entry::Text(_) | Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => None,
+        })
+        .collect();
+    if placeholders.len() != values.len() {
+        return Err(SprintfError::ArityMismatch {
+            variables: values.len(),
+            placeholders: placeholders.len(),
+        });
+    }
 
-    let mut vars = parsed.variables.iter();
+    let options = PrintOptions::default();
+    let mut values = values.iter();
+    // every variable has a value
+    let skipped = skipped_sections(&parsed.entries, |variable| {
+        parsed.variables.iter().any(|known| known == variable)
+    });
+    print_to_string(parsed.size_hint(), |out| {
+        for (index, entry) in parsed.entries.iter().enumerate() {
+            let skip = skipped.iter().any(|range| range.contains(&index));
+            match entry {
+                // the placeholders of a skipped section still take their values
+                Entry::Placeholder(_) if skip => {
+                    values.next();
+                }
+                _ if skip => {}
+                Entry::Section(_) | Entry::SectionEnd(_) => {}
+                Entry::Text(text) => written(out.write_str(text))?,
+                Entry::Style(styles) => written(out.write_str(&style_sequence(styles, &options)))?,
+                Entry::Placeholder(format) => {
+                    // the arity check above made sure there is a value for every placeholder
+                    if let Some(value) = values.next() {
+                        write_value(out, format, value, &options)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Named values given inline, looked up in order.
+struct Pairs<'a>(&'a [(&'a str, Value)]);
+
+impl Resolver for Pairs<'_> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.iter().find(|(n, _)| *n == name).map(|(_, value)| value)
+    }
+}
+
+/// Parses the format string statement `s` and prints it with the variables taken from `args` in
+/// one call, e.g. for scripts and tests.
+/// ```
+/// use sprintf::{printer::sprintf_with, value::Value};
+///
+/// let s = sprintf_with(
+///     r#""%s took %.2fs", user, duration / 1000"#,
+///     &[("user", "foo".into()), ("duration", Value::Numeric(1234.0))],
+/// )
+/// .unwrap();
+/// assert_eq!(s, "foo took 1.23s");
+///
+/// assert!(sprintf_with(r#""%s", user"#, &[]).is_err());
+/// ```
+pub fn sprintf_with(s: &str, args: &[(&str, Value)]) -> Result<String> {
+    sprintf(&parse_format_string(s)?, &Pairs(args))
+}
+
+/// Variables resolved up front, so several outputs can be printed with a single resolve pass.
+struct Resolved<'a>(Map<Cow<'a, str>, Cow<'a, Value>>);
+
+impl Resolver for Resolved<'_> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.get(name).map(AsRef::as_ref)
+    }
+}
+
+/// Prints `parsed` once for every profile in `profiles`, e.g. a redacted and a full variant for
+/// different sinks. Every variable is resolved only once.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::PrintOptions,
+///     parser::parse_format_string,
+///     printer::sprintf_profiles,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// values.insert("password", Value::String("secret".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s logged in with %s", user, password"#).unwrap();
+///
+/// let redacted = PrintOptions {
+///     redact: vec!["password".into()],
+///     ..Default::default()
+/// };
+/// let printed = sprintf_profiles(&parsed, &values, &[PrintOptions::default(), redacted]).unwrap();
+/// assert_eq!(printed, vec!["foo logged in with secret", "foo logged in with ***"]);
+///
+/// // sections are printed if their variable resolves, no matter if it is printed
+/// let parsed = parse_format_string(r#""%s logged in%{?password} with a password%{/password}", user"#).unwrap();
+/// let printed = sprintf_profiles(&parsed, &values, &[PrintOptions::default()]).unwrap();
+/// assert_eq!(printed, vec!["foo logged in with a password"]);
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// values.insert("ports.0", 80.into());
+/// values.insert("ports.1", 443.into());
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s listens on %{join{, }}d", user, ports"#).unwrap();
+/// let printed = sprintf_profiles(&parsed, &values, &[PrintOptions::default()]).unwrap();
+/// assert_eq!(printed, vec!["foo listens on 80, 443"]);
+/// ```
+pub fn sprintf_profiles(
+    parsed: &ParsedFormat,
+    resolver: &impl Resolver,
+    profiles: &[PrintOptions],
+) -> Result<Vec<String>> {
+    let mut resolved = Map::new();
+    for (variable, argument) in parsed.variables.iter().zip(&parsed.arguments) {
+        match resolve_argument(variable, argument, resolver) {
+            Ok(value) => {
+                resolved.insert(Cow::Borrowed(variable.as_str()), value);
+            }
+            // left unresolved, every profile handles it on its own, e.g. with a default
+            Err(SprintfError::UnresolvedVariable(_)) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    // sections are printed only if their variable resolves
     for entry in &parsed.entries {
+        if let Entry::Section(variable) = entry {
+            if let Some(value) = resolver.resolve(variable) {
+                resolved.insert(Cow::Borrowed(variable.as_str()), Cow::Borrowed(value));
+            }
+        }
+    }
+    // joined placeholders print the elements `variable.0`, `variable.1`, ...
+    let placeholders = parsed.entries.iter().filter_map(|entry| match entry {
+        Entry::Placeholder(format) => Some(format),
+        _ => None,
+    });
+    for (format, variable) in placeholders.zip(&parsed.variables) {
+        if format.join().is_none() {
+            continue;
+        }
+        for index in 0.. {
+            let element = format!("{}.{}", variable, index);
+            match resolver.resolve(&element) {
+                Some(value) => resolved.insert(Cow::Owned(element), Cow::Borrowed(value)),
+                None => break,
+            };
+        }
+    }
+    let resolved = Resolved(resolved);
+
+    profiles
+        .iter()
+        .map(|options| sprintf_with_options(parsed, &resolved, options))
+        .collect()
+}
+
+/// Prints `entries` as returned by [crate::parser::parse_format] with the placeholders bound to
+/// `variables` in order.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format,
+///     printer::sprintf_entries,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// values.insert("duration", Value::Numeric(1.2345));
+/// let values: MapResolver = values.into();
+///
+/// let entries = parse_format("%s took %.2fs").unwrap();
+/// let s = sprintf_entries(&entries, &["user", "duration"], &values).unwrap();
+/// assert_eq!(s, "foo took 1.23s");
+///
+/// assert!(sprintf_entries(&entries, &["user"], &values).is_err());
+/// ```
+pub fn sprintf_entries(entries: &[Entry], variables: &[impl AsRef<str>], resolver: &impl Resolver) -> Result<String> {
+    check_arity(entries, variables)?;
+    let arguments: Vec<_> = variables
+        .iter()
+        .map(|v| Expression::from_argument(v.as_ref()))
+        .collect();
+    print_to_string(size_hint(entries), |out| {
+        write_entries(
+            out,
+            entries,
+            variables,
+            &arguments,
+            resolver,
+            &PrintOptions::default(),
+            None,
+        )
+    })
+}
+
+/// Like [sprintf], but additionally returns every variable with its formatted value, e.g. for
+/// structured logging.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_with_fields,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// values.insert("duration", Value::Numeric(1.2345));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s took %.2fs", user, duration"#).unwrap();
+///
+/// let (s, fields) = sprintf_with_fields(&parsed, &values).unwrap();
+/// assert_eq!(s, "foo took 1.23s");
+/// assert_eq!(fields.len(), 2);
+/// assert_eq!(fields["user"], "foo");
+/// assert_eq!(fields["duration"], "1.23");
+/// // ordered by name
+/// assert_eq!(fields.keys().collect::<Vec<_>>(), vec!["duration", "user"]);
+/// ```
+pub fn sprintf_with_fields(
+    parsed: &ParsedFormat,
+    resolver: &impl Resolver,
+) -> Result<(String, BTreeMap<String, String>)> {
+    let mut fields = BTreeMap::new();
+    let options = PrintOptions::default();
+    let result = print_to_string(parsed.size_hint(), |out| {
+        write_entries(
+            out,
+            &parsed.entries,
+            &parsed.variables,
+            &parsed.arguments,
+            resolver,
+            &options,
+            Some(&mut |_, variable, printed| {
+                if let Some(variable) = variable {
+                    fields.insert(variable.to_string(), printed.to_string());
+                }
+            }),
+        )
+    })?;
+
+    Ok((result, fields))
+}
+
+/// Like [sprintf_with_options], but additionally returns a [SourceMap] from the printed output back
+/// to the template entries and variables, e.g. to highlight which part of a line came from which
+/// field.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::PrintOptions,
+///     parser::parse_format_string,
+///     printer::sprintf_with_source_map,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("foo".into()));
+/// values.insert("password", Value::String("secret".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s logged in with %s", user, password"#).unwrap();
+///
+/// let options = PrintOptions {
+///     redact: vec!["password".into()],
+///     ..Default::default()
+/// };
+/// let (s, map) = sprintf_with_source_map(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "foo logged in with ***");
+///
+/// let password = map.variable("password").next().unwrap();
+/// assert_eq!(&s[password.output.clone()], "***");
+/// assert_eq!(password.entry, 2);
+/// assert_eq!(password.template, Some(19..21));
+///
+/// let text = map.at(5).unwrap();
+/// assert_eq!(text.variable, None);
+/// assert_eq!(&s[text.output.clone()], " logged in with ");
+/// ```
+/// Segments of a truncated output end where the ellipsis starts, segments which were cut off
+/// entirely are dropped:
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::{MaxLen, PrintOptions, TagLabel, TagPosition, TemplateTag},
+///     parser::parse_format_string,
+///     printer::sprintf_with_source_map,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("jürgen".into()));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s logged in with %s", user, user"#).unwrap();
+///
+/// let options = PrintOptions {
+///     max_len: Some(MaxLen::new(16)),
+///     tag: Some(TemplateTag::new(TagLabel::Name("login".into()), TagPosition::Prepend)),
+///     ..Default::default()
+/// };
+/// let (s, map) = sprintf_with_source_map(&parsed, &values, &options).unwrap();
+/// assert_eq!(s, "[login] jürgen …");
+/// assert_eq!(map.segments.len(), 2);
+/// assert_eq!(&s[map.segments[0].output.clone()], "jürgen");
+/// assert_eq!(&s[map.segments[1].output.clone()], " ");
+/// assert!(map.segments.iter().all(|segment| segment.output.end <= s.len()));
+/// ```
+pub fn sprintf_with_source_map(
+    parsed: &ParsedFormat,
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+) -> Result<(String, SourceMap)> {
+    let mut map = SourceMap::default();
+    // a prepended tag is the only output before the entries
+    let mut position = match &options.tag {
+        Some(tag) if tag.position == TagPosition::Prepend => print_tag(tag, parsed).len(),
+        _ => 0,
+    };
+    let mut chars = 0;
+    let result = print_to_string(parsed.size_hint(), |out| {
+        write_output(
+            out,
+            parsed,
+            resolver,
+            options,
+            Some(&mut |entry, variable, printed| {
+                map.segments.push(Segment {
+                    output: position..position + printed.len(),
+                    entry,
+                    variable: variable.map(String::from),
+                    template: parsed.spans.get(entry).cloned(),
+                });
+                position += printed.len();
+                chars += printed.chars().count();
+            }),
+        )
+    })?;
+
+    // the segments are counted from the output before truncation
+    if let Some(max) = options
+        .max_len
+        .as_ref()
+        .filter(|max| max.scope == TruncateScope::Output)
+    {
+        let tags = options
+            .tag
+            .as_ref()
+            .map_or(0, |tag| print_tag(tag, parsed).chars().count());
+        map.truncate(unlimited_len(max, &result, tags + chars));
+    }
+
+    Ok((result, map))
+}
+
+/// Like [sprintf], but additionally returns the output byte range printed by every placeholder,
+/// e.g. to highlight the dynamic parts of a line or to account for their lengths.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf_traced,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("user", Value::String("jürgen".into()));
+/// values.insert("took", Value::Numeric(1.5));
+/// let values: MapResolver = values.into();
+/// let parsed = parse_format_string(r#""%s took %.2fs", user, took"#).unwrap();
+///
+/// let (s, spans) = sprintf_traced(&parsed, &values).unwrap();
+/// assert_eq!(s, "jürgen took 1.50s");
+/// assert_eq!(spans.len(), 2);
+/// assert_eq!(spans[0].range, 0..7);
+/// assert_eq!(spans[1].placeholder_index, 1);
+/// assert_eq!(spans[1].variable, "took");
+/// assert_eq!(&s[spans[1].range.clone()], "1.50");
+/// ```
+pub fn sprintf_traced(parsed: &ParsedFormat, resolver: &impl Resolver) -> Result<(String, Vec<OutputSpan>)> {
+    let (result, map) = sprintf_with_source_map(parsed, resolver, &PrintOptions::default())?;
+    let spans = map
+        .segments
+        .into_iter()
+        .filter_map(|segment| Some((segment.variable?, segment.output)))
+        .enumerate()
+        .map(|(placeholder_index, (variable, range))| OutputSpan {
+            placeholder_index,
+            variable,
+            range,
+        })
+        .collect();
+
+    Ok((result, spans))
+}
+
+/// Like [resolve_argument], but falls back to the [Placeholder::default_value] of `format` if the
+/// variable can't be resolved.
+pub(crate) fn resolve_placeholder<'r>(
+    format: &Placeholder,
+    variable: &str,
+    argument: &Expression,
+    resolver: &'r impl Resolver,
+) -> Result<Cow<'r, Value>> {
+    or_default(format, resolve_argument(variable, argument, resolver))
+}
+
+/// The elements `variable.0`, `variable.1`, ... of a sequence printed by a
+/// [Placeholder::join], or the default of `format` if there are none.
+pub(crate) fn resolve_elements<'r>(
+    format: &Placeholder,
+    variable: &str,
+    resolver: &'r impl Resolver,
+) -> Result<Vec<Cow<'r, Value>>> {
+    let elements: Vec<_> = (0..)
+        .map_while(|index| resolver.resolve(&format!("{}.{}", variable, index)))
+        .map(Cow::Borrowed)
+        .collect();
+    match elements.is_empty() {
+        true => or_default(format, Err(SprintfError::UnresolvedVariable(variable.into()))).map(|value| vec![value]),
+        false => Ok(elements),
+    }
+}
+
+/// Writes every element of the sequence `variable` with `format`, joined by `separator`.
+pub(crate) fn write_joined(
+    out: &mut impl Write,
+    format: &Placeholder,
+    separator: &str,
+    variable: &str,
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+) -> Result<()> {
+    let elements = match resolve_elements(format, variable, resolver) {
+        Ok(elements) => elements,
+        Err(error) => return write_field(out, format, variable, Err(error), options),
+    };
+    for (index, element) in elements.into_iter().enumerate() {
+        if index > 0 {
+            written(out.write_str(separator))?;
+        }
+        write_field(out, format, variable, Ok(element), options)?;
+    }
+    Ok(())
+}
+
+/// Resolves the variable of a single placeholder and writes it, see [write_field] and [write_joined].
+fn write_resolved(
+    out: &mut impl Write,
+    format: &Placeholder,
+    variable: &str,
+    argument: &Expression,
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+) -> Result<()> {
+    match format.join() {
+        Some(separator) => write_joined(out, format, separator, variable, resolver, options),
+        None => write_field(
+            out,
+            format,
+            variable,
+            resolve_placeholder(format, variable, argument, resolver),
+            options,
+        ),
+    }
+}
+
+/// `value`, or the default of `format` like `%{:-unknown}s` if the variable is unresolved.
+pub(crate) fn or_default<'r>(format: &Placeholder, value: Result<Cow<'r, Value>>) -> Result<Cow<'r, Value>> {
+    match (value, format.default_value()) {
+        (Err(SprintfError::UnresolvedVariable(_)), Some(default)) => Ok(Cow::Owned(text_value(format, default))),
+        (value, _) => value,
+    }
+}
+
+/// `text` as value for `format`, e.g. a default or a filtered value. Numeric placeholders take
+/// numbers, which are kept exact if they don't fit an `i128`.
+fn text_value(format: &Placeholder, text: &str) -> Value {
+    if !matches!(
+        format.conversion(),
+        Conversion::Number | Conversion::Float | Conversion::Hex | Conversion::UpperHex
+    ) {
+        return Value::String(text.into());
+    }
+    match (text.parse(), Decimal::parse(text), text.parse()) {
+        (Ok(integer), _, _) => Value::Integer(integer),
+        (_, Some(_), _) => Value::Decimal(text.into()),
+        (_, _, Ok(number)) => Value::Numeric(number),
+        _ => Value::String(text.into()),
+    }
+}
+
+/// Resolves `variable` as is, or evaluates it if it is an expression not known to the resolver.
+pub(crate) fn resolve_argument<'r>(
+    variable: &str,
+    argument: &Expression,
+    resolver: &'r impl Resolver,
+) -> Result<Cow<'r, Value>> {
+    match (resolver.resolve(variable), argument) {
+        (Some(value), _) => Ok(Cow::Borrowed(value)),
+        (None, Expression::Variable(_)) => Err(SprintfError::UnresolvedVariable(variable.into())),
+        (None, expression) => expression.evaluate(resolver),
+    }
+}
+
+/// Prints into a new [String]. An aborted print keeps the output printed so far.
+fn print_to_string(capacity: usize, print: impl FnOnce(&mut String) -> Result<()>) -> Result<String> {
+    let mut result = String::with_capacity(capacity);
+    match print(&mut result) {
+        Ok(()) => Ok(result),
+        Err(SprintfError::Aborted { reason, .. }) => Err(SprintfError::Aborted {
+            reason,
+            partial: result,
+        }),
+        Err(error) => Err(error),
+    }
+}
+
+/// Fails with [SprintfError::Aborted] once the [PrintOptions::budget] is exceeded. The partial output
+/// is added by the caller holding it, see [print_to_string].
+pub(crate) fn check_budget(options: &PrintOptions) -> Result<()> {
+    match options.budget.as_ref().and_then(Budget::exceeded) {
+        Some(reason) => Err(SprintfError::Aborted {
+            reason,
+            partial: String::new(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The entry index ranges of the sections whose variable isn't `set`, including their start and
+/// end, see [Entry::Section].
+pub(crate) fn skipped_sections(entries: &[Entry], set: impl Fn(&str) -> bool) -> Vec<Range<usize>> {
+    let mut skipped = Vec::new();
+    let mut index = 0;
+    while index < entries.len() {
+        if let Entry::Section(variable) = &entries[index] {
+            if !set(variable) {
+                let mut depth = 0;
+                let end = entries[index..]
+                    .iter()
+                    .position(|entry| {
+                        match entry {
+                            Entry::Section(_) => depth += 1,
+                            Entry::SectionEnd(_) => depth -= 1,
+                            Entry::Text(_) | Entry::Placeholder(_) | Entry::Style(_) => {}
+                        }
+                        depth == 0
+                    })
+                    .map_or(entries.len(), |len| index + len + 1);
+                skipped.push(index..end);
+                index = end;
+                continue;
+            }
+        }
+        index += 1;
+    }
+    skipped
+}
+
+/// Receives the index of every printed entry with its variable, if any, and its printed text.
+type OnEntry<'a> = &'a mut dyn FnMut(usize, Option<&str>, &str);
+
+/// Writes a single placeholder into `out`, redacted or substituted by [PrintOptions::on_issue] if
+/// configured.
+pub(crate) fn write_field(
+    out: &mut impl Write,
+    format: &Placeholder,
+    variable: &str,
+    value: Result<Cow<Value>>,
+    options: &PrintOptions,
+) -> Result<()> {
+    if options.escape == Escape::None {
+        return write_limited_field(out, format, variable, value, options);
+    }
+    let mut escaped = Escaped {
+        out,
+        escape: options.escape,
+        quoted: false,
+    };
+    write_limited_field(&mut escaped, format, variable, value, options)?;
+    written(escaped.finish())
+}
+
+/// Like [write_unescaped_field], but truncated by [PrintOptions::max_len] with [TruncateScope::Value].
+fn write_limited_field(
+    out: &mut impl Write,
+    format: &Placeholder,
+    variable: &str,
+    value: Result<Cow<Value>>,
+    options: &PrintOptions,
+) -> Result<()> {
+    match &options.max_len {
+        Some(max) if max.scope == TruncateScope::Value => {
+            let mut limited = Limited::new(out, max);
+            write_unescaped_field(&mut limited, format, variable, value, options)?;
+            written(limited.finish())
+        }
+        _ => write_unescaped_field(out, format, variable, value, options),
+    }
+}
+
+/// Like [write_field], but ignores [PrintOptions::escape].
+fn write_unescaped_field(
+    out: &mut impl Write,
+    format: &Placeholder,
+    variable: &str,
+    value: Result<Cow<Value>>,
+    options: &PrintOptions,
+) -> Result<()> {
+    let value = match (value, &options.on_missing) {
+        (Err(SprintfError::UnresolvedVariable(_)), MissingPolicy::Empty) => return Ok(()),
+        (Err(SprintfError::UnresolvedVariable(_)), MissingPolicy::KeepPlaceholder) => {
+            return written(write!(out, "{}", format))
+        }
+        (Err(SprintfError::UnresolvedVariable(_)), MissingPolicy::Custom(text)) => return written(out.write_str(text)),
+        (value, _) => value,
+    };
+    let printed = value.and_then(|value| {
+        if options.is_redacted(variable) {
+            check_value(format, &value)?;
+            return written(out.write_str(&options.redaction_token));
+        }
+        write_value(out, format, &value, options)
+    });
+    match (printed, &options.on_issue) {
+        (Err(SprintfError::Write), _) => Err(SprintfError::Write),
+        (Err(error), Some(handler)) => written(out.write_str(&handler.handle(&RenderIssue {
+            variable,
+            placeholder: format,
+            error: &error,
+        }))),
+        (printed, _) => printed,
+    }
+}
+
+/// Writes all `entries` into `out` and passes every printed entry to `on_entry`.
+fn write_entries(
+    out: &mut impl Write,
+    entries: &[Entry],
+    variables: &[impl AsRef<str>],
+    arguments: &[Expression],
+    resolver: &impl Resolver,
+    options: &PrintOptions,
+    mut on_entry: Option<OnEntry>,
+) -> Result<()> {
+    options.limits.check(entries)?;
+    if variables.is_empty() {
+        // static fast path, nothing to resolve
+        check_arity(entries, variables)?;
+        let skipped = skipped_sections(entries, |variable| resolver.resolve(variable).is_some());
+        for (index, entry) in entries.iter().enumerate() {
+            check_budget(options)?;
+            let text = match entry {
+                _ if skipped.iter().any(|range| range.contains(&index)) => continue,
+                Entry::Text(text) => Cow::Borrowed(text.as_ref()),
+                Entry::Style(styles) => Cow::Owned(style_sequence(styles, options)),
+                Entry::Placeholder(_) | Entry::Section(_) | Entry::SectionEnd(_) => continue,
+            };
+            written(out.write_str(&text))?;
+            if let Some(on_entry) = on_entry.as_mut() {
+                on_entry(index, None, &text);
+            }
+        }
+        return Ok(());
+    }
+
+    // reused for every placeholder which is needed as a whole
+    let mut field = String::new();
+    let mut vars = variables.iter().map(AsRef::as_ref).zip(arguments);
+    let mut position = 0;
+    let skipped = skipped_sections(entries, |variable| resolver.resolve(variable).is_some());
+    for (index, entry) in entries.iter().enumerate() {
+        check_budget(options)?;
+        let skip = skipped.iter().any(|range| range.contains(&index));
         match entry {
+            // the placeholders of a skipped section still take their variables
+            Entry::Placeholder(_) if skip => {
+                vars.next();
+                position += 1;
+            }
+            _ if skip => {}
+            // sections print nothing themselves
+            Entry::Section(_) | Entry::SectionEnd(_) => {}
             Entry::Text(text) => {
-                result.push_str(text);
+                written(out.write_str(text))?;
+                if let Some(on_entry) = on_entry.as_mut() {
+                    on_entry(index, None, text);
+                }
+            }
+            Entry::Style(styles) => {
+                let sequence = style_sequence(styles, options);
+                written(out.write_str(&sequence))?;
+                if let Some(on_entry) = on_entry.as_mut() {
+                    on_entry(index, None, &sequence);
+                }
             }
             Entry::Placeholder(format) => {
-                let variable_name = vars
-                    .next()
-                    .ok_or_else(|| anyhow!("No variable for placeholder {:?}", format))?;
-                let value = resolver
-                    .resolve(variable_name)
-                    .ok_or_else(|| anyhow!("Unable to resolve variable {:?}", variable_name))?;
-                result.push_str(print_value(format, &value)?.as_str());
+                let (variable_name, argument) = vars.next().ok_or_else(|| SprintfError::ArityMismatch {
+                    variables: variables.len(),
+                    placeholders: entries
+                        .iter()
+                        .filter(|item| matches!(item, Entry::Placeholder(_)))
+                        .count(),
+                })?;
+                position += 1;
+                let in_placeholder = |error: SprintfError| error.in_placeholder(position, format, variable_name);
+                match on_entry.as_mut() {
+                    Some(on_entry) => {
+                        field.clear();
+                        write_resolved(&mut field, format, variable_name, argument, resolver, options)
+                            .map_err(in_placeholder)?;
+                        on_entry(index, Some(variable_name), &field);
+                        written(out.write_str(&field))?;
+                    }
+                    // values are written straight from the resolver into `out`, without any copy
+                    None => write_resolved(out, format, variable_name, argument, resolver, options)
+                        .map_err(in_placeholder)?,
+                }
             }
         }
     }
 
-    Ok(result)
+    Ok(())
 }
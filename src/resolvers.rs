@@ -0,0 +1,198 @@
+//! Resolvers adapting or combining other resolvers, so templates can draw from several sources like the fields
+//! of a request, then globals, then defaults.
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use sprintf::{
+//!     parser::parse_format_string,
+//!     printer::sprintf,
+//!     resolvers::{ChainResolver, FallbackResolver, ScopedResolver},
+//!     value::{MapResolver, Value},
+//! };
+//!
+//! let resolver = |values: &[(&str, Value)]| MapResolver::from(values.iter().cloned().collect::<HashMap<_, _>>());
+//! let request = ScopedResolver::new("request", resolver(&[("status", Value::Numeric(404.0))]));
+//! let globals = resolver(&[("host", "foo".into())]);
+//! let defaults = resolver(&[("host", "localhost".into()), ("port", Value::Numeric(80.0))]);
+//!
+//! let parsed = parse_format_string(r#""%s:%d %d", host, port, request.status"#).unwrap();
+//! let chain = ChainResolver::new().with(&request).with(&globals).with(&defaults);
+//! assert_eq!(sprintf(&parsed, &chain).unwrap(), "foo:80 404");
+//!
+//! let fallback = FallbackResolver::new(request, defaults);
+//! assert_eq!(sprintf(&parsed, &fallback).unwrap(), "localhost:80 404");
+//! ```
+
+use std::cell::OnceCell;
+
+use crate::value::{Resolver, Value};
+
+/// Resolves a variable by the first of several borrowed resolvers which knows it.
+#[derive(Default)]
+pub struct ChainResolver<'a> {
+    resolvers: Vec<&'a dyn Resolver>,
+}
+
+impl<'a> ChainResolver<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks `resolver` for variables the resolvers added before don't know.
+    pub fn with(mut self, resolver: &'a dyn Resolver) -> Self {
+        self.resolvers.push(resolver);
+        self
+    }
+}
+
+impl Resolver for ChainResolver<'_> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.resolvers.iter().find_map(|resolver| resolver.resolve(name))
+    }
+}
+
+/// Resolves a variable by `primary`, or by `fallback` if `primary` doesn't know it. Unlike a
+/// [ChainResolver] it owns both, so it can be nested and returned.
+#[derive(Debug, Clone)]
+pub struct FallbackResolver<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: Resolver, F: Resolver> FallbackResolver<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl<P: Resolver, F: Resolver> Resolver for FallbackResolver<P, F> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.primary.resolve(name).or_else(|| self.fallback.resolve(name))
+    }
+}
+
+/// Computes values on demand, e.g. `now.iso` or `hostname`, instead of lending them like a
+/// [Resolver]. Implemented for closures taking the name of the variable.
+pub trait OwnedResolver {
+    fn resolve_owned(&self, name: &str) -> Option<Value>;
+}
+
+impl<F: Fn(&str) -> Option<Value>> OwnedResolver for F {
+    fn resolve_owned(&self, name: &str) -> Option<Value> {
+        self(name)
+    }
+}
+
+/// Resolves variables by an [OwnedResolver]. Every variable is computed when it is resolved for
+/// the first time, and kept for further uses, so variables a print skips are never computed.
+/// ```
+/// use std::cell::Cell;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     resolvers::ComputedResolver,
+///     value::Value,
+/// };
+///
+/// let computed = Cell::new(0);
+/// let compute = |name: &str| {
+///     computed.set(computed.get() + 1);
+///     match name {
+///         "hostname" => Some(Value::String("foo".into())),
+///         "uptime" => Some(Value::Numeric(90.0)),
+///         "ports.0" => Some(80.into()),
+///         "ports.1" => Some(443.into()),
+///         _ => None,
+///     }
+/// };
+///
+/// let resolver = ComputedResolver::new(&compute);
+/// let parsed = parse_format_string(r#""%s up for %dmin%{?error}, failed%{/error}", hostname, uptime / 60"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo up for 1min");
+/// // `hostname`, `error`, `uptime / 60` and `uptime`
+/// assert_eq!(computed.get(), 4);
+///
+/// let parsed = parse_format_string(r#""%s listens on %{join{, }}d", hostname, ports"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo listens on 80, 443");
+/// // `ports.0`, `ports.1` and `ports.2`
+/// assert_eq!(computed.get(), 7);
+/// ```
+#[derive(Debug)]
+pub struct ComputedResolver<R> {
+    resolver: R,
+    computed: OnceCell<Box<Computed>>,
+}
+
+/// A computed variable, followed by the ones computed after it. Variables are only ever appended,
+/// so their values can be lent for as long as the resolver lives.
+#[derive(Debug)]
+struct Computed {
+    name: String,
+    value: Option<Value>,
+    next: OnceCell<Box<Computed>>,
+}
+
+impl Drop for Computed {
+    fn drop(&mut self) {
+        // one by one instead of recursively, so long lists can't overflow the stack
+        let mut next = self.next.take();
+        while let Some(mut computed) = next {
+            next = computed.next.take();
+        }
+    }
+}
+
+impl<R: OwnedResolver> ComputedResolver<R> {
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver,
+            computed: OnceCell::new(),
+        }
+    }
+}
+
+impl<R: OwnedResolver> Resolver for ComputedResolver<R> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        let mut cell = &self.computed;
+        loop {
+            match cell.get() {
+                Some(computed) if computed.name == name => return computed.value.as_ref(),
+                Some(computed) => cell = &computed.next,
+                None => {
+                    let value = self.resolver.resolve_owned(name);
+                    // the cell is only taken already if computing resolved through this resolver, too
+                    let _ = cell.set(Box::new(Computed {
+                        name: name.to_string(),
+                        value,
+                        next: OnceCell::new(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+/// Resolves variables below `prefix` by `inner` without the prefix, e.g. `request.status` as
+/// `status`. Other variables aren't resolved.
+#[derive(Debug, Clone)]
+pub struct ScopedResolver<R> {
+    prefix: String,
+    inner: R,
+}
+
+impl<R: Resolver> ScopedResolver<R> {
+    pub fn new(prefix: impl Into<String>, inner: R) -> Self {
+        Self {
+            prefix: prefix.into(),
+            inner,
+        }
+    }
+}
+
+impl<R: Resolver> Resolver for ScopedResolver<R> {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        let name = name.strip_prefix(self.prefix.as_str())?.strip_prefix('.')?;
+        self.inner.resolve(name)
+    }
+}
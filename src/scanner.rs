@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{Result, SprintfError},
+    expression::Expression,
+    parser::{Entry, ParsedFormat, Placeholder},
+    types::FloatFormat,
+    value::Value,
+};
+
+/// Whether `c` can be part of a printed number of `placeholder`, for placeholders without text
+/// behind them.
+fn is_number_char(placeholder: &Placeholder, c: char) -> bool {
+    match placeholder {
+        Placeholder::Number(_) => c.is_ascii_digit() || matches!(c, '-' | '+' | ','),
+        Placeholder::Float(_) => c.is_ascii_digit() || matches!(c, '-' | '+' | ',' | '.'),
+        Placeholder::Hex(_) => c.is_ascii_hexdigit() || c == '-',
+        _ => !c.is_whitespace(),
+    }
+}
+
+/// The fill character of `placeholder` other than `0`, which is part of the number.
+fn fill_char(placeholder: &Placeholder) -> Option<char> {
+    match placeholder {
+        Placeholder::Number(nf) | Placeholder::Float(FloatFormat { base: nf, .. }) => nf.fill_char,
+        Placeholder::Hex(hf) => hf.nf.fill_char,
+        Placeholder::Display(sf) | Placeholder::String(sf) => sf.fill_char,
+        _ => None,
+    }
+    .filter(|c| *c != '0')
+}
+
+/// The value of `placeholder` printed as `text`.
+fn scan_value(placeholder: &Placeholder, text: &str, offset: usize) -> Result<Value> {
+    let fill = fill_char(placeholder);
+    let trimmed = text.trim_matches(|c: char| c == ' ' || Some(c) == fill);
+    let mismatch = |expected: &str| SprintfError::Mismatch {
+        offset,
+        reason: format!("expected {} instead of '{}'", expected, text),
+    };
+    match placeholder {
+        Placeholder::Number(_) => trimmed
+            .replace(',', "")
+            .parse::<i128>()
+            .map(Value::Integer)
+            .map_err(|_| mismatch("an integer")),
+        Placeholder::Float(_) => trimmed
+            .replace(',', "")
+            .parse()
+            .map(Value::Numeric)
+            .map_err(|_| mismatch("a number")),
+        Placeholder::Hex(_) => {
+            let (negative, digits) = match trimmed.strip_prefix('-') {
+                Some(digits) => (true, digits),
+                None => (false, trimmed),
+            };
+            let number = i128::from_str_radix(digits, 16).map_err(|_| mismatch("a hex number"))?;
+            Ok(Value::Integer(if negative { -number } else { number }))
+        }
+        Placeholder::Display(_) => Ok(match trimmed.parse::<f64>() {
+            Ok(number) if number.is_finite() => Value::Numeric(number),
+            _ => Value::String(trimmed.into()),
+        }),
+        Placeholder::String(_) => Ok(Value::String(trimmed.into())),
+        _ => Ok(Value::String(text.into())),
+    }
+}
+
+/// Reads the values of the variables back from `input` as printed by `parsed`, e.g. to parse log
+/// lines without hand-written regular expressions.
+///
+/// The literal text of the template anchors the placeholders: every placeholder takes the input up
+/// to the next occurrence of the text following it. A placeholder directly followed by another one
+/// takes as long a number as possible, or a string up to the next whitespace. Padding is removed,
+/// numbers (`%d`, `%f`, `%x` and numeric `%v`) are returned as numbers, integers for `%d` and `%x`, all
+/// others as strings.
+/// Variables which are expressions like `ms / 1000` can't be inverted and are left out. Styles are
+/// ignored, print with [crate::options::PrintOptions::strip_styles] to scan the output.
+/// ```
+/// use sprintf::{parser::parse_format_string, scanner::sscanf, value::Value};
+///
+/// let parsed = parse_format_string(r#""%-8s took %.2fs (%d%%, %x) %s", user, took, load, id, ms / 1000"#).unwrap();
+/// let values = sscanf(&parsed, "foo      took 1.50s (42%, ff) 1.2").unwrap();
+/// assert_eq!(values["user"], Value::String("foo".into()));
+/// assert_eq!(values["took"], Value::Numeric(1.5));
+/// assert_eq!(values["load"], Value::Integer(42));
+/// assert_eq!(values["id"], Value::Integer(255));
+/// assert_eq!(values.len(), 4);
+///
+/// let parsed = parse_format_string(r#""%05d%s", code, unit"#).unwrap();
+/// let values = sscanf(&parsed, "00404ms").unwrap();
+/// assert_eq!(values["code"], Value::Integer(404));
+/// assert_eq!(values["unit"], Value::String("ms".into()));
+///
+/// let parsed = parse_format_string(r#""took %ds", took"#).unwrap();
+/// assert_eq!(
+///     sscanf(&parsed, "took 1.5s").unwrap_err().to_string(),
+///     "Input does not match at byte 5: expected an integer instead of '1.5'"
+/// );
+/// assert!(sscanf(&parsed, "lasted 1s").is_err());
+/// ```
+pub fn sscanf(parsed: &ParsedFormat, input: &str) -> Result<BTreeMap<String, Value>> {
+    let mut values = BTreeMap::new();
+    let mut arguments = parsed.variables.iter().zip(&parsed.arguments);
+    let mut offset = 0;
+    // sections are expected to be printed
+    let mut entries = parsed
+        .entries
+        .iter()
+        .filter(|entry| !matches!(entry, Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_)));
+    let mut next = entries.next();
+    while let Some(entry) = next {
+        next = entries.next();
+        let rest = &input[offset..];
+        let placeholder = match entry {
+            Entry::Text(text) => {
+                if !rest.starts_with(text.as_ref()) {
+                    return Err(SprintfError::Mismatch {
+                        offset,
+                        reason: format!("expected '{}'", text),
+                    });
+                }
+                offset += text.len();
+                continue;
+            }
+            Entry::Placeholder(placeholder) => placeholder,
+            Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => continue,
+        };
+
+        let len = match next {
+            Some(Entry::Text(text)) => rest.find(text.as_ref()).ok_or_else(|| SprintfError::Mismatch {
+                offset,
+                reason: format!("expected '{}'", text),
+            })?,
+            Some(_) => {
+                let padding = rest.len() - rest.trim_start_matches(' ').len();
+                padding
+                    + rest[padding..]
+                        .find(|c| !is_number_char(placeholder, c))
+                        .unwrap_or(rest.len() - padding)
+            }
+            None => rest.len(),
+        };
+        let value = scan_value(placeholder, &rest[..len], offset)?;
+        if let Some((variable, Expression::Variable(_))) = arguments.next() {
+            values.insert(variable.clone(), value);
+        }
+        offset += len;
+    }
+
+    if offset < input.len() {
+        return Err(SprintfError::Mismatch {
+            offset,
+            reason: "unexpected text at the end".into(),
+        });
+    }
+    Ok(values)
+}
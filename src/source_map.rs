@@ -0,0 +1,59 @@
+use std::ops::Range;
+
+use crate::parser::Span;
+
+/// The part of a rendered output printed by a single template entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Byte range within the rendered output.
+    pub output: Range<usize>,
+    /// Index into [crate::parser::ParsedFormatString::entries].
+    pub entry: usize,
+    /// The variable a placeholder is bound to, `None` for text.
+    pub variable: Option<String>,
+    /// Span of the entry within the template source, if known.
+    pub template: Option<Span>,
+}
+
+/// The part of a rendered output printed by a single placeholder, see
+/// [crate::printer::sprintf_traced].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSpan {
+    /// 0-based index among the placeholders of the template.
+    pub placeholder_index: usize,
+    /// The variable the placeholder is bound to.
+    pub variable: String,
+    /// Byte range within the rendered output.
+    pub range: Range<usize>,
+}
+
+/// Maps byte ranges of a rendered output back to the template entries and variables they were
+/// printed from, see [crate::printer::sprintf_with_source_map].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SourceMap {
+    /// All segments in output order, without gaps between them except for template tags and the
+    /// ellipsis of a truncated output.
+    pub segments: Vec<Segment>,
+}
+
+impl SourceMap {
+    /// The segment the output byte at `offset` was printed by.
+    pub fn at(&self, offset: usize) -> Option<&Segment> {
+        self.segments.iter().find(|segment| segment.output.contains(&offset))
+    }
+
+    /// All segments printed for `variable`.
+    pub fn variable<'a>(&'a self, variable: &'a str) -> impl Iterator<Item = &'a Segment> {
+        self.segments
+            .iter()
+            .filter(move |segment| segment.variable.as_deref() == Some(variable))
+    }
+
+    /// Cuts the segments off at the output byte `end`, dropping the ones starting at or after it.
+    pub(crate) fn truncate(&mut self, end: usize) {
+        self.segments.retain(|segment| segment.output.start < end);
+        for segment in &mut self.segments {
+            segment.output.end = segment.output.end.min(end);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+/// A flag that can be used within a placeholder, e.g. the `-` in `%-8s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flag {
+    /// `-`, left align.
+    Left,
+    /// `^`, center.
+    Center,
+    /// `*c`, pad with `c`.
+    Fill,
+    /// A leading `0` in the width, pad with zeros.
+    ZeroPad,
+    /// `'`, group thousands as configured by [crate::options::Locale].
+    Grouping,
+    /// `!` or `!c`, truncate or replace values longer than the width.
+    Overflow,
+    /// Minimal width like the `8` in `%8s`.
+    Width,
+    /// Digits after the dot like the `.2` in `%.2f`.
+    Precision,
+}
+
+/// Kind of value a specifier accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    String,
+    Numeric,
+    Bool,
+}
+
+/// Kind of value a placeholder expects, see [crate::parser::Placeholder::expected_kind].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    String,
+    /// A number, fractions are truncated.
+    Integer,
+    Float,
+    Any,
+}
+
+impl ValueKind {
+    /// The kind accepted by placeholders of both kinds: [ValueKind::Any] yields to the other and
+    /// an integer is also a float. Otherwise no value fits both and `self` is kept.
+    pub fn narrow(self, other: ValueKind) -> ValueKind {
+        match (self, other) {
+            (ValueKind::Any, kind) | (kind, ValueKind::Any) => kind,
+            (ValueKind::Integer, ValueKind::Float) | (ValueKind::Float, ValueKind::Integer) => ValueKind::Integer,
+            (kind, _) => kind,
+        }
+    }
+}
+
+/// Machine readable description of a conversion specifier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Specifier {
+    pub conversion: char,
+    pub description: &'static str,
+    pub flags: &'static [Flag],
+    pub value_types: &'static [ValueType],
+    /// Cargo feature the specifier depends on, `None` if always available.
+    pub feature: Option<&'static str>,
+}
+
+const PADDING: &[Flag] = &[Flag::Left, Flag::Center, Flag::Fill, Flag::Overflow, Flag::Width];
+const NUMBER_PADDING: &[Flag] = &[
+    Flag::Left,
+    Flag::Center,
+    Flag::Fill,
+    Flag::Overflow,
+    Flag::ZeroPad,
+    Flag::Width,
+];
+const GROUPED_NUMBER_PADDING: &[Flag] = &[
+    Flag::Left,
+    Flag::Center,
+    Flag::Grouping,
+    Flag::Fill,
+    Flag::Overflow,
+    Flag::ZeroPad,
+    Flag::Width,
+];
+const FLOAT_PADDING: &[Flag] = &[
+    Flag::Left,
+    Flag::Center,
+    Flag::Grouping,
+    Flag::Fill,
+    Flag::Overflow,
+    Flag::ZeroPad,
+    Flag::Width,
+    Flag::Precision,
+];
+
+/// Registry of all conversions, the single source for tokenizing, parsing, printing and describing
+/// placeholders. Every conversion is handled by exhaustive matches, so adding one fails to compile
+/// until it is parsed, printed and described.
+/// ```
+/// use sprintf::{parser::Placeholder, printer::print_value, specifiers::Conversion, value::Value};
+///
+/// for &conversion in Conversion::ALL {
+///     let placeholder: Placeholder = format!("%{}", conversion.char()).try_into().unwrap();
+///     assert_eq!(placeholder.conversion(), conversion);
+///     assert_eq!(placeholder.to_string(), format!("%{}", conversion.char()));
+///
+///     let value = match conversion {
+///         Conversion::String => Value::String("text".into()),
+///         _ => Value::Numeric(10.0),
+///     };
+///     assert!(print_value(&placeholder, &value).is_ok());
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Conversion {
+    /// `%v`
+    Display,
+    /// `%s`
+    String,
+    /// `%d`
+    Number,
+    /// `%f`
+    Float,
+    /// `%x`
+    Hex,
+    /// `%X`
+    UpperHex,
+    /// `%D`
+    Duration,
+    /// `%h`
+    BinarySize,
+    /// `%H`
+    DecimalSize,
+    /// `%p`
+    Percent,
+    /// `%T`
+    #[cfg(feature = "chrono")]
+    DateTime,
+    /// A conversion registered with [crate::formatter::Formatter::register].
+    Custom(char),
+}
+
+impl Conversion {
+    /// All built-in conversions of this build in the order they are documented.
+    pub const ALL: &'static [Conversion] = &[
+        Conversion::Display,
+        Conversion::String,
+        Conversion::Number,
+        Conversion::Float,
+        Conversion::Hex,
+        Conversion::UpperHex,
+        Conversion::Duration,
+        Conversion::BinarySize,
+        Conversion::DecimalSize,
+        Conversion::Percent,
+        #[cfg(feature = "chrono")]
+        Conversion::DateTime,
+    ];
+
+    /// The character a placeholder ends with.
+    pub const fn char(self) -> char {
+        match self {
+            Conversion::Display => 'v',
+            Conversion::String => 's',
+            Conversion::Number => 'd',
+            Conversion::Float => 'f',
+            Conversion::Hex => 'x',
+            Conversion::UpperHex => 'X',
+            Conversion::Duration => 'D',
+            Conversion::BinarySize => 'h',
+            Conversion::DecimalSize => 'H',
+            Conversion::Percent => 'p',
+            #[cfg(feature = "chrono")]
+            Conversion::DateTime => 'T',
+            Conversion::Custom(c) => c,
+        }
+    }
+
+    /// The built-in conversion for `c`.
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::ALL.iter().copied().find(|conversion| conversion.char() == c)
+    }
+
+    pub const fn specifier(self) -> Specifier {
+        match self {
+            Conversion::Display => Specifier {
+                conversion: 'v',
+                description: "any value as displayed",
+                flags: PADDING,
+                value_types: &[ValueType::String, ValueType::Numeric, ValueType::Bool],
+                feature: None,
+            },
+            Conversion::String => Specifier {
+                conversion: 's',
+                description: "string",
+                flags: PADDING,
+                value_types: &[ValueType::String],
+                feature: None,
+            },
+            Conversion::Number => Specifier {
+                conversion: 'd',
+                description: "decimal, fractions are truncated",
+                flags: GROUPED_NUMBER_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::Float => Specifier {
+                conversion: 'f',
+                description: "float",
+                flags: FLOAT_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::Hex => Specifier {
+                conversion: 'x',
+                description: "hexadecimal lowercase",
+                flags: NUMBER_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::UpperHex => Specifier {
+                conversion: 'X',
+                description: "hexadecimal uppercase",
+                flags: NUMBER_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::Duration => Specifier {
+                conversion: 'D',
+                description: "duration in seconds, or milliseconds with `{:ms}`, like `1h 23m 45s`",
+                flags: PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::BinarySize => Specifier {
+                conversion: 'h',
+                description: "byte size in binary units like `1.5 MiB`",
+                flags: FLOAT_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::DecimalSize => Specifier {
+                conversion: 'H',
+                description: "byte size in SI units like `1.5 MB`",
+                flags: FLOAT_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            Conversion::Percent => Specifier {
+                conversion: 'p',
+                description: "ratio as percentage like `%.1p` printing `0.425` as `42.5%`",
+                flags: FLOAT_PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: None,
+            },
+            #[cfg(feature = "chrono")]
+            Conversion::DateTime => Specifier {
+                conversion: 'T',
+                description: "date and time of a unix timestamp in UTC",
+                flags: PADDING,
+                value_types: &[ValueType::Numeric],
+                feature: Some("chrono"),
+            },
+            // the handler interprets all flags itself
+            Conversion::Custom(c) => Specifier {
+                conversion: c,
+                description: "custom conversion",
+                flags: &[],
+                value_types: &[ValueType::String, ValueType::Numeric, ValueType::Bool],
+                feature: None,
+            },
+        }
+    }
+}
+
+const SPECIFIERS: [Specifier; Conversion::ALL.len()] = {
+    let mut specifiers = [Conversion::Display.specifier(); Conversion::ALL.len()];
+    let mut i = 0;
+    while i < specifiers.len() {
+        specifiers[i] = Conversion::ALL[i].specifier();
+        i += 1;
+    }
+    specifiers
+};
+
+/// All specifiers supported by this build, e.g. to generate help texts or autocompletion.
+/// ```
+/// use sprintf::{specifiers::*, tokenizer::CONVERSIONS};
+///
+/// let conversions: Vec<char> = supported_specifiers().iter().map(|s| s.conversion).collect();
+/// assert_eq!(conversions, CONVERSIONS);
+///
+/// let float = supported_specifiers().iter().find(|s| s.conversion == 'f').unwrap();
+/// assert!(float.flags.contains(&Flag::Precision));
+/// assert_eq!(float.value_types, &[ValueType::Numeric]);
+/// ```
+pub fn supported_specifiers() -> &'static [Specifier] {
+    &SPECIFIERS
+}
@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use crate::{
+    error::{Result, SprintfError},
+    parser::{check_arity, Entry, ParsedFormat, ParsedFormatString, Placeholder},
+    specifiers::Conversion,
+    types::{Alignment, DurationFormat, FloatFormat, HexFormat, NumberFormat, Overflow, StringFormat},
+};
+
+#[cfg(feature = "chrono")]
+use crate::types::DateTimeFormat;
+
+/// The parts of a `[[fill]align][0][width][.precision][type]` format spec.
+#[derive(Debug, Default)]
+struct Spec {
+    fill: Option<char>,
+    align: Option<Alignment>,
+    zero: bool,
+    width: Option<u16>,
+    precision: Option<u16>,
+    conversion: Option<char>,
+}
+
+fn alignment(c: char) -> Option<Alignment> {
+    match c {
+        '<' => Some(Alignment::Left),
+        '^' => Some(Alignment::Center),
+        '>' => Some(Alignment::Right),
+        _ => None,
+    }
+}
+
+fn align_char(align: Alignment) -> char {
+    match align {
+        Alignment::Left => '<',
+        Alignment::Center => '^',
+        Alignment::Right => '>',
+    }
+}
+
+fn parse_spec(spec: &str) -> Result<Spec> {
+    let invalid = || SprintfError::InvalidTemplate(format!("Unsupported format spec '{}'", spec));
+    let mut result = Spec::default();
+
+    let mut chars = spec.chars();
+    let rest = match (chars.next(), chars.next().and_then(alignment)) {
+        (Some(fill), Some(align)) => {
+            result.fill = Some(fill);
+            result.align = Some(align);
+            chars.as_str()
+        }
+        _ => match spec.chars().next().and_then(alignment) {
+            Some(align) => {
+                result.align = Some(align);
+                &spec[1..]
+            }
+            None => spec,
+        },
+    };
+
+    let rest = match rest.strip_prefix('0') {
+        Some(digits) if digits.starts_with(|c: char| c.is_ascii_digit()) => {
+            result.zero = true;
+            digits
+        }
+        _ => rest,
+    };
+
+    let (rest, conversion) = match rest.chars().next_back() {
+        Some(c @ ('x' | 'X' | '?')) => (&rest[..rest.len() - 1], Some(c)),
+        _ => (rest, None),
+    };
+    result.conversion = conversion;
+
+    let (width, precision) = match rest.split_once('.') {
+        Some((width, precision)) => (width, Some(precision)),
+        None => (rest, None),
+    };
+    let number = |s: &str| s.parse::<u16>().map_err(|_| invalid());
+    result.width = (!width.is_empty()).then(|| number(width)).transpose()?;
+    result.precision = precision.map(number).transpose()?;
+
+    Ok(result)
+}
+
+/// Fill of a right aligned number. Without one, the printf IR does not pad at all.
+fn number_fill(spec: &Spec) -> Option<char> {
+    match (spec.fill, spec.align.unwrap_or(Alignment::Right)) {
+        (Some(' '), Alignment::Left | Alignment::Center) => None,
+        (Some(fill), _) => Some(fill),
+        (None, Alignment::Right) if spec.width.is_some() && !spec.zero => Some(' '),
+        (None, _) => None,
+    }
+}
+
+fn placeholder(spec: &Spec) -> Placeholder {
+    let nf = NumberFormat {
+        fill_zeros: spec.zero,
+        digits: spec.width,
+        fill_char: number_fill(spec),
+        align: spec.align.unwrap_or(Alignment::Right),
+        grouping: false,
+        overflow: Overflow::Extend,
+    };
+
+    match (spec.conversion, spec.precision) {
+        (Some(conversion @ ('x' | 'X')), _) => Placeholder::Hex(HexFormat {
+            uppercase: conversion == 'X',
+            nf,
+        }),
+        (_, Some(precision)) => Placeholder::Float(FloatFormat {
+            base: NumberFormat {
+                // the width of right aligned floats only covers the integer part
+                digits: match nf.align {
+                    Alignment::Right => nf.digits.map(|width| width.saturating_sub(precision + 1)),
+                    Alignment::Left | Alignment::Center => nf.digits,
+                },
+                ..nf
+            },
+            fraction: NumberFormat {
+                digits: Some(precision),
+                fill_zeros: true,
+                ..Default::default()
+            },
+        }),
+        _ if spec.zero => Placeholder::Number(nf),
+        _ => Placeholder::Display(StringFormat {
+            width: spec.width,
+            fill_char: spec.fill.filter(|c| *c != ' '),
+            // like strings in std::fmt, values without alignment are left aligned
+            align: spec.align.unwrap_or(Alignment::Left),
+            overflow: Overflow::Extend,
+            verbose: false,
+        }),
+    }
+}
+
+/// Parses a `format!`-style template like `{user:>12} took {secs:.2}s` into a [ParsedFormatString].
+///
+/// Every argument has to be named inline, positional arguments like `{}` or `{0}` are rejected, see
+/// [parse_std_fmt_args] for those.
+/// The spec supports `[[fill]align][0][width][.precision][type]` with the types `x`, `X` and `?`.
+/// A precision results in a [Placeholder::Float], the `0` flag in a [Placeholder::Number] and
+/// everything else in a [Placeholder::Display]. Use [to_std_fmt] for the way back.
+/// ```
+/// use sprintf::{parser::*, std_fmt::parse_std_fmt, types::*};
+///
+/// let parsed = parse_std_fmt("{{{user:>8}}} took {secs:.2}s, id {id:08x}").unwrap();
+/// assert_eq!(parsed.variables, vec!["user", "secs", "id"]);
+/// assert_eq!(parsed.entries[0], Entry::Text("{".into()));
+/// assert_eq!(
+///     parsed.entries[1],
+///     Entry::Placeholder(Placeholder::Display(StringFormat {
+///         width: Some(8),
+///         align: Alignment::Right,
+///         ..Default::default()
+///     }))
+/// );
+/// assert!(matches!(parsed.entries[3], Entry::Placeholder(Placeholder::Float(_))));
+/// assert!(matches!(parsed.entries[5], Entry::Placeholder(Placeholder::Hex(_))));
+///
+/// assert!(parse_std_fmt("{} took {:.2}s").is_err());
+/// assert!(parse_std_fmt("{user:+}").is_err());
+/// ```
+pub fn parse_std_fmt(input: &str) -> Result<ParsedFormatString> {
+    parse(input, None)
+}
+
+/// Like [parse_std_fmt], but binds positional arguments like `{}` and `{0:.2}` to `arguments`, as
+/// `format!` does. `{}` takes the next argument, `{1}` the second one. Inline names can be mixed
+/// in, and every argument has to be used.
+/// ```
+/// use sprintf::{printer::sprintf_with, std_fmt::parse_std_fmt_args, value::Value};
+///
+/// let parsed = parse_std_fmt_args("{} took {:.2}s, {user} again after {1:.0}s", &["user", "ms / 1000"]).unwrap();
+/// assert_eq!(parsed.variables, vec!["user", "ms / 1000", "user", "ms / 1000"]);
+/// assert_eq!(
+///     sprintf_with(&parsed.to_string(), &[("user", "foo".into()), ("ms", Value::Numeric(1234.0))]).unwrap(),
+///     "foo took 1.23s, foo again after 1s"
+/// );
+///
+/// assert!(parse_std_fmt_args("{} took {}s", &["user"]).is_err());
+/// assert!(parse_std_fmt_args("{user}", &["secs"]).is_err());
+/// ```
+pub fn parse_std_fmt_args(input: &str, arguments: &[&str]) -> Result<ParsedFormatString> {
+    parse(input, Some(arguments))
+}
+
+/// Parses `input`, positional arguments are rejected without `arguments`.
+fn parse(input: &str, arguments: Option<&[&str]>) -> Result<ParsedFormatString> {
+    let mut used = vec![false; arguments.map_or(0, <[_]>::len)];
+    let mut next = 0;
+    let mut entries = Vec::new();
+    let mut variables = Vec::new();
+    let mut text = String::new();
+    let mut text_start = 0;
+
+    let mut rest = input;
+    while let Some(start) = rest.find(['{', '}']) {
+        text.push_str(&rest[..start]);
+        let brace = &rest[start..];
+        if let Some(after) = brace.strip_prefix("{{").or_else(|| brace.strip_prefix("}}")) {
+            text.push_str(&brace[..1]);
+            rest = after;
+            continue;
+        }
+        let end = match brace.strip_prefix('{').and_then(|inner| inner.find('}')) {
+            Some(end) => end,
+            None => return Err(SprintfError::InvalidTemplate(format!("Unmatched brace in '{}'", input))),
+        };
+
+        let argument = &brace[1..1 + end];
+        let (name, spec) = argument.split_once(':').unwrap_or((argument, ""));
+        let mut name = name.trim();
+        if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+            let arguments = arguments.ok_or_else(|| {
+                SprintfError::InvalidTemplate(format!("Positional arguments are not supported: '{{{}}}'", argument))
+            })?;
+            let index = match name.parse() {
+                Ok(index) => index,
+                Err(_) => {
+                    next += 1;
+                    next - 1
+                }
+            };
+            name = arguments.get(index).ok_or_else(|| {
+                SprintfError::InvalidTemplate(format!("Missing argument {} for '{{{}}}'", index, argument))
+            })?;
+            used[index] = true;
+        }
+
+        let offset = input.len() - brace.len();
+        if !text.is_empty() {
+            entries.push((Entry::Text(std::mem::take(&mut text).into()), text_start..offset));
+        }
+        entries.push((
+            Entry::Placeholder(placeholder(&parse_spec(spec)?)),
+            offset..offset + end + 2,
+        ));
+        variables.push(name.to_string());
+
+        rest = &brace[end + 2..];
+        text_start = input.len() - rest.len();
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        entries.push((Entry::Text(text.into()), text_start..input.len()));
+    }
+
+    if let Some(index) = used.iter().position(|used| !used) {
+        return Err(SprintfError::InvalidTemplate(format!(
+            "Argument '{}' is never used",
+            arguments.unwrap_or_default()[index]
+        )));
+    }
+
+    Ok(ParsedFormatString::new_spanned(entries, variables))
+}
+
+fn write_spec(out: &mut String, fill: Option<char>, align: Option<Alignment>, zero: bool, width: Option<u16>) {
+    match (fill, align) {
+        (Some(fill), align) => {
+            out.push(fill);
+            out.push(align_char(align.unwrap_or(Alignment::Right)));
+        }
+        (None, Some(align)) => out.push(align_char(align)),
+        (None, None) => {}
+    }
+    if zero {
+        out.push('0');
+    }
+    if let Some(width) = width {
+        let _ = write!(out, "{}", width);
+    }
+}
+
+fn write_number_spec(out: &mut String, nf: &NumberFormat, width: Option<u16>) {
+    match (nf.align, nf.fill()) {
+        // not padded at all
+        (_, None) => {}
+        (Alignment::Right, Some('0')) if nf.fill_char.is_none() => write_spec(out, None, None, true, width),
+        (align, Some(' ')) => write_spec(out, None, Some(align), false, width),
+        (align, fill) => write_spec(out, fill, Some(align), false, width),
+    }
+}
+
+fn write_string_spec(out: &mut String, sf: &StringFormat) {
+    if sf.width.is_some() {
+        write_spec(out, sf.fill_char, Some(sf.align), false, sf.width);
+    }
+}
+
+fn write_placeholder(out: &mut String, placeholder: &Placeholder) {
+    match placeholder {
+        Placeholder::Display(sf) | Placeholder::String(sf) => write_string_spec(out, sf),
+        Placeholder::Number(nf) => write_number_spec(out, nf, nf.digits),
+        Placeholder::Hex(hf) => {
+            write_number_spec(out, &hf.nf, hf.nf.digits);
+            out.push(if hf.uppercase { 'X' } else { 'x' });
+        }
+        Placeholder::Float(ff) => match ff.fraction.digits {
+            Some(precision) => {
+                let width = match ff.base.align {
+                    Alignment::Right => ff.base.digits.map(|digits| digits + precision + 1),
+                    Alignment::Left | Alignment::Center => ff.base.digits,
+                };
+                write_number_spec(out, &ff.base, width);
+                let _ = write!(out, ".{}", precision);
+            }
+            // both print the shortest representation without a precision
+            None => write_number_spec(out, &ff.base, ff.base.digits),
+        },
+        Placeholder::Duration(DurationFormat { base: sf, .. }) => write_string_spec(out, sf),
+        // the width covers the whole size
+        Placeholder::Size(sf) => write_number_spec(out, &sf.ff.base, sf.ff.base.digits),
+        #[cfg(feature = "chrono")]
+        Placeholder::DateTime(DateTimeFormat { base: sf, .. }) => write_string_spec(out, sf),
+        // the flags are up to the handler, C flags have no exact counterpart, percentages are
+        // rejected by to_std_fmt
+        Placeholder::Custom(..) | Placeholder::C99(_) | Placeholder::Percent(_) => {}
+        Placeholder::Modified(_, inner) => write_placeholder(out, inner),
+    }
+}
+
+/// Converts `parsed` into a `format!`-style template, the counterpart of [parse_std_fmt].
+///
+/// Both syntaxes differ in details, so the conversion is lossy: `%s` and `%v` both become `{name}`,
+/// and a `%d` without padding can't be told apart from `%v` anymore.
+/// ```
+/// use sprintf::{parser::parse_format_string, std_fmt::to_std_fmt};
+///
+/// let parsed = parse_format_string(r#""%-12s took %04.2fs {%08X}", user, secs, id"#).unwrap();
+/// assert_eq!(to_std_fmt(&parsed).unwrap(), "{user:<12} took {secs:07.2}s {{{id:08X}}}");
+/// ```
+pub fn to_std_fmt(parsed: &ParsedFormat) -> Result<String> {
+    check_arity(&parsed.entries, &parsed.variables)?;
+
+    let mut out = String::new();
+    let mut variables = parsed.variables.iter();
+    for entry in &parsed.entries {
+        match entry {
+            Entry::Text(text) => out.push_str(&text.replace('{', "{{").replace('}', "}}")),
+            // std::fmt has no styles
+            Entry::Style(_) => {}
+            Entry::Section(_) | Entry::SectionEnd(_) => {
+                return Err(SprintfError::InvalidTemplate(
+                    "std::fmt has no conditional sections".into(),
+                ))
+            }
+            Entry::Placeholder(placeholder) if placeholder.conversion() == Conversion::Percent => {
+                return Err(SprintfError::InvalidTemplate("std::fmt has no percentages".into()))
+            }
+            Entry::Placeholder(placeholder) => {
+                let mut spec = String::new();
+                write_placeholder(&mut spec, placeholder);
+                // check_arity made sure there is a variable for every placeholder
+                let name = variables.next().map(String::as_str).unwrap_or_default();
+                if spec.is_empty() {
+                    let _ = write!(out, "{{{}}}", name);
+                } else {
+                    let _ = write!(out, "{{{}:{}}}", name, spec);
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
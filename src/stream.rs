@@ -0,0 +1,71 @@
+use crate::{
+    error::{Result, SprintfError},
+    options::ParseOptions,
+    parser::{explode_with, Entry},
+    tokenizer::Tokenizer,
+};
+
+/// Parses a bare format string which arrives in chunks, e.g. a multi-megabyte generated template
+/// read from a file, without keeping all of it in memory.
+///
+/// Every [StreamParser::feed] returns the entries completed so far. Only a placeholder cut by a chunk
+/// border is kept back until the next chunk. Text may thus be split into several entries.
+/// ```
+/// use sprintf::{parser::*, stream::StreamParser};
+///
+/// let mut parser = StreamParser::default();
+/// let mut entries = parser.feed("took %.2").unwrap();
+/// assert_eq!(entries, vec![Entry::Text("took ".into())]);
+///
+/// entries.extend(parser.feed("fs at 10").unwrap());
+/// entries.extend(parser.feed("0%%").unwrap());
+/// entries.extend(parser.finish().unwrap());
+/// assert!(matches!(entries[1], Entry::Placeholder(Placeholder::Float(_))));
+/// assert_eq!(entries[2], Entry::Text("s at 10".into()));
+/// assert_eq!(entries[3], Entry::Text("0".into()));
+/// assert_eq!(entries[4], Entry::Text("%".into()));
+///
+/// let mut parser = StreamParser::default();
+/// parser.feed("100%").unwrap();
+/// assert!(parser.finish().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct StreamParser {
+    options: ParseOptions,
+    /// Start of a placeholder which is not complete yet.
+    pending: String,
+}
+
+impl StreamParser {
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            pending: String::new(),
+        }
+    }
+
+    /// Parses the next chunk of the input and returns all entries completed by it.
+    pub fn feed(&mut self, chunk: &str) -> Result<Vec<Entry<'static>>> {
+        self.pending.push_str(chunk);
+        let incomplete = Tokenizer::new(&self.pending)
+            .find_map(|token| match token {
+                Err(SprintfError::UnterminatedFormat { span, .. }) => Some(span.start),
+                _ => None,
+            })
+            .unwrap_or(self.pending.len());
+
+        let entries = explode_with(&self.pending[..incomplete], &self.options)?
+            .into_iter()
+            .map(Entry::into_owned)
+            .collect();
+        self.pending.drain(..incomplete);
+        Ok(entries)
+    }
+
+    /// Ends the input and returns the remaining entries. Fails on an incomplete placeholder unless
+    /// stray `%` are taken literally.
+    pub fn finish(self) -> Result<Vec<Entry<'static>>> {
+        let entries = explode_with(&self.pending, &self.options)?;
+        Ok(entries.into_iter().map(Entry::into_owned).collect())
+    }
+}
@@ -0,0 +1,294 @@
+use std::ops::Range;
+
+use crate::{
+    c99::{CFormat, Scan},
+    error::{Result, SprintfError},
+    extractor::{
+        CENTER_MARKER, FILL_MARKER, GROUPING_MARKER, LEFT_MARKER, MODIFIERS_END, MODIFIERS_START, NAME_END, NAME_START,
+        OVERFLOW_MARKER, SECTION_END, SECTION_START, STYLE_END, STYLE_START, VERBOSE_MARKER,
+    },
+    specifiers::Conversion,
+};
+
+/// All conversion characters a placeholder can end with, see [Conversion].
+pub const CONVERSIONS: &[char] = &{
+    let mut chars = ['\0'; Conversion::ALL.len()];
+    let mut i = 0;
+    while i < chars.len() {
+        chars[i] = Conversion::ALL[i].char();
+        i += 1;
+    }
+    chars
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
+    /// Literal text.
+    Text(&'a str),
+    /// An escaped percent sign, `%%`.
+    Percent,
+    /// A complete placeholder like `%-*.8d`, including the leading `%`.
+    Placeholder(&'a str),
+    /// A style token like `%[bold red]`, including the leading `%`.
+    Style(&'a str),
+    /// The start or end of a conditional section like `%{?error}` or `%{/error}`, including the
+    /// leading `%`.
+    Section(&'a str),
+}
+
+/// Where the tokenizer is within a placeholder
+/// `%[(name)|position$][{modifiers}][-^]['][*c][!c][width][.precision]conversion`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Percent,
+    /// Within the variable name of `%(name)s`.
+    Name,
+    /// Behind the variable name, like [State::Percent] but without `%%` and styles.
+    Named,
+    Modifiers,
+    /// Behind the modifiers block, like [State::Percent] but without `%%`.
+    Modified,
+    /// Behind the `+` of `%+v`.
+    Verbose,
+    Align,
+    Grouping,
+    Fill,
+    FillChar,
+    Overflow,
+    OverflowFill,
+    Width,
+    Precision,
+}
+
+/// Splits a format string into [Token]s and their byte ranges.
+///
+/// After an error the tokenizer continues behind the offending `%`, so callers that want to collect
+/// all problems of an input can keep iterating.
+/// ```
+/// use sprintf::tokenizer::{Token, Tokenizer};
+///
+/// let tokens: Vec<_> = Tokenizer::new("%-8s: 100%%").collect::<Result<_, _>>().unwrap();
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         (Token::Placeholder("%-8s"), 0..4),
+///         (Token::Text(": 100"), 4..9),
+///         (Token::Percent, 9..11),
+///     ]
+/// );
+///
+/// let err = Tokenizer::new("temperature: %.2q").find_map(|t| t.err()).unwrap();
+/// assert_eq!(err.to_string(), "invalid conversion 'q' at column 17");
+/// assert_eq!(err.span(), Some(13..17));
+///
+/// let err = Tokenizer::new("100%").find_map(|t| t.err()).unwrap();
+/// assert_eq!(err.to_string(), "unterminated placeholder at column 4");
+/// ```
+pub struct Tokenizer<'a> {
+    input: &'a str,
+    pos: usize,
+    custom_conversions: &'a [char],
+    c99: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::with_conversions(input, &[])
+    }
+
+    /// Also ends placeholders at the characters of `custom_conversions`, see
+    /// [crate::formatter::Formatter].
+    pub fn with_conversions(input: &'a str, custom_conversions: &'a [char]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            custom_conversions,
+            c99: false,
+        }
+    }
+
+    /// Only accepts strict C99 placeholders like `%-+8.3le`, see [crate::c99].
+    pub fn c99(input: &'a str) -> Self {
+        Self {
+            c99: true,
+            ..Self::new(input)
+        }
+    }
+
+    fn is_conversion(&self, c: char) -> bool {
+        CONVERSIONS.contains(&c) || self.custom_conversions.contains(&c)
+    }
+
+    fn column(&self, offset: usize) -> usize {
+        self.input[..offset].chars().count() + 1
+    }
+
+    fn c99_placeholder(&mut self, start: usize) -> Result<(Token<'a>, Range<usize>)> {
+        let spec = &self.input[start + 1..];
+        if spec.starts_with('%') {
+            self.pos = start + 2;
+            return Ok((Token::Percent, start..self.pos));
+        }
+        // continue behind the '%' on errors
+        self.pos = start + 1;
+        match CFormat::scan(spec) {
+            Scan::Format(_, len) => {
+                self.pos = start + 1 + len;
+                Ok((Token::Placeholder(&self.input[start..self.pos]), start..self.pos))
+            }
+            Scan::Invalid(offset) => {
+                let at = start + 1 + offset;
+                let conversion = self.input[at..].chars().next().unwrap_or_default();
+                Err(SprintfError::UnknownConversion {
+                    conversion,
+                    column: self.column(at),
+                    span: start..at + conversion.len_utf8(),
+                })
+            }
+            Scan::Unterminated => Err(SprintfError::UnterminatedFormat {
+                column: self.column(start),
+                span: start..self.input.len(),
+            }),
+        }
+    }
+
+    fn placeholder(&mut self, start: usize) -> Result<(Token<'a>, Range<usize>)> {
+        if self.c99 {
+            return self.c99_placeholder(start);
+        }
+        let mut state = State::Percent;
+        // braces nested in the modifiers block, like the messages of `%{plural:one{# file} other{# files}}s`
+        let mut depth = 0;
+        for (offset, c) in self.input[start + 1..].char_indices() {
+            let at = start + 1 + offset;
+            state = match (state, c) {
+                (State::Percent, '%') => {
+                    self.pos = at + 1;
+                    return Ok((Token::Percent, start..self.pos));
+                }
+                (State::Percent, STYLE_START) => match self.input[at..].find(STYLE_END) {
+                    Some(len) => {
+                        self.pos = at + len + STYLE_END.len_utf8();
+                        return Ok((Token::Style(&self.input[start..self.pos]), start..self.pos));
+                    }
+                    None => break,
+                },
+                (State::Percent, MODIFIERS_START) if self.input[at + 1..].starts_with([SECTION_START, SECTION_END]) => {
+                    match self.input[at..].find(MODIFIERS_END) {
+                        Some(len) => {
+                            self.pos = at + len + MODIFIERS_END.len_utf8();
+                            return Ok((Token::Section(&self.input[start..self.pos]), start..self.pos));
+                        }
+                        None => break,
+                    }
+                }
+                (State::Percent, NAME_START) => State::Name,
+                (State::Name, NAME_END) => State::Named,
+                (State::Name, _) => State::Name,
+                (State::Percent | State::Named, MODIFIERS_START) => State::Modifiers,
+                (State::Modifiers, MODIFIERS_START) => {
+                    depth += 1;
+                    State::Modifiers
+                }
+                (State::Modifiers, MODIFIERS_END) if depth > 0 => {
+                    depth -= 1;
+                    State::Modifiers
+                }
+                (State::Modifiers, MODIFIERS_END) => State::Modified,
+                (State::Modifiers, _) => State::Modifiers,
+                (State::Percent | State::Named | State::Modified, VERBOSE_MARKER) => State::Verbose,
+                (State::Percent | State::Named | State::Modified | State::Verbose, LEFT_MARKER | CENTER_MARKER) => {
+                    State::Align
+                }
+                (State::Percent | State::Named | State::Modified | State::Align, GROUPING_MARKER) => State::Grouping,
+                (
+                    State::Percent | State::Named | State::Modified | State::Verbose | State::Align | State::Grouping,
+                    FILL_MARKER,
+                ) => State::Fill,
+                (State::Fill, _) => State::FillChar,
+                (
+                    State::Percent
+                    | State::Named
+                    | State::Modified
+                    | State::Verbose
+                    | State::Align
+                    | State::Grouping
+                    | State::FillChar,
+                    OVERFLOW_MARKER,
+                ) => State::Overflow,
+                (State::Overflow, c) if !c.is_ascii_digit() && c != '.' && !self.is_conversion(c) => {
+                    State::OverflowFill
+                }
+                (
+                    State::Percent
+                    | State::Named
+                    | State::Modified
+                    | State::Verbose
+                    | State::Align
+                    | State::Grouping
+                    | State::FillChar
+                    | State::Overflow
+                    | State::OverflowFill
+                    | State::Width,
+                    '0'..='9',
+                ) => State::Width,
+                (
+                    State::Percent
+                    | State::Named
+                    | State::Modified
+                    | State::Verbose
+                    | State::Align
+                    | State::Grouping
+                    | State::FillChar
+                    | State::Overflow
+                    | State::OverflowFill
+                    | State::Width,
+                    '.',
+                ) => State::Precision,
+                (State::Precision, '0'..='9') => State::Precision,
+                // the position of `%2$s`, which may be followed by everything but the modifiers block
+                (State::Width, '$') if self.input[start + 1..at].bytes().all(|b| b.is_ascii_digit()) => State::Modified,
+                (_, c) if self.is_conversion(c) => {
+                    self.pos = at + c.len_utf8();
+                    return Ok((Token::Placeholder(&self.input[start..self.pos]), start..self.pos));
+                }
+                (_, c) => {
+                    // continue behind the '%'
+                    self.pos = start + 1;
+                    return Err(SprintfError::UnknownConversion {
+                        conversion: c,
+                        column: self.column(at),
+                        span: start..at + c.len_utf8(),
+                    });
+                }
+            };
+        }
+
+        self.pos = start + 1;
+        Err(SprintfError::UnterminatedFormat {
+            column: self.column(start),
+            span: start..self.input.len(),
+        })
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(Token<'a>, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let rest = &self.input[start..];
+        match rest.find('%') {
+            _ if rest.is_empty() => None,
+            Some(0) => Some(self.placeholder(start)),
+            Some(len) => {
+                self.pos = start + len;
+                Some(Ok((Token::Text(&rest[..len]), start..self.pos)))
+            }
+            None => {
+                self.pos = self.input.len();
+                Some(Ok((Token::Text(rest), start..self.pos)))
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    fields::{collect_composite, join, Composite, Field, FieldResolver, Values},
+    value::{Resolver, Value},
+};
+
+/// Tables add their fields by dotted paths and arrays their elements by index, both also as a
+/// whole. Datetimes are strings in their TOML notation.
+impl Field for ::toml::Value {
+    fn collect(&self, path: &str, values: &mut Values) {
+        match self {
+            ::toml::Value::String(string) => string.collect(path, values),
+            ::toml::Value::Integer(integer) => integer.collect(path, values),
+            ::toml::Value::Float(float) => float.collect(path, values),
+            ::toml::Value::Boolean(boolean) => boolean.collect(path, values),
+            ::toml::Value::Datetime(datetime) => datetime.to_string().collect(path, values),
+            ::toml::Value::Array(elements) => elements.as_slice().collect(path, values),
+            ::toml::Value::Table(table) => table.collect(path, values),
+        }
+    }
+}
+
+impl Field for ::toml::Table {
+    fn collect(&self, path: &str, values: &mut Values) {
+        for (name, field) in self {
+            field.collect(&join(path, name), values);
+        }
+        collect_composite(path, Composite::Map, self.keys().map(String::as_str), values);
+    }
+}
+
+/// Resolves variables like `server.name` or `server.ports.0` from a TOML document, which is
+/// converted once when the resolver is created.
+/// ```
+/// use sprintf::{parser::parse_format_string, printer::sprintf, toml::TomlResolver};
+///
+/// let config = r#"
+/// title = "foo"
+///
+/// [server]
+/// ports = [8080, 8443]
+/// started = 2024-01-02T03:04:05Z
+/// "#;
+/// let resolver = TomlResolver::new(&toml::from_str(config).unwrap());
+/// let parsed = parse_format_string(r#""%s on %d since %s", title, server.ports.1, server.started"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo on 8443 since 2024-01-02T03:04:05Z");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TomlResolver(FieldResolver);
+
+impl TomlResolver {
+    pub fn new(document: &::toml::Table) -> Self {
+        Self(FieldResolver::new(document))
+    }
+}
+
+impl Resolver for TomlResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.resolve(name)
+    }
+}
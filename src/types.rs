@@ -1,17 +1,399 @@
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Hash, Default)]
+pub enum Alignment {
+    #[default]
+    Right,
+    Left,   //< %-8s
+    Center, //< %^8s
+}
+
+/// What happens to a value longer than the width of its placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// Print the whole value, the output gets wider.
+    #[default]
+    Extend,
+    /// Cut the value off at the width, `%!4s`.
+    Truncate,
+    /// Fill the width with the character instead like a spreadsheet, `%!#4d`.
+    Fill(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
 pub struct NumberFormat {
     pub fill_zeros: bool,
     pub digits: Option<u16>,
+    /// Character used to pad up to `digits`. Takes precedence over `fill_zeros`.
+    pub fill_char: Option<char>,
+    pub align: Alignment,
+    /// Separates groups of thousands as configured by the printing locale, `%'d`.
+    pub grouping: bool,
+    pub overflow: Overflow,
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+impl NumberFormat {
+    /// The character to pad with, if the number should be padded at all.
+    /// Left and center aligned numbers are never zero filled but padded with spaces by default.
+    pub fn fill(&self) -> Option<char> {
+        match self.align {
+            Alignment::Right => self.fill_char.or(if self.fill_zeros { Some('0') } else { None }),
+            Alignment::Left | Alignment::Center => Some(self.fill_char.unwrap_or(' ')),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
+pub struct StringFormat {
+    pub width: Option<u16>,
+    /// Character used to pad up to `width`, defaults to a space.
+    pub fill_char: Option<char>,
+    pub align: Alignment,
+    pub overflow: Overflow,
+    /// `%+v`: prints the field names of a [crate::value::Value::Map].
+    pub verbose: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
 pub struct FloatFormat {
     pub base: NumberFormat,
     pub fraction: NumberFormat,
 }
 
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
 pub struct HexFormat {
     pub uppercase: bool,
     pub nf: NumberFormat,
 }
+
+/// Format of `%h` and `%H`, which print a number of bytes like `1.5 MiB` or `1.5 MB`.
+///
+/// The precision defaults to one digit, plain bytes have none. The width covers the whole output.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("ram", Value::Numeric(1536000.0));
+/// values.insert("disk", Value::Numeric(1048575.0));
+/// values.insert("small", Value::Numeric(512.0));
+/// let values: MapResolver = values.into();
+///
+/// let statement = r#""%h|%.2H|%h|%-*.10h|%h", ram, ram, disk, small, small"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "1.5 MiB|1.54 MB|1.0 MiB|512 B.....|512 B");
+/// ```
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
+pub struct SizeFormat {
+    /// SI units with a base of 1000 instead of binary units with a base of 1024.
+    pub si: bool,
+    pub ff: FloatFormat,
+}
+
+/// Unit of the values printed by `%D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DurationUnit {
+    #[default]
+    Seconds,
+    /// `%{:ms}D`
+    Milliseconds,
+}
+
+impl DurationUnit {
+    pub fn name(self) -> &'static str {
+        match self {
+            DurationUnit::Seconds => "s",
+            DurationUnit::Milliseconds => "ms",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "s" => Some(DurationUnit::Seconds),
+            "ms" => Some(DurationUnit::Milliseconds),
+            _ => None,
+        }
+    }
+}
+
+/// Format of `%D`, which prints a duration like `1h 23m 45s`, `2.3s` or `150ms`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("uptime", Value::Numeric(5025.0));
+/// values.insert("took", Value::Numeric(2.34));
+/// values.insert("latency", Value::Numeric(150.0));
+/// values.insert("age", Value::Numeric(-90061.0));
+/// let values: MapResolver = values.into();
+///
+/// let statement = r#""%D|%6D|%{:ms}D|%{:ms}D|%D", uptime, took, latency, uptime, age"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "1h 23m 45s|  2.3s|150ms|5s|-1d 1h 1m 1s");
+///
+/// assert!(parse_format_string(r#""%{:min}D", uptime"#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
+pub struct DurationFormat {
+    pub base: StringFormat,
+    pub unit: DurationUnit,
+}
+
+/// Format of `%T`, which prints a unix timestamp as date and time in UTC.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("ts", Value::Numeric(1700000000.0));
+/// let values: MapResolver = values.into();
+///
+/// let statement = r#""%T|%{:%d.%m.%Y %H:%M}T|%-12{:%Y}T|", ts, ts, ts"#;
+/// assert!(parse_format_string(statement).is_err());
+///
+/// let statement = r#""%T|%{:%d.%m.%Y %H:%M}T|%{:%Y}-6T|", ts, ts, ts"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "2023-11-14 22:13:20|14.11.2023 22:13|2023  |");
+///
+/// assert!(parse_format_string(r#""%{:%Y}d", ts"#).is_err());
+/// assert!(parse_format_string(r#""%{:%Q}T", ts"#).is_err());
+/// ```
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
+pub struct DateTimeFormat {
+    pub base: StringFormat,
+    /// strftime-style pattern like `%Y-%m-%d`, following `:` in the `{...}` block, e.g.
+    /// `%{:%H:%M}T`. Defaults to `%Y-%m-%d %H:%M:%S`.
+    pub pattern: Option<String>,
+}
+
+/// Transforms a value before it is formatted, e.g. the `upper` in `%{upper|trim}s`.
+///
+/// Filters work on the value printed as string, so `%{trim}d` also accepts `" 42 "`.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("name", Value::String("  Ada Lovelace ".into()));
+/// values.insert("count", Value::String(" 42 ".into()));
+/// let values: MapResolver = values.into();
+///
+/// let statement = r#""%{trim|upper}-14s|%{trim|reverse}s|%{lower}s|%{trim}04d", name, name, name, count"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "ADA LOVELACE  |ecalevoL adA|  ada lovelace |0042");
+///
+/// let parsed = parse_format_string(r#""%{slug}s", name"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &values).unwrap_err().root().to_string(), "Unknown filter 'slug'");
+/// assert!(parse_format_string(r#""%{trim|}s", name"#).is_err());
+///
+/// let statement = r#""%{plural:=0{no files} one{# file} other{# files}}s, %{select:admin{an admin} other{a user}|upper}s", count, role"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// for (count, role, printed) in [(0.0, "admin", "no files, AN ADMIN"), (1.0, "dev", "1 file, A USER"), (12.0, "dev", "12 files, A USER")] {
+///     let values: MapResolver = HashMap::from([("count", Value::Numeric(count)), ("role", role.into())]).into();
+///     assert_eq!(sprintf(&parsed, &values).unwrap(), printed);
+/// }
+/// assert!(parse_format_string(r#""%{plural:one{# file}}s", count"#).is_err());
+/// assert!(parse_format_string(r#""%{plural:some{# files} other{# files}}s", count"#).is_err());
+/// // only English categories, and no nested messages
+/// assert!(parse_format_string(r#""%{plural:few{# files} other{# files}}s", count"#).is_err());
+/// assert!(parse_format_string(r#""%{plural:one{{#} file} other{# files}}s", count"#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    Upper,
+    Lower,
+    /// Removes leading and trailing whitespace.
+    Trim,
+    /// Reverses the characters.
+    Reverse,
+    /// A filter registered with [crate::formatter::Formatter::register_filter].
+    Custom(String),
+    /// Chooses a message by the plural category of a number like `%{plural:one{# file} other{# files}}s`,
+    /// `#` is replaced by the number. A key `=N` matches exactly `N`, else the English categories
+    /// `one` for 1 and `other` for everything else apply, other categories are rejected.
+    ///
+    /// Unlike in ICU MessageFormat, messages are plain text: `#` is their only substitution, so
+    /// placeholders like `%d` are printed as they are and nested messages are rejected.
+    Plural(Vec<Branch>),
+    /// Chooses a message by the value like `%{select:admin{an admin} other{a user}}s`. Like for
+    /// [Filter::Plural], the messages are plain text.
+    Select(Vec<Branch>),
+}
+
+/// A message of a [Filter::Plural] or [Filter::Select] and the key choosing it, like `one{# file}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Branch {
+    pub key: String,
+    pub message: String,
+}
+
+impl Filter {
+    pub fn name(&self) -> &str {
+        match self {
+            Filter::Upper => "upper",
+            Filter::Lower => "lower",
+            Filter::Trim => "trim",
+            Filter::Reverse => "reverse",
+            Filter::Custom(name) => name,
+            Filter::Plural(_) => "plural",
+            Filter::Select(_) => "select",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "trim" => Filter::Trim,
+            "reverse" => Filter::Reverse,
+            name => Filter::Custom(name.into()),
+        }
+    }
+}
+
+/// The `{...}` block following the `%` of a placeholder like `%{upper|trim}-8s` or `%{:-unknown}s`.
+#[derive(Debug, Clone, PartialEq, Hash, Default)]
+pub struct Modifiers {
+    /// Applied from left to right to the value printed as string.
+    pub filters: Vec<Filter>,
+    /// Printed instead of a variable which can't be resolved, following `:-`.
+    pub default: Option<String>,
+    /// Separator of the elements of a sequence variable, which are printed one by one like
+    /// `%{join{, }}d`, see [crate::parser::Placeholder::join].
+    pub join: Option<String>,
+}
+
+/// An ANSI text style like the `bold` and `red` in `%[bold red]`.
+///
+/// Styles are printed as escape sequences unless [crate::options::PrintOptions::strip_styles] is set,
+/// e.g. when `std::io::stdout().is_terminal()` is false.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     options::PrintOptions,
+///     parser::parse_format_string,
+///     printer::*,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("status", Value::String("failed".into()));
+/// let values: MapResolver = values.into();
+///
+/// let statement = r#""%[bold red]%s%[reset] after 3 tries", status"#;
+/// let parsed = parse_format_string(statement).unwrap();
+/// assert_eq!(parsed.to_string(), statement);
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), "\x1b[1;31mfailed\x1b[0m after 3 tries");
+///
+/// let options = PrintOptions {
+///     strip_styles: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(sprintf_with_options(&parsed, &values, &options).unwrap(), "failed after 3 tries");
+///
+/// assert!(parse_format_string(r#""%[blink]%s", status"#).is_err());
+/// assert!(parse_format_string(r#""%[]%s", status"#).is_err());
+/// assert!(parse_format_string(r#""%[bold%s", status"#).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Resets all styles.
+    Reset,
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Style {
+    pub const ALL: &'static [Style] = &[
+        Style::Reset,
+        Style::Bold,
+        Style::Dim,
+        Style::Italic,
+        Style::Underline,
+        Style::Black,
+        Style::Red,
+        Style::Green,
+        Style::Yellow,
+        Style::Blue,
+        Style::Magenta,
+        Style::Cyan,
+        Style::White,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Reset => "reset",
+            Style::Bold => "bold",
+            Style::Dim => "dim",
+            Style::Italic => "italic",
+            Style::Underline => "underline",
+            Style::Black => "black",
+            Style::Red => "red",
+            Style::Green => "green",
+            Style::Yellow => "yellow",
+            Style::Blue => "blue",
+            Style::Magenta => "magenta",
+            Style::Cyan => "cyan",
+            Style::White => "white",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|style| style.name() == name)
+    }
+
+    /// The SGR parameter of the style, e.g. `31` for red.
+    pub fn code(self) -> u8 {
+        match self {
+            Style::Reset => 0,
+            Style::Bold => 1,
+            Style::Dim => 2,
+            Style::Italic => 3,
+            Style::Underline => 4,
+            Style::Black => 30,
+            Style::Red => 31,
+            Style::Green => 32,
+            Style::Yellow => 33,
+            Style::Blue => 34,
+            Style::Magenta => 35,
+            Style::Cyan => 36,
+            Style::White => 37,
+        }
+    }
+}
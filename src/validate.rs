@@ -0,0 +1,83 @@
+use crate::{
+    error::SprintfError,
+    parser::{check_arity, Entry, ParsedFormat},
+    printer::{check_value, resolve_elements, resolve_placeholder, skipped_sections},
+    value::Resolver,
+};
+
+/// A problem found by [ParsedFormat::validate].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The variable the problem belongs to, `None` if it concerns the whole template.
+    pub variable: Option<String>,
+    pub error: SprintfError,
+}
+
+impl ParsedFormat<'_> {
+    /// Checks that every variable resolves to a value its placeholder can print, without printing
+    /// anything. All problems are collected instead of stopping at the first one.
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     error::SprintfError,
+    ///     parser::parse_format_string,
+    ///     value::{MapResolver, Value},
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("user", Value::String("foo".into()));
+    /// values.insert("duration", Value::String("slow".into()));
+    /// let values: MapResolver = values.into();
+    ///
+    /// let parsed = parse_format_string(r#""%s took %.2fs at %v", user, duration, time"#).unwrap();
+    /// let errors = parsed.validate(&values).unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(errors[0].variable.as_deref(), Some("duration"));
+    /// assert_eq!(errors[0].error, SprintfError::TypeMismatch { expected: "numeric" });
+    /// assert_eq!(errors[1].error, SprintfError::UnresolvedVariable("time".into()));
+    ///
+    /// let parsed = parse_format_string(r#""%v took %v", user, duration"#).unwrap();
+    /// assert!(parsed.validate(&values).is_ok());
+    /// ```
+    pub fn validate(&self, resolver: &impl Resolver) -> Result<(), Vec<ValidationError>> {
+        if let Err(error) = check_arity(&self.entries, &self.variables) {
+            return Err(vec![ValidationError { variable: None, error }]);
+        }
+
+        // skipped sections aren't printed
+        let skipped = skipped_sections(&self.entries, |variable| resolver.resolve(variable).is_some());
+        let placeholders = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| match entry {
+                Entry::Placeholder(placeholder) => {
+                    Some((placeholder, skipped.iter().any(|range| range.contains(&index))))
+                }
+                Entry::Text(_) | Entry::Style(_) | Entry::Section(_) | Entry::SectionEnd(_) => None,
+            });
+        let errors: Vec<_> = placeholders
+            .zip(self.variables.iter().zip(&self.arguments))
+            .filter(|((_, skip), _)| !skip)
+            .filter_map(|((placeholder, _), (variable, argument))| {
+                let checked = match placeholder.join() {
+                    Some(_) => resolve_elements(placeholder, variable, resolver)
+                        .and_then(|elements| elements.iter().try_for_each(|value| check_value(placeholder, value))),
+                    None => resolve_placeholder(placeholder, variable, argument, resolver)
+                        .and_then(|value| check_value(placeholder, &value)),
+                };
+                checked.err().map(|error| ValidationError {
+                    variable: Some(variable.clone()),
+                    error,
+                })
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! The values templates print and the resolvers they are looked up by.
+//!
+//! Domain types become printable by implementing [Resolver]:
+//! ```
+//! use sprintf::{
+//!     parser::parse_format_string,
+//!     printer::sprintf,
+//!     value::{Resolver, Value},
+//! };
+//!
+//! struct Reading {
+//!     sensor: Value,
+//!     celsius: Value,
+//! }
+//!
+//! impl Resolver for Reading {
+//!     fn resolve(&self, name: &str) -> Option<&Value> {
+//!         match name {
+//!             "sensor" => Some(&self.sensor),
+//!             "celsius" => Some(&self.celsius),
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//!
+//! let reading = Reading {
+//!     sensor: Value::String("kitchen".into()),
+//!     celsius: Value::Numeric(21.5),
+//! };
+//! let parsed = parse_format_string(r#""%s: %.1f°C", sensor, celsius"#).unwrap();
+//! assert_eq!(sprintf(&parsed, &reading).unwrap(), "kitchen: 21.5°C");
+//! ```
+//!
+//! With the `metrics_evaluation` feature, values of the
+//! [metrics_evaluation](https://github.com/Dirk007/metrics_evaluation.git) crate convert into [Value]
+//! and its resolvers are adapted by [MetricsResolver].
+
+use std::{collections::HashMap, fmt};
+
+/// A value of a variable. Rust numbers convert into [Value::Integer] and [Value::Numeric]:
+/// ```
+/// use std::collections::HashMap;
+///
+/// use sprintf::{
+///     parser::parse_format_string,
+///     printer::sprintf,
+///     value::{MapResolver, Value},
+/// };
+///
+/// let mut values = HashMap::new();
+/// values.insert("lossy", Value::Numeric(u64::MAX as f64));
+/// values.insert("exact", u64::MAX.into());
+/// values.insert("id", (-9_007_199_254_740_993i64).into());
+/// values.insert("min", Value::Integer(i128::MIN));
+/// values.insert("ratio", 1.5f32.into());
+/// values.insert("name", "foo".into());
+/// values.insert("zip", "01234".into());
+/// values.insert("huge", Value::Decimal("-123456789012345678901234567890123456789012.5".into()));
+/// let values: MapResolver = values.into();
+///
+/// let parsed = parse_format_string(r#""%d %d %x %d %.1f", lossy, exact, exact, id, exact"#).unwrap();
+/// assert_eq!(
+///     sprintf(&parsed, &values).unwrap(),
+///     "18446744073709551616 18446744073709551615 ffffffffffffffff -9007199254740993 18446744073709551615.0"
+/// );
+///
+/// let parsed = parse_format_string(r#""%050d %.2f", min, ratio"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &values).unwrap(), format!("{:050} 1.50", i128::MIN));
+///
+/// let parsed = parse_format_string(r#""%d %.0f", huge, huge"#).unwrap();
+/// assert_eq!(
+///     sprintf(&parsed, &values).unwrap(),
+///     "-123456789012345678901234567890123456789012 -123456789012345678901234567890123456789013"
+/// );
+///
+/// assert!(sprintf(&parse_format_string(r#""%d", name"#).unwrap(), &values).is_err());
+/// assert!(sprintf(&parse_format_string(r#""%d", zip"#).unwrap(), &values).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    /// An integer, which unlike [Value::Numeric] prints exactly beyond the 2^53 an `f64` holds
+    /// without loss, e.g. for large IDs and counters.
+    Integer(i128),
+    Numeric(f64),
+    /// A decimal number held exactly as its digits like `-123.45`, e.g. from
+    /// [crate::printer::bigint_value]. `%d`, `%f` and `%p` print it without the rounding errors of
+    /// [Value::Numeric], while a [Value::String] of the same digits is no number at all. `%s` prints
+    /// the digits as they are.
+    Decimal(String),
+    Bool(bool),
+    /// The elements of a list or array, see [crate::fields::collect_composite]. Printed Go-style by
+    /// `%v` like `[80 443]`.
+    Sequence(Vec<Value>),
+    /// The fields of a struct or object in their order. Printed Go-style by `%v` like
+    /// `{foo [80 443]}`, and with the field names by `%+v` like `{name:foo ports:[80 443]}`, which
+    /// is the alternate form `{:#}` of [fmt::Display].
+    Map(Vec<(String, Value)>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{s}"),
+            Value::Integer(n) => write!(f, "{n}"),
+            Value::Numeric(n) => write!(f, "{n}"),
+            Value::Decimal(digits) => write!(f, "{digits}"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Sequence(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    match f.alternate() {
+                        true => write!(f, "{element:#}")?,
+                        false => write!(f, "{element}")?,
+                    }
+                }
+                write!(f, "]")
+            }
+            Value::Map(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, field)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    match f.alternate() {
+                        true => write!(f, "{name}:{field:#}")?,
+                        false => write!(f, "{field}")?,
+                    }
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+macro_rules! integer_from {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::Integer(value as i128)
+            }
+        }
+    )*};
+}
+
+integer_from!(i8, i16, i32, i64, i128, u8, u16, u32, u64, isize, usize);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Numeric(value.into())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Numeric(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+/// Looks up the value of a variable by its name.
+pub trait Resolver {
+    fn resolve(&self, name: &str) -> Option<&Value>;
+}
+
+/// Resolves variables from a map of their names to their values.
+#[derive(Debug, Clone, Default)]
+pub struct MapResolver {
+    map: HashMap<String, Value>,
+}
+
+impl From<HashMap<&str, Value>> for MapResolver {
+    fn from(map: HashMap<&str, Value>) -> Self {
+        Self {
+            map: map.into_iter().map(|(name, value)| (name.to_string(), value)).collect(),
+        }
+    }
+}
+
+impl Resolver for MapResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.map.get(name)
+    }
+}
+
+#[cfg(feature = "metrics_evaluation")]
+pub use self::metrics::MetricsResolver;
+
+#[cfg(feature = "metrics_evaluation")]
+mod metrics {
+    use super::Value;
+    use crate::resolvers::OwnedResolver;
+
+    /// Values without a counterpart, like times, are converted to the string they display as.
+    impl From<metrics_evaluation::Value> for Value {
+        fn from(value: metrics_evaluation::Value) -> Self {
+            match value {
+                metrics_evaluation::Value::String(s) => Value::String(s),
+                metrics_evaluation::Value::Numeric(n) => Value::Numeric(n),
+                metrics_evaluation::Value::Bool(b) => Value::Bool(b),
+                #[allow(unreachable_patterns)]
+                other => Value::String(other.to_string()),
+            }
+        }
+    }
+
+    /// Adapts a resolver of metrics_evaluation. As its values have to be converted, it is an
+    /// [OwnedResolver], e.g. for [crate::resolvers::ComputedResolver]:
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use sprintf::{
+    ///     parser::parse_format_string, printer::sprintf, resolvers::ComputedResolver, value::MetricsResolver,
+    /// };
+    ///
+    /// let mut values = HashMap::new();
+    /// values.insert("user", metrics_evaluation::Value::String("foo".into()));
+    /// values.insert("took", metrics_evaluation::Value::Numeric(1.5));
+    /// let metrics: metrics_evaluation::MapResolver = values.into();
+    ///
+    /// let parsed = parse_format_string(r#""%s took %.2fs", user, took"#).unwrap();
+    /// let resolver = ComputedResolver::new(MetricsResolver(metrics));
+    /// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo took 1.50s");
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct MetricsResolver<R>(pub R);
+
+    impl<R: metrics_evaluation::Resolver> OwnedResolver for MetricsResolver<R> {
+        fn resolve_owned(&self, name: &str) -> Option<Value> {
+            // whether lent or owned, the value is converted from its own copy
+            self.0.resolve(name).map(|value| Value::from(value.clone()))
+        }
+    }
+}
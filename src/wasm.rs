@@ -0,0 +1,130 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{
+    fields::{collect_composite, join, Composite, Field, FieldResolver, Values},
+    parser::{parse_format_string, Entry},
+    printer::sprintf,
+};
+
+/// Values of a JS object by their dotted path, e.g. `user.name` or `items.0` for arrays, and objects
+/// and arrays also as a whole.
+struct JsFields<'a>(&'a JsValue);
+
+impl Field for JsFields<'_> {
+    fn collect(&self, path: &str, values: &mut Values) {
+        let value = self.0;
+        if let Some(number) = value.as_f64() {
+            number.collect(path, values);
+        } else if let Some(string) = value.as_string() {
+            string.collect(path, values);
+        } else if let Some(boolean) = value.as_bool() {
+            boolean.collect(path, values);
+        } else if let Some(object) = value.dyn_ref::<Object>() {
+            let mut names = Vec::new();
+            for entry in Object::entries(object).iter() {
+                let entry: Array = entry.unchecked_into();
+                if let Some(key) = entry.get(0).as_string() {
+                    JsFields(&entry.get(1)).collect(&join(path, &key), values);
+                    names.push(key);
+                }
+            }
+            let composite = match Array::is_array(value) {
+                true => Composite::Sequence,
+                false => Composite::Map,
+            };
+            collect_composite(path, composite, names.iter().map(String::as_str), values);
+        }
+    }
+}
+
+fn set(object: &Object, key: &str, value: impl Into<JsValue>) {
+    // setting a property of a plain object can't fail
+    let _ = Reflect::set(object, &key.into(), &value.into());
+}
+
+/// Offset of the byte `offset` of `input` in UTF-16 code units, as JS strings are indexed.
+fn utf16_offset(input: &str, offset: usize) -> usize {
+    input[..offset].encode_utf16().count()
+}
+
+/// Parses `statement` like [parse_format_string] into an array of its entries, e.g. to highlight
+/// them in an editor. Every entry is an object with `kind` (`text`, `placeholder` or `style`), its
+/// `text`, the bound `variable` of placeholders and its `start` and `end` in `statement`.
+#[wasm_bindgen]
+pub fn parse(statement: &str) -> Result<Array, JsError> {
+    let parsed = parse_format_string(statement).map_err(|error| JsError::new(&error.to_string()))?;
+    let mut variables = parsed.variables.iter();
+    let entries = Array::new();
+    for (entry, span) in parsed.entries.iter().zip(&parsed.spans) {
+        let object = Object::new();
+        match entry {
+            Entry::Text(text) => {
+                set(&object, "kind", "text");
+                set(&object, "text", text.as_ref());
+            }
+            Entry::Placeholder(placeholder) => {
+                set(&object, "kind", "placeholder");
+                set(&object, "text", placeholder.to_string());
+                if let Some(variable) = variables.next() {
+                    set(&object, "variable", variable.as_str());
+                }
+            }
+            Entry::Style(_) => {
+                set(&object, "kind", "style");
+                set(&object, "text", entry.to_string());
+            }
+            Entry::Section(variable) => {
+                set(&object, "kind", "section");
+                set(&object, "text", entry.to_string());
+                set(&object, "variable", variable.as_str());
+            }
+            Entry::SectionEnd(variable) => {
+                set(&object, "kind", "end");
+                set(&object, "text", entry.to_string());
+                set(&object, "variable", variable.as_str());
+            }
+        }
+        set(&object, "start", utf16_offset(statement, span.start) as f64);
+        set(&object, "end", utf16_offset(statement, span.end) as f64);
+        entries.push(&object);
+    }
+    Ok(entries)
+}
+
+/// Checks `statement` against the JS object `values` like [crate::parser::ParsedFormat::validate].
+/// Returns an array of the problems, empty if there are none. Every problem is an object with its
+/// `message` and the `variable` it belongs to, which is missing if it concerns the whole template.
+#[wasm_bindgen]
+pub fn validate(statement: &str, values: &JsValue) -> Array {
+    let problems = Array::new();
+    let errors = match parse_format_string(statement) {
+        Ok(parsed) => parsed
+            .validate(&FieldResolver::new(&JsFields(values)))
+            .err()
+            .unwrap_or_default(),
+        Err(error) => {
+            let object = Object::new();
+            set(&object, "message", error.to_string());
+            problems.push(&object);
+            return problems;
+        }
+    };
+    for error in errors {
+        let object = Object::new();
+        set(&object, "message", error.error.to_string());
+        if let Some(variable) = error.variable {
+            set(&object, "variable", variable);
+        }
+        problems.push(&object);
+    }
+    problems
+}
+
+/// Prints `statement` with the variables resolved from the JS object `values`, e.g.
+/// `format('"%s took %.2fs", user.name, took', { user: { name: "foo" }, took: 1.5 })`.
+#[wasm_bindgen]
+pub fn format(statement: &str, values: &JsValue) -> Result<String, JsError> {
+    let parsed = parse_format_string(statement).map_err(|error| JsError::new(&error.to_string()))?;
+    sprintf(&parsed, &FieldResolver::new(&JsFields(values))).map_err(|error| JsError::new(&error.to_string()))
+}
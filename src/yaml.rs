@@ -0,0 +1,73 @@
+use crate::{
+    fields::{collect_composite, join, Composite, Field, FieldResolver, Values},
+    value::{Resolver, Value},
+};
+
+/// Like JSON, mappings add their fields by dotted paths and sequences their elements by index, both
+/// also as a whole.
+/// Numbers and booleans as keys are used by their text, other keys are left out. Tags are ignored.
+impl Field for serde_yaml::Value {
+    fn collect(&self, path: &str, values: &mut Values) {
+        match self {
+            serde_yaml::Value::Null => {}
+            serde_yaml::Value::Bool(boolean) => boolean.collect(path, values),
+            serde_yaml::Value::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    integer.collect(path, values);
+                } else if let Some(integer) = number.as_u64() {
+                    integer.collect(path, values);
+                } else if let Some(number) = number.as_f64() {
+                    number.collect(path, values);
+                }
+            }
+            serde_yaml::Value::String(string) => string.collect(path, values),
+            serde_yaml::Value::Sequence(elements) => elements.as_slice().collect(path, values),
+            serde_yaml::Value::Mapping(fields) => {
+                let mut names = Vec::new();
+                for (key, field) in fields.iter() {
+                    let name = match key {
+                        serde_yaml::Value::String(name) => name.clone(),
+                        serde_yaml::Value::Bool(boolean) => boolean.to_string(),
+                        serde_yaml::Value::Number(number) => number.to_string(),
+                        _ => continue,
+                    };
+                    field.collect(&join(path, &name), values);
+                    names.push(name);
+                }
+                collect_composite(path, Composite::Map, names.iter().map(String::as_str), values);
+            }
+            serde_yaml::Value::Tagged(tagged) => tagged.value.collect(path, values),
+        }
+    }
+}
+
+/// Resolves variables like `server.name` or `listen.0.port` from a YAML document, which is
+/// converted once when the resolver is created.
+/// ```
+/// use sprintf::{parser::parse_format_string, printer::sprintf, yaml::YamlResolver};
+///
+/// let config = "
+/// server:
+///   name: foo
+///   listen:
+///     - port: 8080
+///     - port: 8443
+/// ";
+/// let resolver = YamlResolver::new(&serde_yaml::from_str(config).unwrap());
+/// let parsed = parse_format_string(r#""%s listens on %d and %d", server.name, server.listen.0.port, server.listen.1.port"#).unwrap();
+/// assert_eq!(sprintf(&parsed, &resolver).unwrap(), "foo listens on 8080 and 8443");
+/// ```
+#[derive(Debug, Clone)]
+pub struct YamlResolver(FieldResolver);
+
+impl YamlResolver {
+    pub fn new(document: &serde_yaml::Value) -> Self {
+        Self(FieldResolver::new(document))
+    }
+}
+
+impl Resolver for YamlResolver {
+    fn resolve(&self, name: &str) -> Option<&Value> {
+        self.0.resolve(name)
+    }
+}